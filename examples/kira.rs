@@ -0,0 +1,41 @@
+use eframe::{
+    egui::{self, CentralPanel},
+    App, NativeOptions,
+};
+use egui_player::{audio_context::AudioContext, kira_backend::KiraBackend, player::Player};
+use kira::{AudioManager, AudioManagerSettings, DefaultBackend};
+
+struct MyApp {
+    player: Player,
+    // The player is heard for as long as the manager runs
+    _manager: AudioManager,
+}
+
+impl App for MyApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Playing through kira");
+            self.player.ui(ui);
+        });
+    }
+}
+
+fn main() -> eframe::Result {
+    let mut manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default())
+        .expect("could not start the kira audio manager");
+    let backend = KiraBackend::new(&mut manager).expect("could not play through kira");
+
+    let mut player = Player::from_path("assets/Dreamweaver.mp3");
+    player.set_audio_context(AudioContext::with_backend(backend));
+
+    eframe::run_native(
+        "Kira Example",
+        NativeOptions::default(),
+        Box::new(|_| {
+            Ok(Box::new(MyApp {
+                player,
+                _manager: manager,
+            }))
+        }),
+    )
+}
@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use crate::Spectrogram;
+
+/// Stores computed waveform peaks, spectrograms and loudness on disk so reopening a file does not decode it again
+///
+/// Entries are keyed by [`crate::media_information::content_hash`] and the resolution they were computed at.
+/// Every entry is a small header followed by little endian ``f32`` values
+#[derive(Debug, Clone)]
+pub(crate) struct AnalysisCache {
+    directory: PathBuf,
+}
+
+impl AnalysisCache {
+    pub(crate) fn new(directory: impl Into<PathBuf>) -> Self {
+        Self {
+            directory: directory.into(),
+        }
+    }
+
+    fn entry(&self, hash: &str, kind: &str, columns: usize, height: usize) -> PathBuf {
+        self.directory
+            .join(format!("{hash}-{kind}-{columns}x{height}.bin"))
+    }
+
+    pub(crate) fn load_peaks(&self, hash: &str, columns: usize) -> Option<Vec<f32>> {
+        let (_, values) = read_entry(&self.entry(hash, "peaks", columns, 1)).ok()?;
+        Some(values)
+    }
+
+    pub(crate) fn store_peaks(&self, hash: &str, columns: usize, peaks: &[f32]) -> io::Result<()> {
+        write_entry(&self.entry(hash, "peaks", columns, 1), 1, peaks)
+    }
+
+    /// Integrated loudness in LUFS, see [`crate::media_information::integrated_loudness`]
+    pub(crate) fn load_loudness(&self, hash: &str) -> Option<f32> {
+        let (_, values) = read_entry(&self.entry(hash, "loudness", 1, 1)).ok()?;
+        values.first().copied()
+    }
+
+    pub(crate) fn store_loudness(&self, hash: &str, loudness: f32) -> io::Result<()> {
+        write_entry(&self.entry(hash, "loudness", 1, 1), 1, &[loudness])
+    }
+
+    pub(crate) fn load_spectrogram(
+        &self,
+        hash: &str,
+        columns: usize,
+        bins: usize,
+    ) -> Option<Spectrogram> {
+        let (height, values) = read_entry(&self.entry(hash, "spectrogram", columns, bins)).ok()?;
+        Some(Spectrogram {
+            width: values.len() / height.max(1),
+            height,
+            values,
+        })
+    }
+
+    pub(crate) fn store_spectrogram(
+        &self,
+        hash: &str,
+        columns: usize,
+        spectrogram: &Spectrogram,
+    ) -> io::Result<()> {
+        write_entry(
+            &self.entry(hash, "spectrogram", columns, spectrogram.height),
+            spectrogram.height,
+            &spectrogram.values,
+        )
+    }
+}
+
+fn write_entry(path: &Path, height: usize, values: &[f32]) -> io::Result<()> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let mut bytes = Vec::with_capacity(4 + values.len() * 4);
+    bytes.extend((height as u32).to_le_bytes());
+    bytes.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+    // Writing to a temporary file first keeps a crash from leaving a truncated entry behind
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, bytes)?;
+    fs::rename(temporary, path)
+}
+
+fn read_entry(path: &Path) -> io::Result<(usize, Vec<f32>)> {
+    let mut bytes = vec![];
+    fs::File::open(path)?.read_to_end(&mut bytes)?;
+    if bytes.len() < 4 || (bytes.len() - 4) % 4 != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "corrupt analysis cache entry",
+        ));
+    }
+    let height = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize;
+    let values = bytes[4..]
+        .chunks_exact(4)
+        .map(|value| f32::from_le_bytes([value[0], value[1], value[2], value[3]]))
+        .collect();
+    Ok((height, values))
+}
@@ -0,0 +1,734 @@
+use rodio::{
+    cpal::{
+        self,
+        traits::{HostTrait, StreamTrait},
+        BufferSize, BuildStreamError, FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+        SupportedBufferSize,
+    },
+    dynamic_mixer::{self, DynamicMixer, DynamicMixerController},
+    source::UniformSourceIterator,
+    Device, DeviceTrait, Sink, Source, StreamError, SupportedStreamConfig,
+};
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        mpsc, Arc, Mutex, OnceLock,
+    },
+    thread::{self, Thread},
+    time::Duration,
+};
+
+use crate::clock::{Clock, SystemClock};
+
+/// Audio that is handed to an [`AudioBackend`], interleaved samples along with their channel count and sample rate
+pub type BackendSource = Box<dyn Source<Item = f32> + Send>;
+
+/// Somewhere an [`crate::audio_context::AudioContext`] sends the audio of its players
+///
+/// [`RodioBackend`] plays through the default output device, [`NullBackend`] works without one and
+/// [`PullBackend`] hands the audio to an engine the app already runs.
+/// Implement this to play through any other audio library
+pub trait AudioBackend: Send + Sync {
+    /// Starts playing ``source`` right away, playback stops when the returned handle is dropped
+    fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle>;
+}
+
+impl fmt::Debug for dyn AudioBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AudioBackend")
+    }
+}
+
+/// Controls a source that is being played by an [`AudioBackend`]
+pub trait PlaybackHandle: Send {
+    /// Between ``0.0`` and ``1.0``
+    fn set_volume(&self, volume: f32);
+
+    /// How much of the source has been played
+    fn position(&self) -> Duration;
+
+    /// Whether the whole source has been played
+    fn is_finished(&self) -> bool;
+
+    /// Whether the output stopped working while playing, for example because the device was unplugged
+    ///
+    /// Players stop and show [`crate::player::PlayerState::Error`] once this is true
+    fn has_failed(&self) -> bool {
+        false
+    }
+
+    /// Whether the backend switched to another output device, the source has to be played again to be heard on it
+    ///
+    /// Players start over from their current position once this is true
+    fn has_moved(&self) -> bool {
+        false
+    }
+}
+
+/// Wait before the [`RodioBackend`] first checks that its output device is still the default, after opening or switching it
+const DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest wait between two checks of the output device, the wait doubles up to this while the device stays the same
+const MAX_DEVICE_CHECK_INTERVAL: Duration = Duration::from_secs(8);
+
+/// Size of the buffer the output device plays from, set with [`RodioBackend::open_with_latency`]
+///
+/// Sound starts playing one buffer after it is handed to the device, so smaller buffers react faster to clicks but
+/// may crackle on a busy machine
+///
+/// ``Default``: Lets the system decide, safe for normal playback
+///
+/// ``Low``: Buffers of about 5 milliseconds, for interactive apps such as soundboards
+///
+/// ``Frames``: Buffers of exactly this many frames
+///
+/// Sizes are kept within what the device supports, and the system default is used if a device refuses the size
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum OutputLatency {
+    #[default]
+    Default,
+    Low,
+    Frames(u32),
+}
+
+impl OutputLatency {
+    pub(crate) fn buffer_size(self, supported: &SupportedStreamConfig) -> BufferSize {
+        let frames = match self {
+            OutputLatency::Default => return BufferSize::Default,
+            OutputLatency::Low => supported.sample_rate().0 / 200,
+            OutputLatency::Frames(frames) => frames,
+        };
+        match supported.buffer_size() {
+            SupportedBufferSize::Range { min, max } => BufferSize::Fixed(frames.clamp(*min, *max)),
+            SupportedBufferSize::Unknown => BufferSize::Fixed(frames),
+        }
+    }
+}
+
+/// Mixer of an open output stream, sources added to it are played right away
+type OutputMixer = Arc<DynamicMixerController<f32>>;
+
+/// Output stream of a [`RodioBackend`], replaced whenever the default device changes
+#[derive(Default)]
+struct RodioOutput {
+    /// ``None`` while there is no device to play through
+    mixer: Mutex<Option<OutputMixer>>,
+    /// Goes up every time the stream is replaced or lost, playback started on an older stream has to start again
+    generation: AtomicU64,
+    /// Set by the stream when the device reports an error
+    broken: AtomicBool,
+    /// Set once the backend is dropped
+    closed: AtomicBool,
+    /// Thread that owns the stream, woken up when the device reports an error or the backend is dropped
+    monitor: OnceLock<Thread>,
+}
+
+impl RodioOutput {
+    fn wake_monitor(&self) {
+        if let Some(monitor) = self.monitor.get() {
+            monitor.unpark();
+        }
+    }
+}
+
+/// Plays through the default output device with rodio
+///
+/// The backend follows the default device of the system, so when Bluetooth headphones connect or a USB headset is
+/// unplugged it opens the new default device. Sources that were playing on the old device report [`PlaybackHandle::has_moved`]
+/// and their players carry on from the same position on the new one. When no device is left they report [`PlaybackHandle::has_failed`]
+///
+/// An unplugged device is noticed right away, a new default device within a few seconds
+pub struct RodioBackend {
+    output: Arc<RodioOutput>,
+}
+
+/// Streams the samples of ``mixer`` to ``device`` in its own sample format
+fn build_output_stream<T: SizedSample + FromSample<f32>>(
+    device: &Device,
+    config: &StreamConfig,
+    mut mixer: DynamicMixer<f32>,
+    output: Arc<RodioOutput>,
+) -> Result<Stream, BuildStreamError> {
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| {
+            for sample in data {
+                *sample = T::from_sample(mixer.next().unwrap_or(0.0));
+            }
+        },
+        move |error| {
+            log::error!("Audio output error: {error}");
+            if let cpal::StreamError::DeviceNotAvailable = error {
+                output.broken.store(true, Ordering::Release);
+                output.wake_monitor();
+            }
+        },
+        None,
+    )
+}
+
+/// Opens an output stream on ``device`` with buffers sized after ``latency``
+fn open_output(
+    device: &Device,
+    latency: OutputLatency,
+    output: &Arc<RodioOutput>,
+) -> Result<(Stream, OutputMixer), StreamError> {
+    let supported = device
+        .default_output_config()
+        .map_err(StreamError::DefaultStreamConfigError)?;
+    let mut config = supported.config();
+    config.buffer_size = latency.buffer_size(&supported);
+    let build = |config: &StreamConfig| {
+        let (controller, mixer) = dynamic_mixer::mixer(config.channels, config.sample_rate.0);
+        let output = Arc::clone(output);
+        let stream = match supported.sample_format() {
+            SampleFormat::I16 => build_output_stream::<i16>(device, config, mixer, output),
+            SampleFormat::U16 => build_output_stream::<u16>(device, config, mixer, output),
+            SampleFormat::I32 => build_output_stream::<i32>(device, config, mixer, output),
+            _ => build_output_stream::<f32>(device, config, mixer, output),
+        };
+        stream.map(|stream| (stream, controller))
+    };
+    let (stream, controller) = match build(&config) {
+        Err(error) if config.buffer_size != BufferSize::Default => {
+            log::warn!("The output device refused {latency:?} buffers, using its default: {error}");
+            config.buffer_size = BufferSize::Default;
+            build(&config)
+        }
+        result => result,
+    }
+    .map_err(StreamError::BuildStreamError)?;
+    stream.play().map_err(StreamError::PlayStreamError)?;
+    Ok((stream, controller))
+}
+
+/// Opens the default output device, along with its name when it is known
+///
+/// Falls back to any other device that works, whose name is not kept
+fn open_default_output(
+    latency: OutputLatency,
+    output: &Arc<RodioOutput>,
+) -> Result<(Stream, OutputMixer, Option<String>), StreamError> {
+    let host = cpal::default_host();
+    let device = host.default_output_device().ok_or(StreamError::NoDevice)?;
+    let name = device.name().ok();
+    open_output(&device, latency, output)
+        .map(|(stream, mixer)| (stream, mixer, name))
+        .or_else(|error| {
+            host.output_devices()
+                .map_err(|_| StreamError::NoDevice)?
+                .find_map(|device| open_output(&device, latency, output).ok())
+                .map(|(stream, mixer)| (stream, mixer, None))
+                .ok_or(error)
+        })
+}
+
+/// Name of the output device currently picked as the default by the system
+fn default_output_name() -> Option<String> {
+    cpal::default_host()
+        .default_output_device()
+        .and_then(|device| device.name().ok())
+}
+
+impl RodioBackend {
+    /// Opens the default output device
+    pub fn open() -> Result<Self, StreamError> {
+        Self::open_with_latency(OutputLatency::default())
+    }
+
+    /// Opens the default output device with buffers sized after ``latency``, see [`OutputLatency`]
+    pub fn open_with_latency(latency: OutputLatency) -> Result<Self, StreamError> {
+        let (opened_sender, opened_receiver) = mpsc::channel();
+        let output = Arc::new(RodioOutput::default());
+        let thread_output = Arc::clone(&output);
+        // The stream can not be moved between threads, so it lives on its own thread for as long as the backend does
+        let monitor = thread::spawn(move || {
+            let (mut stream, mut device_name) = match open_default_output(latency, &thread_output) {
+                Ok((stream, mixer, name)) => {
+                    log::info!("Opened the default audio output");
+                    *thread_output.mixer.lock().unwrap() = Some(mixer);
+                    opened_sender.send(Ok(())).unwrap();
+                    (Some(stream), name)
+                }
+                Err(error) => {
+                    log::error!("Could not open the default audio output: {error}");
+                    opened_sender.send(Err(error)).unwrap();
+                    return;
+                }
+            };
+            let mut interval = DEVICE_CHECK_INTERVAL;
+            loop {
+                thread::park_timeout(interval);
+                if thread_output.closed.load(Ordering::Acquire) {
+                    break;
+                }
+                interval = (interval * 2).min(MAX_DEVICE_CHECK_INTERVAL);
+                // Only the name of the default device is queried, an unplugged device is noticed through the error
+                // of its stream or because the system picked another default
+                let default_name = default_output_name();
+                let lost = thread_output.broken.swap(false, Ordering::AcqRel)
+                    || stream.is_none()
+                    || (device_name.is_some() && default_name.is_none());
+                // Only a device that was opened by name is followed, the name of a fallback device is not known
+                let default_changed = device_name.is_some() && default_name != device_name;
+                if !lost && !default_changed {
+                    continue;
+                }
+                let had_stream = stream.is_some();
+                match open_default_output(latency, &thread_output) {
+                    Ok((new_stream, mixer, name)) => {
+                        log::info!("Switched to the default audio output {name:?}");
+                        interval = DEVICE_CHECK_INTERVAL;
+                        *thread_output.mixer.lock().unwrap() = Some(mixer);
+                        stream = Some(new_stream);
+                        device_name = name;
+                    }
+                    Err(error) if lost && had_stream => {
+                        log::warn!(
+                            "Lost the audio output device and no other one is available: {error}"
+                        );
+                        *thread_output.mixer.lock().unwrap() = None;
+                        stream = None;
+                    }
+                    Err(_) => continue,
+                }
+                if had_stream {
+                    thread_output.generation.fetch_add(1, Ordering::AcqRel);
+                }
+            }
+            log::debug!("Closed the audio output");
+        });
+        let _ = output.monitor.set(monitor.thread().clone());
+        opened_receiver.recv().unwrap()?;
+        Ok(Self { output })
+    }
+}
+
+impl Drop for RodioBackend {
+    /// Closes the stream on the thread that owns it
+    fn drop(&mut self) {
+        self.output.closed.store(true, Ordering::Release);
+        self.output.wake_monitor();
+    }
+}
+
+impl AudioBackend for RodioBackend {
+    fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle> {
+        let generation = self.output.generation.load(Ordering::Acquire);
+        let Some(mixer) = self.output.mixer.lock().unwrap().clone() else {
+            log::warn!("Could not play, there is no audio output");
+            return Box::new(FailedPlayback);
+        };
+        let (sink, queue) = Sink::new_idle();
+        mixer.add(queue);
+        sink.append(source);
+        Box::new(RodioPlayback {
+            sink,
+            generation,
+            output: Arc::clone(&self.output),
+        })
+    }
+}
+
+/// Source played by the [`RodioBackend`]
+struct RodioPlayback {
+    sink: Sink,
+    /// Generation of the stream the sink plays on
+    generation: u64,
+    output: Arc<RodioOutput>,
+}
+
+impl PlaybackHandle for RodioPlayback {
+    fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume);
+    }
+
+    fn position(&self) -> Duration {
+        self.sink.position()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.sink.is_finished()
+    }
+
+    fn has_failed(&self) -> bool {
+        self.output.generation.load(Ordering::Acquire) != self.generation
+            && self.output.mixer.lock().unwrap().is_none()
+    }
+
+    fn has_moved(&self) -> bool {
+        self.output.generation.load(Ordering::Acquire) != self.generation
+            && self.output.mixer.lock().unwrap().is_some()
+    }
+}
+
+/// Handle of a source that could not be played because there is no output
+struct FailedPlayback;
+
+impl PlaybackHandle for FailedPlayback {
+    fn set_volume(&self, _volume: f32) {}
+
+    fn position(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn is_finished(&self) -> bool {
+        false
+    }
+
+    fn has_failed(&self) -> bool {
+        true
+    }
+}
+
+impl PlaybackHandle for Sink {
+    fn set_volume(&self, volume: f32) {
+        Sink::set_volume(self, volume);
+    }
+
+    fn position(&self) -> Duration {
+        self.get_pos()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.empty()
+    }
+}
+
+/// Backend without an output device, sources are pulled and thrown away at the pace they would be played at
+///
+/// Players keep time, visualize and measure their audio as usual, which makes this a fit for servers and CI
+/// machines without a sound card. The pace follows the system time, or the [`Clock`] of [`NullBackend::with_clock`]
+#[derive(Debug, Clone)]
+pub struct NullBackend {
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        Self::with_clock(SystemClock)
+    }
+}
+
+impl NullBackend {
+    /// Pulls the audio as time passes on ``clock``, so a player given the same clock with
+    /// [`crate::player::Player::set_clock`] runs out of audio when the clock reaches the end of it
+    pub fn with_clock(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Arc::new(clock),
+        }
+    }
+}
+
+/// How often the [`NullBackend`] pulls the audio that is due from its sources
+const NULL_BACKEND_TICK: Duration = Duration::from_millis(10);
+
+#[derive(Debug, Default)]
+struct NullPlayback {
+    played_nanos: AtomicU64,
+    finished: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// Handle of a source played by the [`NullBackend`], stops the source when dropped
+struct NullPlaybackHandle(Arc<NullPlayback>);
+
+impl AudioBackend for NullBackend {
+    fn play(&self, mut source: BackendSource) -> Box<dyn PlaybackHandle> {
+        let playback = Arc::new(NullPlayback::default());
+        let state = Arc::clone(&playback);
+        let clock = Arc::clone(&self.clock);
+        thread::spawn(move || {
+            let channels = source.channels().max(1) as usize;
+            let sample_rate = source.sample_rate().max(1) as f64;
+            let started = clock.now();
+            let mut played = 0_usize;
+            while !state.stopped.load(Ordering::Relaxed) {
+                // Everything up to the current time of the clock counts as played, whole frames at a time
+                let due = clock.now().saturating_duration_since(started);
+                let due_samples = (due.as_secs_f64() * sample_rate) as usize * channels;
+                let wanted = due_samples.saturating_sub(played);
+                let pulled = source.by_ref().take(wanted).count();
+                played += pulled;
+                state.played_nanos.store(
+                    (played as f64 / channels as f64 / sample_rate * 1e9) as u64,
+                    Ordering::Relaxed,
+                );
+                if pulled < wanted {
+                    break;
+                }
+                thread::sleep(NULL_BACKEND_TICK);
+            }
+            state.finished.store(true, Ordering::Relaxed);
+        });
+        Box::new(NullPlaybackHandle(playback))
+    }
+}
+
+impl PlaybackHandle for NullPlaybackHandle {
+    fn set_volume(&self, _volume: f32) {}
+
+    fn position(&self) -> Duration {
+        Duration::from_nanos(self.0.played_nanos.load(Ordering::Relaxed))
+    }
+
+    fn is_finished(&self) -> bool {
+        self.0.finished.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for NullPlaybackHandle {
+    fn drop(&mut self) {
+        self.0.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Playback state shared between a source in a [`Mixer`] and its [`PlaybackHandle`]
+#[derive(Debug)]
+pub(crate) struct Voice {
+    /// Bits of the ``f32`` volume
+    volume: AtomicU32,
+    played_frames: AtomicU64,
+    finished: AtomicBool,
+    stopped: AtomicBool,
+}
+
+/// A source being mixed, converted to the format of the mix
+struct MixedSource {
+    source: UniformSourceIterator<BackendSource, f32>,
+    voice: Arc<Voice>,
+}
+
+/// Sources mixed together by backends that fill the buffers of the output themselves
+#[derive(Default)]
+pub(crate) struct Mixer {
+    sources: Mutex<Vec<MixedSource>>,
+}
+
+impl Mixer {
+    /// Adds ``source`` to the mix, converted to ``channels`` and ``sample_rate``
+    pub(crate) fn add(&self, source: BackendSource, channels: u16, sample_rate: u32) -> Arc<Voice> {
+        let voice = Arc::new(Voice {
+            volume: AtomicU32::new(1.0_f32.to_bits()),
+            played_frames: AtomicU64::new(0),
+            finished: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+        });
+        self.sources.lock().unwrap().push(MixedSource {
+            source: UniformSourceIterator::new(source, channels, sample_rate),
+            voice: Arc::clone(&voice),
+        });
+        voice
+    }
+
+    /// Fills interleaved ``data`` with the next frames of every source, silence once none are left
+    ///
+    /// Runs on the audio callback so it never waits for the sources, while a source is being added the output is silent
+    pub(crate) fn fill<T: FromSample<f32>>(&self, data: &mut [T], channels: u16) {
+        let Ok(mut sources) = self.sources.try_lock() else {
+            for sample in data.iter_mut() {
+                *sample = T::from_sample_(0.0);
+            }
+            return;
+        };
+        for frame in data.chunks_mut(usize::from(channels.max(1))) {
+            for sample in frame.iter_mut() {
+                let mut mixed = 0.0;
+                for mixed_source in sources.iter_mut() {
+                    match mixed_source.source.next() {
+                        Some(value) => {
+                            let volume = mixed_source.voice.volume.load(Ordering::Relaxed);
+                            mixed += value * f32::from_bits(volume);
+                        }
+                        None => mixed_source.voice.finished.store(true, Ordering::Relaxed),
+                    }
+                }
+                *sample = T::from_sample_(mixed.clamp(-1.0, 1.0));
+            }
+            // Only frames a source actually played move its position, so it stops at the end
+            for mixed_source in sources.iter() {
+                if !mixed_source.voice.finished.load(Ordering::Relaxed) {
+                    mixed_source
+                        .voice
+                        .played_frames
+                        .fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+        sources.retain(|mixed_source| {
+            !mixed_source.voice.finished.load(Ordering::Relaxed)
+                && !mixed_source.voice.stopped.load(Ordering::Relaxed)
+        });
+    }
+}
+
+/// Handle of a source in a [`Mixer`], takes the source out of the mix when dropped
+pub(crate) struct MixerPlaybackHandle {
+    pub(crate) voice: Arc<Voice>,
+    pub(crate) sample_rate: u32,
+    /// Set once the output of the mix stops working
+    pub(crate) failed: Option<Arc<AtomicBool>>,
+}
+
+impl PlaybackHandle for MixerPlaybackHandle {
+    fn set_volume(&self, volume: f32) {
+        self.voice.volume.store(volume.to_bits(), Ordering::Relaxed);
+    }
+
+    fn position(&self) -> Duration {
+        Duration::from_secs_f64(
+            self.voice.played_frames.load(Ordering::Relaxed) as f64
+                / f64::from(self.sample_rate.max(1)),
+        )
+    }
+
+    fn is_finished(&self) -> bool {
+        self.voice.finished.load(Ordering::Relaxed)
+    }
+
+    fn has_failed(&self) -> bool {
+        self.failed
+            .as_ref()
+            .is_some_and(|failed| failed.load(Ordering::Acquire))
+    }
+}
+
+impl Drop for MixerPlaybackHandle {
+    fn drop(&mut self) {
+        self.voice.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Backend that leaves the output to the host, which pulls the mixed audio of the players from a [`PullOutput`]
+///
+/// Use it when the app already runs an audio engine, such as kira in a bevy or macroquad game, so the players do not
+/// open a competing stream on the device. Call [`PullOutput::fill`] from wherever the engine asks for samples
+/// and the audio of every player of the context ends up in its mix. For kira this is done by the ``KiraBackend``
+/// of the ``kira`` feature
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::audio_backend::PullBackend;
+/// use egui_player::audio_context::AudioContext;
+///
+/// let (backend, output) = PullBackend::new(2, 48000);
+/// let context = AudioContext::with_backend(backend);
+/// // Inside of the audio callback of the engine
+/// let mut buffer = [0.0; 1024];
+/// output.fill(&mut buffer);
+/// ```
+pub struct PullBackend {
+    mixer: Arc<Mixer>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+/// Mixed audio of a [`PullBackend`], cloning is cheap and every clone reads the same mix
+#[derive(Clone)]
+pub struct PullOutput {
+    mixer: Arc<Mixer>,
+    channels: u16,
+}
+
+impl PullBackend {
+    /// Mixes players into interleaved audio of ``channels`` at ``sample_rate``, the format the host engine plays
+    pub fn new(channels: u16, sample_rate: u32) -> (Self, PullOutput) {
+        let mixer = Arc::new(Mixer::default());
+        let channels = channels.max(1);
+        (
+            Self {
+                mixer: Arc::clone(&mixer),
+                channels,
+                sample_rate,
+            },
+            PullOutput { mixer, channels },
+        )
+    }
+}
+
+impl AudioBackend for PullBackend {
+    fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle> {
+        Box::new(MixerPlaybackHandle {
+            voice: self.mixer.add(source, self.channels, self.sample_rate),
+            sample_rate: self.sample_rate,
+            failed: None,
+        })
+    }
+}
+
+impl PullOutput {
+    /// Fills interleaved ``buffer`` with the next samples of the mix, players keep time by how much is pulled
+    pub fn fill(&self, buffer: &mut [f32]) {
+        self.mixer.fill(buffer, self.channels);
+    }
+}
+
+impl fmt::Debug for PullOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PullOutput")
+            .field("channels", &self.channels)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    /// Mono source of ``frames`` samples at ``value``
+    fn constant(value: f32, frames: usize) -> BackendSource {
+        Box::new(SamplesBuffer::new(1, 48000, vec![value; frames]))
+    }
+
+    #[test]
+    fn mixer_adds_sources_until_they_run_out() {
+        let mixer = Mixer::default();
+        let short = mixer.add(constant(0.25, 100), 1, 48000);
+        let long = mixer.add(constant(0.5, 200), 1, 48000);
+        let mut buffer = [1.0; 300];
+        mixer.fill(&mut buffer, 1);
+        assert!(buffer[..100].iter().all(|sample| *sample == 0.75));
+        assert!(buffer[100..200].iter().all(|sample| *sample == 0.5));
+        assert!(buffer[200..].iter().all(|sample| *sample == 0.0));
+        assert!(short.finished.load(Ordering::Relaxed));
+        assert!(long.finished.load(Ordering::Relaxed));
+        assert_eq!(short.played_frames.load(Ordering::Relaxed), 100);
+        assert_eq!(long.played_frames.load(Ordering::Relaxed), 200);
+
+        // Finished sources are out of the mix and stay at their end
+        mixer.fill(&mut buffer, 1);
+        assert!(buffer.iter().all(|sample| *sample == 0.0));
+        assert_eq!(long.played_frames.load(Ordering::Relaxed), 200);
+        assert!(mixer.sources.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn mixer_position_counts_frames_of_every_channel_once() {
+        let mixer = Mixer::default();
+        let handle = MixerPlaybackHandle {
+            voice: mixer.add(constant(0.5, 4800), 2, 48000),
+            sample_rate: 48000,
+            failed: None,
+        };
+        let mut buffer = [0.0; 2 * 2400];
+        mixer.fill(&mut buffer, 2);
+        assert!(buffer.iter().all(|sample| *sample == 0.5));
+        assert_eq!(handle.position(), Duration::from_millis(50));
+        assert!(!handle.is_finished());
+    }
+
+    #[test]
+    fn mixer_is_silent_while_the_sources_are_locked() {
+        let mixer = Mixer::default();
+        let voice = mixer.add(constant(0.5, 100), 1, 48000);
+        let mut buffer = [1.0; 10];
+        let sources = mixer.sources.lock().unwrap();
+        mixer.fill(&mut buffer, 1);
+        drop(sources);
+        assert!(buffer.iter().all(|sample| *sample == 0.0));
+        assert_eq!(voice.played_frames.load(Ordering::Relaxed), 0);
+    }
+}
@@ -0,0 +1,140 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+};
+
+use crate::{
+    audio_backend::{
+        AudioBackend, BackendSource, NullBackend, OutputLatency, PlaybackHandle, RodioBackend,
+    },
+    clock::Clock,
+};
+use rodio::StreamError;
+
+/// Audio output that [`crate::player::Player`]s play through
+///
+/// Opening an output stream claims a handle on the audio device, so instead of every player opening its own
+/// they all share one context. Players use [`AudioContext::global`] unless they are given one with
+/// [`crate::player::Player::set_audio_context`]. Cloning is cheap and every clone plays through the same
+/// [`AudioBackend`], which is closed once the last clone is dropped
+///
+/// The context also coordinates solo and mute, so soloing a player silences every other player of the same context
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::audio_context::AudioContext;
+/// use egui_player::player::Player;
+///
+/// let context = AudioContext::new().unwrap();
+/// let mut first = Player::from_path("hello.mp3");
+/// let mut second = Player::from_path("world.mp3");
+/// first.set_audio_context(context.clone());
+/// second.set_audio_context(context);
+/// ```
+#[derive(Clone)]
+pub struct AudioContext {
+    backend: Arc<dyn AudioBackend>,
+    /// Amount of players that are soloed, see [`crate::player::Player::set_solo`]
+    soloed: Arc<AtomicUsize>,
+}
+
+impl AudioContext {
+    /// Opens the default output device with the [`RodioBackend`]
+    pub fn new() -> Result<Self, StreamError> {
+        Ok(Self::with_backend(RodioBackend::open()?))
+    }
+
+    /// Opens the default output device with buffers sized after ``latency``, see [`OutputLatency`]
+    ///
+    /// ``` no_run
+    /// use egui_player::audio_backend::OutputLatency;
+    /// use egui_player::audio_context::AudioContext;
+    ///
+    /// // Every pad of a soundboard shares one low latency output
+    /// let context = AudioContext::with_latency(OutputLatency::Low).unwrap();
+    /// ```
+    pub fn with_latency(latency: OutputLatency) -> Result<Self, StreamError> {
+        Ok(Self::with_backend(RodioBackend::open_with_latency(
+            latency,
+        )?))
+    }
+
+    /// Plays through ``backend``
+    pub fn with_backend(backend: impl AudioBackend + 'static) -> Self {
+        Self {
+            backend: Arc::new(backend),
+            soloed: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Context without an output device that uses the [`NullBackend`], players keep time as usual but play nothing
+    ///
+    /// Useful for tests and headless environments that have no audio device
+    pub fn silent() -> Self {
+        Self::with_backend(NullBackend::default())
+    }
+
+    /// Context without an output device whose audio is played as time passes on ``clock``, see [`NullBackend::with_clock`]
+    ///
+    /// Give players the same clock with [`crate::player::Player::set_clock`] and they reach the end of their audio
+    /// once the clock was moved past it
+    pub fn silent_with_clock(clock: impl Clock + 'static) -> Self {
+        Self::with_backend(NullBackend::with_clock(clock))
+    }
+
+    /// Process wide context on the default output device, opened the first time it is used
+    ///
+    /// Falls back to [`AudioContext::silent`] if there is no output device available
+    pub fn global() -> &'static AudioContext {
+        static GLOBAL: OnceLock<AudioContext> = OnceLock::new();
+        GLOBAL.get_or_init(|| {
+            AudioContext::new().unwrap_or_else(|error| {
+                log::warn!("No audio output available, playing silently: {error}");
+                AudioContext::silent()
+            })
+        })
+    }
+
+    /// Starts playing ``source`` through the backend of this context
+    pub(crate) fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle> {
+        self.backend.play(source)
+    }
+
+    /// Whether any player of this context is soloed
+    pub fn has_solo(&self) -> bool {
+        self.soloed.load(Ordering::Acquire) > 0
+    }
+
+    /// Whether a player with these flags should be heard, players are silenced when muted
+    /// or when another player is soloed
+    pub(crate) fn is_audible(&self, muted: bool, solo: bool) -> bool {
+        !muted && (solo || !self.has_solo())
+    }
+}
+
+/// Counts a soloed player towards its [`AudioContext`] for as long as it is alive
+#[derive(Debug)]
+pub(crate) struct SoloGuard(AudioContext);
+
+impl SoloGuard {
+    pub(crate) fn new(context: AudioContext) -> Self {
+        context.soloed.fetch_add(1, Ordering::AcqRel);
+        Self(context)
+    }
+}
+
+impl Drop for SoloGuard {
+    fn drop(&mut self) {
+        self.0.soloed.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl fmt::Debug for AudioContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AudioContext").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,101 @@
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Source of time for the stopwatch of a [`crate::player::Player`], set with [`crate::player::Player::set_clock`]
+///
+/// Players use the [`SystemClock`]. Give them a [`ManualClock`] to move time forward yourself, for example in tests,
+/// or implement this to drive players from a replay or simulation. ``Arc`` of a clock is also a clock so one can be shared
+pub trait Clock: Send + Sync {
+    fn now(&self) -> Instant;
+}
+
+impl fmt::Debug for dyn Clock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Clock")
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> Instant {
+        (**self).now()
+    }
+}
+
+/// Follows the time of the system
+#[derive(Debug, Copy, Clone, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock that only moves when [`ManualClock::advance`] is called, clones share the same time
+///
+/// Together with [`crate::audio_context::AudioContext::silent_with_clock`] a player can be driven by a headless egui
+/// context without an audio device, its audio runs out once the clock was moved past the end
+///
+/// # Examples
+///
+/// ``` no_run
+/// use eframe::egui::{CentralPanel, Context, RawInput};
+/// use egui_player::audio_context::AudioContext;
+/// use egui_player::clock::ManualClock;
+/// use egui_player::key_bindings::PlayerAction;
+/// use egui_player::player::{Player, PlayerState};
+/// use std::time::Duration;
+///
+/// let clock = ManualClock::new();
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_audio_context(AudioContext::silent_with_clock(clock.clone()));
+/// player.set_clock(clock.clone());
+///
+/// let ctx = Context::default();
+/// let frame = |player: &mut Player| {
+///     let _ = ctx.run(RawInput::default(), |ctx| {
+///         CentralPanel::default().show(ctx, |ui| {
+///             player.ui(ui);
+///         });
+///     });
+/// };
+///
+/// player.run_action(PlayerAction::PlayPause);
+/// frame(&mut player);
+/// clock.advance(Duration::from_secs(2));
+/// frame(&mut player);
+/// assert_eq!(player.player_state, PlayerState::Playing);
+/// assert_eq!(player.elapsed_time, Duration::from_secs(2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Moves the time of this clock and all of its clones forward by ``duration``
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
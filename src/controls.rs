@@ -0,0 +1,78 @@
+use eframe::egui::Ui;
+use std::fmt;
+
+/// Closure that adds widgets to the control bar
+type Slot = Box<dyn FnMut(&mut Ui) + Send>;
+
+/// Customizes the control bar of a [`crate::player::Player`], set with [`crate::player::Player::set_controls`]
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::controls::ControlsConfig;
+/// use egui_player::player::Player;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_controls(
+///     ControlsConfig::default()
+///         .custom_left(|ui| {
+///             ui.label("🎵");
+///         })
+///         .custom_right(|ui| {
+///             if ui.button("Share").clicked() {
+///                 // share the file
+///             }
+///         }),
+/// );
+/// ```
+#[derive(Default)]
+pub struct ControlsConfig {
+    pub(crate) custom_left: Option<Slot>,
+    pub(crate) custom_right: Option<Slot>,
+    pub(crate) touch_mode: bool,
+    pub(crate) gestures: bool,
+}
+
+impl ControlsConfig {
+    /// Widgets added in front of the play / pause button
+    pub fn custom_left(mut self, add_contents: impl FnMut(&mut Ui) + Send + 'static) -> Self {
+        self.custom_left = Some(Box::new(add_contents));
+        self
+    }
+
+    /// Widgets added after the built in controls
+    pub fn custom_right(mut self, add_contents: impl FnMut(&mut Ui) + Send + 'static) -> Self {
+        self.custom_right = Some(Box::new(add_contents));
+        self
+    }
+
+    /// Controls for touch screens on phones and tablets
+    ///
+    /// Buttons and the seek bar get larger and easier to hit, tapping an empty part of the player plays or pauses it,
+    /// and swiping left or right across it seeks 10 seconds back or forward
+    pub fn touch_mode(mut self, touch_mode: bool) -> Self {
+        self.touch_mode = touch_mode;
+        self
+    }
+
+    /// Gestures of mobile players on the media surface
+    ///
+    /// Dragging left or right scrubs through the file, dragging up or down on the right half changes the volume.
+    /// A bubble in the middle of the player shows where the drag lands, the seek only happens once it is let go.
+    /// Takes over from the swipes of [`ControlsConfig::touch_mode`] when both are on
+    pub fn gestures(mut self, gestures: bool) -> Self {
+        self.gestures = gestures;
+        self
+    }
+}
+
+impl fmt::Debug for ControlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ControlsConfig")
+            .field("custom_left", &self.custom_left.is_some())
+            .field("custom_right", &self.custom_right.is_some())
+            .field("touch_mode", &self.touch_mode)
+            .field("gestures", &self.gestures)
+            .finish()
+    }
+}
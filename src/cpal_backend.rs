@@ -0,0 +1,186 @@
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    BuildStreamError, Device, FromSample, SampleFormat, SizedSample, Stream, StreamConfig,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+use crate::audio_backend::{
+    AudioBackend, BackendSource, Mixer, MixerPlaybackHandle, OutputLatency, PlaybackHandle,
+};
+
+/// Settings of a [`CpalBackend`], ``None`` keeps the default of the device
+///
+/// ``device``: Name of the output device, as listed by [`CpalBackend::output_devices`]
+///
+/// ``sample_rate`` and ``channels``: Format the device is opened with, sources are converted to it
+///
+/// ``sample_format``: Format of the samples handed to the device, for devices that play some formats natively
+///
+/// ``latency``: Size of the buffers of the device, see [`OutputLatency`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CpalConfig {
+    pub device: Option<String>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<u16>,
+    pub sample_format: Option<SampleFormat>,
+    pub latency: OutputLatency,
+}
+
+/// State shared between a [`CpalBackend`] and the callback of its stream
+#[derive(Default)]
+struct CpalOutput {
+    mixer: Mixer,
+    /// Set once the stream reports an error
+    failed: Arc<AtomicBool>,
+}
+
+/// Plays through an output device with cpal directly, mixing sources itself instead of going through rodio
+///
+/// Pick the device, sample rate, channels, sample format and buffer size with a [`CpalConfig`].
+/// Decoding still happens in the player with symphonia, the same as for every other backend.
+/// Unlike the [`crate::audio_backend::RodioBackend`] it stays on the device it was opened with and reports
+/// [`PlaybackHandle::has_failed`] once that device stops working
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::audio_context::AudioContext;
+/// use egui_player::cpal_backend::{CpalBackend, CpalConfig};
+///
+/// let device = CpalBackend::output_devices().into_iter().next();
+/// let backend = CpalBackend::open(CpalConfig {
+///     device,
+///     sample_rate: Some(48000),
+///     ..Default::default()
+/// })
+/// .unwrap();
+/// let context = AudioContext::with_backend(backend);
+/// ```
+pub struct CpalBackend {
+    output: Arc<CpalOutput>,
+    channels: u16,
+    sample_rate: u32,
+    /// The thread that owns the stream closes it once the backend is dropped
+    _keep_alive: mpsc::Sender<()>,
+}
+
+impl CpalBackend {
+    /// Names of the output devices of the default host
+    pub fn output_devices() -> Vec<String> {
+        cpal::default_host()
+            .output_devices()
+            .map(|devices| devices.filter_map(|device| device.name().ok()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Opens the device of ``config``, or the default output device
+    pub fn open(config: CpalConfig) -> Result<Self, BuildStreamError> {
+        let (keep_alive, closed) = mpsc::channel::<()>();
+        let (opened_sender, opened_receiver) = mpsc::channel();
+        let output = Arc::new(CpalOutput::default());
+        let thread_output = Arc::clone(&output);
+        // The stream can not be moved between threads, so it lives on its own thread for as long as the backend does
+        thread::spawn(move || match open_stream(&config, thread_output) {
+            Ok((_stream, stream_config)) => {
+                log::info!(
+                    "Opened the audio output {:?} at {} Hz",
+                    config.device,
+                    stream_config.sample_rate.0
+                );
+                opened_sender.send(Ok(stream_config)).unwrap();
+                while closed.recv().is_ok() {}
+                log::debug!("Closed the audio output");
+            }
+            Err(error) => {
+                log::error!(
+                    "Could not open the audio output {:?}: {error}",
+                    config.device
+                );
+                opened_sender.send(Err(error)).unwrap();
+            }
+        });
+        let stream_config = opened_receiver.recv().unwrap()?;
+        Ok(Self {
+            output,
+            channels: stream_config.channels,
+            sample_rate: stream_config.sample_rate.0,
+            _keep_alive: keep_alive,
+        })
+    }
+}
+
+/// Finds the device of ``config`` and starts a stream on it that plays the mix of ``output``
+fn open_stream(
+    config: &CpalConfig,
+    output: Arc<CpalOutput>,
+) -> Result<(Stream, StreamConfig), BuildStreamError> {
+    let host = cpal::default_host();
+    let device = match &config.device {
+        Some(name) => host
+            .output_devices()
+            .map_err(|_| BuildStreamError::DeviceNotAvailable)?
+            .find(|device| device.name().is_ok_and(|other| &other == name)),
+        None => host.default_output_device(),
+    }
+    .ok_or(BuildStreamError::DeviceNotAvailable)?;
+    let supported = device
+        .default_output_config()
+        .map_err(|_| BuildStreamError::StreamConfigNotSupported)?;
+    let mut stream_config = supported.config();
+    if let Some(sample_rate) = config.sample_rate {
+        stream_config.sample_rate.0 = sample_rate;
+    }
+    if let Some(channels) = config.channels {
+        stream_config.channels = channels.max(1);
+    }
+    stream_config.buffer_size = config.latency.buffer_size(&supported);
+    let stream = match config.sample_format.unwrap_or(supported.sample_format()) {
+        SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, output),
+        SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, output),
+        SampleFormat::I32 => build_stream::<i32>(&device, &stream_config, output),
+        SampleFormat::F64 => build_stream::<f64>(&device, &stream_config, output),
+        _ => build_stream::<f32>(&device, &stream_config, output),
+    }?;
+    stream
+        .play()
+        .map_err(|_| BuildStreamError::DeviceNotAvailable)?;
+    Ok((stream, stream_config))
+}
+
+/// Builds a stream that fills every buffer with the mix of ``output``
+fn build_stream<T: SizedSample + FromSample<f32>>(
+    device: &Device,
+    config: &StreamConfig,
+    output: Arc<CpalOutput>,
+) -> Result<Stream, BuildStreamError> {
+    let channels = config.channels;
+    let error_output = Arc::clone(&output);
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _| output.mixer.fill(data, channels),
+        move |error| {
+            log::error!("Audio output error: {error}");
+            error_output.failed.store(true, Ordering::Release);
+        },
+        None,
+    )
+}
+
+impl AudioBackend for CpalBackend {
+    fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle> {
+        Box::new(MixerPlaybackHandle {
+            voice: self
+                .output
+                .mixer
+                .add(source, self.channels, self.sample_rate),
+            sample_rate: self.sample_rate,
+            failed: Some(Arc::clone(&self.output.failed)),
+        })
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// Frames per second of the ``mm:ss:ff`` timestamps of a CUE sheet, as on an audio CD
+const FRAMES_PER_SECOND: u64 = 75;
+
+/// A track of a [`CueSheet`]
+///
+/// ``start``: Where ``INDEX 01`` of the track points to in the file, the pregap before it belongs to the track before
+#[derive(Debug, Clone, PartialEq)]
+pub struct CueTrack {
+    pub number: u32,
+    pub title: String,
+    pub performer: Option<String>,
+    pub start: Duration,
+}
+
+/// Tracks of an album stored as one long file, read from a ``.cue`` file
+///
+/// Only the first ``FILE`` of the sheet is used, which is the only one single file albums have.
+/// Load one into a player with [`crate::player::Player::from_cue`] or [`crate::player::Player::set_cue_sheet`],
+/// which turns every track into a chapter
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::cue::CueSheet;
+///
+/// let sheet = CueSheet::open("album.cue").unwrap();
+/// for track in &sheet.tracks {
+///     println!("{} {} at {:?}", track.number, track.title, track.start);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CueSheet {
+    pub title: Option<String>,
+    pub performer: Option<String>,
+    /// The audio file the tracks are in, relative to the sheet unless it was parsed with [`CueSheet::parse`]
+    pub file: Option<PathBuf>,
+    pub tracks: Vec<CueTrack>,
+}
+
+impl CueSheet {
+    /// Reads the sheet at ``path``, resolving its ``FILE`` next to the sheet
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        // Sheets are often written in a legacy code page, only the text of titles suffers from reading them lossily
+        let mut sheet = Self::parse(&String::from_utf8_lossy(&fs::read(path)?));
+        if let (Some(file), Some(folder)) = (&mut sheet.file, path.parent()) {
+            *file = folder.join(&file);
+        }
+        Ok(sheet)
+    }
+
+    /// Reads a sheet from its text, commands that are not understood are skipped
+    pub fn parse(contents: &str) -> Self {
+        let mut sheet = Self::default();
+        let mut has_file = false;
+        for line in contents.trim_start_matches('\u{feff}').lines() {
+            let line = line.trim();
+            let (command, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let rest = rest.trim();
+            match command.to_ascii_uppercase().as_str() {
+                "FILE" if !has_file => {
+                    has_file = true;
+                    sheet.file = Some(PathBuf::from(quoted(file_name(rest))));
+                }
+                // Tracks of any later file would need a player of their own
+                "FILE" => break,
+                "TRACK" => sheet.tracks.push(CueTrack {
+                    number: rest
+                        .split_whitespace()
+                        .next()
+                        .and_then(|number| number.parse().ok())
+                        .unwrap_or(sheet.tracks.len() as u32 + 1),
+                    title: String::new(),
+                    performer: None,
+                    start: Duration::ZERO,
+                }),
+                "TITLE" => match sheet.tracks.last_mut() {
+                    Some(track) => track.title = quoted(rest),
+                    None => sheet.title = Some(quoted(rest)),
+                },
+                "PERFORMER" => match sheet.tracks.last_mut() {
+                    Some(track) => track.performer = Some(quoted(rest)),
+                    None => sheet.performer = Some(quoted(rest)),
+                },
+                "INDEX" => {
+                    let mut parts = rest.split_whitespace();
+                    if parts.next().and_then(|index| index.parse::<u32>().ok()) == Some(1)
+                        && let Some(start) = parts.next().and_then(timestamp)
+                        && let Some(track) = sheet.tracks.last_mut()
+                    {
+                        track.start = start;
+                    }
+                }
+                _ => {}
+            }
+        }
+        for track in &mut sheet.tracks {
+            if track.title.is_empty() {
+                track.title = format!("Track {:02}", track.number);
+            }
+        }
+        sheet
+    }
+}
+
+/// Drops the file type, such as ``WAVE`` or ``MP3``, from the end of a ``FILE`` command
+fn file_name(rest: &str) -> &str {
+    match rest.rsplit_once(char::is_whitespace) {
+        Some((name, kind)) if !kind.ends_with('"') => name.trim(),
+        _ => rest,
+    }
+}
+
+/// Value of a command, without the quotes around it if it has them
+fn quoted(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+/// Parses a ``mm:ss:ff`` timestamp
+fn timestamp(value: &str) -> Option<Duration> {
+    let mut parts = value.split(':').map(|part| part.parse::<u64>().ok());
+    let (minutes, seconds, frames) = (parts.next()??, parts.next()??, parts.next()??);
+    // Timestamps too large to count in frames are not valid
+    let frames = minutes
+        .checked_mul(60)?
+        .checked_add(seconds)?
+        .checked_mul(FRAMES_PER_SECOND)?
+        .checked_add(frames)?;
+    Some(
+        Duration::from_secs(frames / FRAMES_PER_SECOND)
+            + Duration::from_nanos(frames % FRAMES_PER_SECOND * 1_000_000_000 / FRAMES_PER_SECOND),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHEET: &str = r#"PERFORMER "The Band"
+TITLE "Live Album"
+FILE "album.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "Opening"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    PERFORMER "Guest"
+    INDEX 00 03:58:00
+    INDEX 01 04:00:30
+FILE "bonus.flac" WAVE
+  TRACK 03 AUDIO
+    INDEX 01 00:00:00
+"#;
+
+    #[test]
+    fn tracks_of_the_first_file_are_read() {
+        let sheet = CueSheet::parse(SHEET);
+        assert_eq!(sheet.title.as_deref(), Some("Live Album"));
+        assert_eq!(sheet.performer.as_deref(), Some("The Band"));
+        assert_eq!(sheet.file, Some(PathBuf::from("album.flac")));
+        assert_eq!(
+            sheet.tracks,
+            vec![
+                CueTrack {
+                    number: 1,
+                    title: "Opening".to_string(),
+                    performer: None,
+                    start: Duration::ZERO,
+                },
+                CueTrack {
+                    number: 2,
+                    title: "Track 02".to_string(),
+                    performer: Some("Guest".to_string()),
+                    start: Duration::from_millis(240_400),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_range_timestamps_are_skipped() {
+        assert_eq!(
+            timestamp("99:59:74"),
+            Some(Duration::from_secs(5999) + Duration::from_nanos(986_666_666))
+        );
+        assert_eq!(timestamp("18446744073709551615:00:00"), None);
+        assert_eq!(timestamp("00:01:18446744073709551615"), None);
+        assert_eq!(timestamp("00:00"), None);
+    }
+}
@@ -0,0 +1,263 @@
+use rodio::{source::SeekError, Source};
+use std::{
+    io::{self, Cursor},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+use symphonia::core::{
+    audio::{SampleBuffer, SignalSpec},
+    codecs::{self, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error,
+    formats::{FormatOptions, FormatReader, SeekMode, SeekTo, Track},
+    io::{MediaSource, MediaSourceStream},
+    meta::MetadataOptions,
+    probe::Hint,
+    units::{Time, TimeBase},
+};
+
+#[cfg(feature = "streaming")]
+use crate::remote_reader::{self, RemoteReader};
+use crate::{dsp::AudioTap, file_reader::FileReader, InputMode};
+
+/// Packets in a row that may be skipped before the file counts as broken beyond repair
+const MAX_SKIPPED_PACKETS: usize = 100;
+
+/// Decodes a file into ``f32`` samples, skipping packets that are corrupt instead of ending the audio at the first one
+///
+/// Only errors that leave nothing to read after them end the stream, such as a file that can no longer be read.
+/// Skipped packets and the error that ended the stream are logged and reported to the [`AudioTap`], if there is one
+pub(crate) struct SkippingDecoder {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn codecs::Decoder>,
+    track_id: u32,
+    time_base: Option<TimeBase>,
+    total_duration: Option<Duration>,
+    /// Layout of the samples in ``buffer``
+    spec: SignalSpec,
+    buffer: Vec<f32>,
+    offset: usize,
+    /// Time of the last packet that was read, where skipped packets are reported at
+    position: Duration,
+    audio_tap: Option<Arc<AudioTap>>,
+}
+
+impl SkippingDecoder {
+    /// Opens the first track that can be decoded and decodes its first packet
+    ///
+    /// ``audio_tap``: Where the skipped packets of playback are reported, analysis only logs them
+    pub(crate) fn open(
+        file_input: InputMode,
+        audio_tap: Option<Arc<AudioTap>>,
+    ) -> Result<Self, Error> {
+        let mut hint = Hint::new();
+        let source: Box<dyn MediaSource> = match file_input {
+            InputMode::FilePath(file_path) => {
+                if let Some(extension) = Path::new(&file_path).extension().and_then(|e| e.to_str())
+                {
+                    hint.with_extension(extension);
+                }
+                Box::new(FileReader::open(file_path)?)
+            }
+            InputMode::Bytes(bytes) => Box::new(Cursor::new(bytes)),
+            #[cfg(feature = "streaming")]
+            InputMode::Url(url) => {
+                if let Some(extension) = remote_reader::extension(&url) {
+                    hint.with_extension(extension);
+                }
+                Box::new(RemoteReader::open(&url)?)
+            }
+        };
+        let probed = symphonia::default::get_probe().format(
+            &hint,
+            MediaSourceStream::new(source, Default::default()),
+            &FormatOptions {
+                enable_gapless: true,
+                ..Default::default()
+            },
+            &MetadataOptions::default(),
+        )?;
+        let track = decodable_track(probed.format.tracks())?.clone();
+        let mut decoder = Self {
+            decoder: symphonia::default::get_codecs()
+                .make(&track.codec_params, &DecoderOptions::default())?,
+            format: probed.format,
+            track_id: track.id,
+            time_base: track.codec_params.time_base,
+            total_duration: track
+                .codec_params
+                .time_base
+                .zip(track.codec_params.n_frames)
+                .map(|(base, frames)| time_to_duration(base.calc_time(frames))),
+            spec: SignalSpec::new(
+                track.codec_params.sample_rate.unwrap_or(44_100),
+                track.codec_params.channels.unwrap_or_default(),
+            ),
+            buffer: vec![],
+            offset: 0,
+            position: Duration::ZERO,
+            audio_tap,
+        };
+        decoder.decode_packet();
+        Ok(decoder)
+    }
+
+    /// Replaces ``buffer`` with the samples of the next packet that decodes, leaving it empty once the stream ended
+    fn decode_packet(&mut self) -> bool {
+        self.buffer.clear();
+        self.offset = 0;
+        let mut skipped = 0;
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(error)) if error.kind() == io::ErrorKind::UnexpectedEof => {
+                    return false;
+                }
+                // Chained streams, such as Ogg radio, go on with a new track
+                Err(Error::ResetRequired) => match self.reset_track() {
+                    Ok(()) => continue,
+                    Err(error) => return self.fail(error),
+                },
+                Err(Error::DecodeError(message)) => {
+                    self.skip(message, &mut skipped);
+                    if skipped > MAX_SKIPPED_PACKETS {
+                        return self.fail(Error::DecodeError("too many corrupt packets in a row"));
+                    }
+                    continue;
+                }
+                Err(error) => return self.fail(error),
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            if let Some(time_base) = self.time_base {
+                self.position = time_to_duration(time_base.calc_time(packet.ts()));
+            }
+            match self.decoder.decode(&packet) {
+                Ok(decoded) => {
+                    self.spec = *decoded.spec();
+                    let mut samples =
+                        SampleBuffer::<f32>::new(decoded.capacity() as u64, self.spec);
+                    samples.copy_interleaved_ref(decoded);
+                    self.buffer.extend_from_slice(samples.samples());
+                    if !self.buffer.is_empty() {
+                        return true;
+                    }
+                }
+                Err(Error::ResetRequired) => self.decoder.reset(),
+                // A truncated packet is as recoverable as a malformed one
+                Err(Error::DecodeError(message)) => self.skip(message, &mut skipped),
+                Err(Error::IoError(_)) => self.skip("truncated packet", &mut skipped),
+                Err(error) => return self.fail(error),
+            }
+            if skipped > MAX_SKIPPED_PACKETS {
+                return self.fail(Error::DecodeError("too many corrupt packets in a row"));
+            }
+        }
+    }
+
+    /// Makes a decoder for the track the format reader switched to
+    fn reset_track(&mut self) -> Result<(), Error> {
+        let track = decodable_track(self.format.tracks())?;
+        self.decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())?;
+        self.track_id = track.id;
+        self.time_base = track.codec_params.time_base;
+        Ok(())
+    }
+
+    /// Reports a packet that could not be decoded and was left out
+    fn skip(&mut self, message: &str, skipped: &mut usize) {
+        *skipped += 1;
+        log::warn!("Skipped a corrupt packet at {:?}: {message}", self.position);
+        if let Some(audio_tap) = &self.audio_tap {
+            audio_tap.record_decode_warning(self.position, message.to_string());
+        }
+    }
+
+    /// Reports the error that ended the stream, returns ``false`` for [`SkippingDecoder::decode_packet`] to end with
+    fn fail(&mut self, error: Error) -> bool {
+        log::error!("Decoding stopped at {:?}: {error}", self.position);
+        if let Some(audio_tap) = &self.audio_tap {
+            audio_tap.record_decode_failure(self.position, error.to_string());
+        }
+        false
+    }
+}
+
+impl Iterator for SkippingDecoder {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        let sample = *self.buffer.get(self.offset)?;
+        self.offset += 1;
+        // Decoding the next packet right away keeps the frame length and spec correct for what comes next
+        if self.offset == self.buffer.len() {
+            self.decode_packet();
+        }
+        Some(sample)
+    }
+}
+
+impl Source for SkippingDecoder {
+    fn current_frame_len(&self) -> Option<usize> {
+        Some(self.buffer.len() - self.offset)
+    }
+
+    fn channels(&self) -> u16 {
+        self.spec.channels.count().max(1) as u16
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.spec.rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        // Some formats can only seek to just before the end
+        let pos = match self.total_duration {
+            Some(total) => pos.min(total.saturating_sub(Duration::from_millis(1))),
+            None => pos,
+        };
+        // The next sample has to be for the same channel as before the seek
+        let channel = self.offset % self.channels() as usize;
+        let seeked = self
+            .format
+            .seek(
+                SeekMode::Accurate,
+                SeekTo::Time {
+                    time: Time::from(pos.as_secs_f64()),
+                    track_id: Some(self.track_id),
+                },
+            )
+            .map_err(|error| SeekError::Other(Box::new(error)))?;
+        self.decoder.reset();
+        // The reader lands on the packet holding the position, the frames of it before the position are dropped
+        let mut frames_to_skip = seeked.required_ts.saturating_sub(seeked.actual_ts) as usize;
+        while self.decode_packet() {
+            let frames = self.buffer.len() / self.channels() as usize;
+            if frames_to_skip < frames {
+                self.offset = frames_to_skip * self.channels() as usize
+                    + channel.min(self.channels() as usize - 1);
+                break;
+            }
+            frames_to_skip -= frames;
+        }
+        Ok(())
+    }
+}
+
+/// First track with a codec that symphonia can decode
+fn decodable_track(tracks: &[Track]) -> Result<&Track, Error> {
+    tracks
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or(Error::Unsupported("no track with a supported codec"))
+}
+
+fn time_to_duration(time: Time) -> Duration {
+    Duration::from_secs(time.seconds) + Duration::from_secs_f64(time.frac)
+}
@@ -0,0 +1,1145 @@
+use rodio::{source::SeekError, Source};
+use std::{
+    collections::VecDeque,
+    f32::consts::PI,
+    mem,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{effects::EffectsChain, player::PlayerEvent, ChannelLevel, SpeedMode};
+
+/// Amount of frames processed at a time by [`EffectsStage`], small enough to keep latency low
+const BLOCK_FRAMES: usize = 512;
+
+/// Length of the most recent audio kept by [`AudioTap`] for visualizations
+const TAP_DURATION: Duration = Duration::from_millis(200);
+
+/// Most events the [`AudioTap`] holds on to until the player takes them, a badly broken file reports a lot of them
+const MAX_PENDING_EVENTS: usize = 64;
+
+/// How fast the [`Limiter`] reaches the gain that keeps the output below the ceiling
+const LIMITER_ATTACK: Duration = Duration::from_millis(1);
+
+/// How slowly the [`Limiter`] lets go once the audio is quieter again, long enough to not pump on every beat
+const LIMITER_RELEASE: Duration = Duration::from_millis(150);
+
+/// Playback settings shared between the [`crate::player::Player`] and its audio stream
+///
+/// Values are stored atomically so the player can change them while the stream is running
+#[derive(Debug)]
+pub(crate) struct DspSettings {
+    speed: AtomicU32,
+    preserve_pitch: AtomicBool,
+    pitch_semitones: AtomicU32,
+    mono: AtomicBool,
+    /// Linear gain applied after the effects, set from decibels with [`crate::player::Player::set_level_gain`]
+    gain: AtomicU32,
+    limiter: AtomicBool,
+    /// Factor the output is multiplied with after this stage, which the [`Limiter`] has to leave headroom for
+    output_volume: AtomicU32,
+}
+
+impl DspSettings {
+    pub(crate) fn new() -> Self {
+        Self {
+            speed: AtomicU32::new(1.0_f32.to_bits()),
+            preserve_pitch: AtomicBool::new(true),
+            pitch_semitones: AtomicU32::new(0.0_f32.to_bits()),
+            mono: AtomicBool::new(false),
+            gain: AtomicU32::new(1.0_f32.to_bits()),
+            limiter: AtomicBool::new(true),
+            output_volume: AtomicU32::new(1.0_f32.to_bits()),
+        }
+    }
+
+    /// Copies the current values, so an offline render is not affected by later changes
+    pub(crate) fn snapshot(&self) -> Self {
+        let settings = Self::new();
+        settings.set_speed(self.speed());
+        settings.set_speed_mode(self.speed_mode());
+        settings.set_pitch_semitones(self.pitch_semitones());
+        settings.set_mono(self.mono());
+        settings.set_gain(self.gain());
+        // Offline renders are not turned up by the volume afterwards, so the output volume stays at one
+        settings.set_limiter(self.limiter());
+        settings
+    }
+
+    pub(crate) fn speed(&self) -> f32 {
+        f32::from_bits(self.speed.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set_speed(&self, speed: f32) {
+        self.speed.store(speed.to_bits(), Ordering::Release);
+    }
+
+    pub(crate) fn speed_mode(&self) -> SpeedMode {
+        if self.preserve_pitch.load(Ordering::Acquire) {
+            SpeedMode::PreservePitch
+        } else {
+            SpeedMode::Resample
+        }
+    }
+
+    pub(crate) fn set_speed_mode(&self, mode: SpeedMode) {
+        self.preserve_pitch
+            .store(mode == SpeedMode::PreservePitch, Ordering::Release);
+    }
+
+    pub(crate) fn pitch_semitones(&self) -> f32 {
+        f32::from_bits(self.pitch_semitones.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set_pitch_semitones(&self, semitones: f32) {
+        self.pitch_semitones
+            .store(semitones.to_bits(), Ordering::Release);
+    }
+
+    pub(crate) fn mono(&self) -> bool {
+        self.mono.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_mono(&self, mono: bool) {
+        self.mono.store(mono, Ordering::Release);
+    }
+
+    pub(crate) fn gain(&self) -> f32 {
+        f32::from_bits(self.gain.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set_gain(&self, gain: f32) {
+        self.gain.store(gain.to_bits(), Ordering::Release);
+    }
+
+    pub(crate) fn limiter(&self) -> bool {
+        self.limiter.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_limiter(&self, limiter: bool) {
+        self.limiter.store(limiter, Ordering::Release);
+    }
+
+    pub(crate) fn output_volume(&self) -> f32 {
+        f32::from_bits(self.output_volume.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set_output_volume(&self, volume: f32) {
+        self.output_volume
+            .store(volume.to_bits(), Ordering::Release);
+    }
+
+    /// Returns the tempo and pitch factors that the [`TimeStretch`] stage should apply
+    fn factors(&self) -> (f64, f64) {
+        let speed = self.speed() as f64;
+        let pitch_shift = 2.0_f64.powf(self.pitch_semitones() as f64 / 12.0);
+        match self.speed_mode() {
+            SpeedMode::Resample => (speed, speed * pitch_shift),
+            SpeedMode::PreservePitch => (speed, pitch_shift),
+        }
+    }
+}
+
+/// Waveform similarity overlap-add (WSOLA) time stretcher
+///
+/// Short Hann windowed segments are read from the input at ``tempo`` times the rate they are written out.
+/// Each segment is picked within a small tolerance so that it lines up with the waveform of the previous one,
+/// which keeps the pitch intact and avoids the phasing of a plain overlap-add
+struct Wsola {
+    channels: usize,
+    hop: usize,
+    tolerance: usize,
+    window: Vec<f32>,
+    input: Vec<f32>,
+    analysis: f64,
+    continuation: Option<usize>,
+    overlap: Vec<f32>,
+}
+
+impl Wsola {
+    fn new(channels: usize, sample_rate: u32) -> Self {
+        // 20ms hops with 40ms segments and an 8ms search range work well for both speech and music
+        let hop = (sample_rate as usize / 50).max(1);
+        let tolerance = sample_rate as usize / 125;
+        let window = (0..hop * 2)
+            .map(|n| 0.5 - 0.5 * (PI * n as f32 / hop as f32).cos())
+            .collect();
+        Self {
+            channels,
+            hop,
+            tolerance,
+            window,
+            input: vec![],
+            analysis: 0.0,
+            continuation: None,
+            overlap: vec![0.0; hop * channels],
+        }
+    }
+
+    fn reset(&mut self) {
+        self.input.clear();
+        self.analysis = 0.0;
+        self.continuation = None;
+        self.overlap.fill(0.0);
+    }
+
+    fn buffered_frames(&self) -> usize {
+        self.input.len() / self.channels
+    }
+
+    /// Amount of buffered frames needed before [`Wsola::step`] can run
+    fn required_frames(&self) -> usize {
+        let furthest =
+            (self.analysis as usize + self.tolerance).max(self.continuation.unwrap_or(0));
+        furthest + self.hop * 2
+    }
+
+    /// Overlaps the next segment and pushes ``hop`` finished frames to ``output``
+    fn step(&mut self, tempo: f64, output: &mut VecDeque<f32>) {
+        let channels = self.channels;
+        let offset = match self.continuation {
+            None => self.analysis.round() as usize,
+            // Without a tempo change the natural continuation reconstructs the input exactly
+            Some(continuation) if tempo == 1.0 => {
+                self.analysis = continuation as f64;
+                continuation
+            }
+            Some(continuation) => self.best_offset(continuation),
+        };
+
+        // Nothing comes before the first segment, so it is not faded in
+        let fade_in = self.continuation.is_some();
+        for frame in 0..self.hop {
+            let window = if fade_in { self.window[frame] } else { 1.0 };
+            for channel in 0..channels {
+                let index = frame * channels + channel;
+                let sample = self.input[offset * channels + index] * window;
+                output.push_back(self.overlap[index] + sample);
+            }
+        }
+        for frame in 0..self.hop {
+            for channel in 0..channels {
+                let index = frame * channels + channel;
+                self.overlap[index] = self.input[(offset + self.hop) * channels + index]
+                    * self.window[self.hop + frame];
+            }
+        }
+
+        self.continuation = Some(offset + self.hop);
+        self.analysis += self.hop as f64 * tempo;
+        self.discard_consumed();
+    }
+
+    /// Pushes out the rest of the buffered input once the input has run out, as long as it lasts at ``tempo``
+    fn flush(&mut self, tempo: f64, output: &mut VecDeque<f32>) {
+        self.input.truncate(self.buffered_frames() * self.channels);
+        let remaining = ((self.buffered_frames() as f64 - self.analysis) / tempo)
+            .round()
+            .max(0.0) as usize
+            * self.channels;
+        let start = output.len();
+        while output.len() - start < remaining {
+            // Silence after the end lets the last segments be overlapped like any other
+            let missing = self
+                .required_frames()
+                .saturating_sub(self.buffered_frames());
+            self.input
+                .resize(self.input.len() + missing * self.channels, 0.0);
+            self.step(tempo, output);
+        }
+        output.truncate(start + remaining);
+    }
+
+    /// Finds the segment start near the analysis position that best matches the natural continuation
+    fn best_offset(&self, continuation: usize) -> usize {
+        let target = self.analysis.round() as usize;
+        let mut best = (target, f32::MIN);
+        // Stepping through candidates and samples sparsely keeps this cheap enough for realtime use
+        for candidate in
+            (target.saturating_sub(self.tolerance)..=target + self.tolerance).step_by(2)
+        {
+            let mut correlation = 0.0;
+            let mut energy = 0.0;
+            for frame in (0..self.hop).step_by(4) {
+                let candidate_sample = self.mono(candidate + frame);
+                correlation += candidate_sample * self.mono(continuation + frame);
+                energy += candidate_sample * candidate_sample;
+            }
+            let score = correlation / energy.sqrt().max(1e-6);
+            if score > best.1 {
+                best = (candidate, score);
+            }
+        }
+        best.0
+    }
+
+    fn mono(&self, frame: usize) -> f32 {
+        self.input[frame * self.channels..(frame + 1) * self.channels]
+            .iter()
+            .sum()
+    }
+
+    /// Drops input frames that no future segment can start from
+    fn discard_consumed(&mut self) {
+        let keep_from = (self.analysis as usize)
+            .saturating_sub(self.tolerance)
+            .min(self.continuation.unwrap_or(0));
+        if keep_from > 0 {
+            self.input.drain(..keep_from * self.channels);
+            self.analysis -= keep_from as f64;
+            self.continuation = self
+                .continuation
+                .map(|continuation| continuation - keep_from);
+        }
+    }
+}
+
+/// Linear interpolation resampler, plays its input ``ratio`` times faster which also shifts the pitch
+struct Resampler {
+    channels: usize,
+    previous: Vec<f32>,
+    next: Vec<f32>,
+    position: f64,
+}
+
+impl Resampler {
+    fn new(channels: usize) -> Self {
+        Self {
+            channels,
+            previous: vec![0.0; channels],
+            next: vec![0.0; channels],
+            // Starting past the first frame makes the first output line up with the first input frame
+            position: 2.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.position = 2.0;
+    }
+
+    /// Consumes frames from ``input`` and pushes interpolated frames to ``output``
+    fn process(&mut self, ratio: f64, input: &mut VecDeque<f32>, output: &mut VecDeque<f32>) {
+        loop {
+            while self.position >= 1.0 {
+                if input.len() < self.channels {
+                    return;
+                }
+                std::mem::swap(&mut self.previous, &mut self.next);
+                for sample in self.next.iter_mut() {
+                    *sample = input.pop_front().unwrap_or_default();
+                }
+                self.position -= 1.0;
+            }
+            let fraction = self.position as f32;
+            for channel in 0..self.channels {
+                let previous = self.previous[channel];
+                output.push_back(previous + (self.next[channel] - previous) * fraction);
+            }
+            self.position += ratio;
+        }
+    }
+
+    /// Pushes out the frames up to the last one of the input, which are held back until the next frame arrives
+    fn flush(&mut self, ratio: f64, input: &mut VecDeque<f32>, output: &mut VecDeque<f32>) {
+        input.extend(std::iter::repeat_n(0.0, self.channels));
+        self.process(ratio, input, output);
+    }
+}
+
+/// Applies the speed and pitch shift set in [`DspSettings`] to a source
+///
+/// Depending on the [`SpeedMode`] the audio is either resampled, which shifts its pitch along with the speed,
+/// or time stretched with [`Wsola`] so that the pitch stays natural.
+/// A pitch shift resamples by the pitch ratio and lets [`Wsola`] undo the resulting tempo change
+pub(crate) struct TimeStretch<S>
+where
+    S: Source<Item = f32>,
+{
+    input: S,
+    settings: Arc<DspSettings>,
+    channels: u16,
+    sample_rate: u32,
+    wsola: Wsola,
+    resampler: Resampler,
+    stretched: VecDeque<f32>,
+    output: VecDeque<f32>,
+    input_finished: bool,
+    flushed: bool,
+}
+
+impl<S> TimeStretch<S>
+where
+    S: Source<Item = f32>,
+{
+    pub(crate) fn new(input: S, settings: Arc<DspSettings>) -> Self {
+        let channels = input.channels().max(1);
+        let sample_rate = input.sample_rate();
+        Self {
+            input,
+            settings,
+            channels,
+            sample_rate,
+            wsola: Wsola::new(channels as usize, sample_rate),
+            resampler: Resampler::new(channels as usize),
+            stretched: VecDeque::new(),
+            output: VecDeque::new(),
+            input_finished: false,
+            flushed: false,
+        }
+    }
+
+    /// Runs the stretch and pitch stages once, returns false when there is nothing left to process
+    fn refill(&mut self) -> bool {
+        let (tempo, pitch) = self.settings.factors();
+        if self.input_finished {
+            if self.flushed {
+                return false;
+            }
+            self.wsola.flush(tempo / pitch, &mut self.stretched);
+            self.resampler
+                .flush(pitch, &mut self.stretched, &mut self.output);
+            self.flushed = true;
+            return true;
+        }
+        if self.stretched.len() < self.channels as usize {
+            while self.wsola.buffered_frames() < self.wsola.required_frames() {
+                if !self.pull_frame() {
+                    self.input_finished = true;
+                    return true;
+                }
+            }
+            self.wsola.step(tempo / pitch, &mut self.stretched);
+        }
+        self.resampler
+            .process(pitch, &mut self.stretched, &mut self.output);
+        true
+    }
+
+    fn pull_frame(&mut self) -> bool {
+        for _ in 0..self.channels {
+            match self.input.next() {
+                Some(sample) => self.wsola.input.push(sample),
+                None => return false,
+            }
+        }
+        true
+    }
+}
+
+impl<S> Iterator for TimeStretch<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        loop {
+            if let Some(sample) = self.output.pop_front() {
+                return Some(sample);
+            }
+            if !self.refill() {
+                return None;
+            }
+        }
+    }
+}
+
+impl<S> Source for TimeStretch<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.wsola.reset();
+        self.resampler.reset();
+        self.stretched.clear();
+        self.output.clear();
+        self.input_finished = false;
+        self.flushed = false;
+        Ok(())
+    }
+}
+
+/// Decodes a source ahead of playback on a thread of its own, holding at most ``read_ahead`` of decoded audio
+///
+/// Keeps slow storage or a busy decoder from starving the output while memory stays bounded however long the file is.
+/// Without any read ahead the source is decoded on the audio thread as it is played, which is the default.
+/// Either way the time it takes to decode each block is recorded to the [`AudioTap`].
+/// Only the source without read ahead can seek, so seek before wrapping it
+pub(crate) struct ReadAhead {
+    direct: Option<Box<dyn Source<Item = f32> + Send>>,
+    blocks: Option<Receiver<Vec<f32>>>,
+    block: Vec<f32>,
+    position: usize,
+    channels: u16,
+    sample_rate: u32,
+    tap: Arc<AudioTap>,
+}
+
+impl ReadAhead {
+    pub(crate) fn new(
+        mut input: Box<dyn Source<Item = f32> + Send>,
+        read_ahead: Duration,
+        tap: Arc<AudioTap>,
+    ) -> Self {
+        let channels = input.channels().max(1);
+        let sample_rate = input.sample_rate();
+        let block_length = BLOCK_FRAMES as f64 / sample_rate.max(1) as f64;
+        let capacity = (read_ahead.as_secs_f64() / block_length).ceil() as usize;
+        if capacity == 0 {
+            return Self {
+                direct: Some(input),
+                blocks: None,
+                block: vec![],
+                position: 0,
+                channels,
+                sample_rate,
+                tap,
+            };
+        }
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let decode_tap = Arc::clone(&tap);
+        thread::spawn(move || {
+            profiling::register_thread!("egui_player decode");
+            loop {
+                let block = decode_block(&mut input, channels, sample_rate, &decode_tap);
+                // Stops once the source runs out or the stream that played it is gone
+                if block.is_empty() || sender.send(block).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            direct: None,
+            blocks: Some(receiver),
+            block: vec![],
+            position: 0,
+            channels,
+            sample_rate,
+            tap,
+        }
+    }
+}
+
+/// Decodes the next block of ``input`` and records how long that took, the block is empty once ``input`` runs out
+fn decode_block(
+    input: &mut Box<dyn Source<Item = f32> + Send>,
+    channels: u16,
+    sample_rate: u32,
+    tap: &AudioTap,
+) -> Vec<f32> {
+    profiling::scope!("decode_block");
+    let started = Instant::now();
+    let block: Vec<f32> = input
+        .by_ref()
+        .take(BLOCK_FRAMES * channels as usize)
+        .collect();
+    if !block.is_empty() {
+        let frames = block.len() / channels as usize;
+        tap.record_decode(
+            started.elapsed(),
+            Duration::from_secs_f64(frames as f64 / sample_rate.max(1) as f64),
+        );
+    }
+    block
+}
+
+impl Iterator for ReadAhead {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.block.len() {
+            self.block = match &mut self.direct {
+                Some(input) => decode_block(input, self.channels, self.sample_rate, &self.tap),
+                None => self.blocks.as_ref()?.recv().ok()?,
+            };
+            self.position = 0;
+        }
+        let sample = self.block.get(self.position).copied();
+        self.position += 1;
+        sample
+    }
+}
+
+impl Source for ReadAhead {
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        match &mut self.direct {
+            Some(input) => {
+                input.try_seek(pos)?;
+                // What was decoded before the seek is not played anymore
+                self.block.clear();
+                self.position = 0;
+                Ok(())
+            }
+            None => Err(SeekError::NotSupported {
+                underlying_source: "ReadAhead",
+            }),
+        }
+    }
+}
+
+/// Measurements of the audio that is being output, written by the audio stream and read by the UI
+#[derive(Debug, Default)]
+pub(crate) struct AudioTap {
+    state: Mutex<TapState>,
+    /// Blocks that took longer to decode than they take to play
+    slow_blocks: AtomicU64,
+    decode_errors: AtomicU64,
+    decode_nanos: AtomicU64,
+    blocks: AtomicU64,
+    /// Output time decoded and played since the stream started, the difference is what is buffered
+    decoded_nanos: AtomicU64,
+    played_nanos: AtomicU64,
+    streaming: AtomicBool,
+    /// Whether the output reached full scale before the [`Limiter`] since the last check
+    clipped: AtomicBool,
+    /// Reported by the decoder, until the player hands them to the hook of [`crate::player::Player::on_event`]
+    events: Mutex<Vec<PlayerEvent>>,
+}
+
+#[derive(Debug, Default)]
+struct TapState {
+    sample_rate: u32,
+    recent: VecDeque<f32>,
+    written: u64,
+    peaks: Vec<f32>,
+    squares: Vec<f32>,
+    frames: usize,
+}
+
+impl AudioTap {
+    fn write(&self, samples: &[f32], channels: u16, sample_rate: u32) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        let channels = channels as usize;
+        if state.peaks.len() != channels || state.sample_rate != sample_rate {
+            state.sample_rate = sample_rate;
+            state.recent.clear();
+            state.peaks = vec![0.0; channels];
+            state.squares = vec![0.0; channels];
+            state.frames = 0;
+        }
+
+        let capacity = (TAP_DURATION.as_secs_f32() * sample_rate as f32) as usize * channels;
+        state.recent.extend(samples);
+        state.written += samples.len() as u64;
+        let overflow = state.recent.len().saturating_sub(capacity);
+        state.recent.drain(..overflow);
+
+        for frame in samples.chunks(channels) {
+            for (channel, sample) in frame.iter().enumerate() {
+                state.peaks[channel] = state.peaks[channel].max(sample.abs());
+                state.squares[channel] += sample * sample;
+            }
+        }
+        state.frames += samples.len() / channels;
+    }
+
+    /// Copies the interleaved samples written since ``cursor`` and moves the cursor past them
+    ///
+    /// Returns the samples along with their channel count and sample rate.
+    /// A reader that falls behind by more than [`TAP_DURATION`] only receives the most recent part
+    pub(crate) fn new_samples(&self, cursor: &mut u64) -> (Vec<f32>, u16, u32) {
+        let Ok(state) = self.state.lock() else {
+            return (vec![], 1, 0);
+        };
+        let new = (state.written.saturating_sub(*cursor) as usize).min(state.recent.len());
+        *cursor = state.written;
+        (
+            state
+                .recent
+                .range(state.recent.len() - new..)
+                .copied()
+                .collect(),
+            state.peaks.len().max(1) as u16,
+            state.sample_rate,
+        )
+    }
+
+    /// Counts a block that took ``decode_time`` to decode and lasts ``duration`` when played
+    fn record_decode(&self, decode_time: Duration, duration: Duration) {
+        self.decode_nanos
+            .fetch_add(decode_time.as_nanos() as u64, Ordering::Relaxed);
+        self.blocks.fetch_add(1, Ordering::Relaxed);
+        if decode_time > duration {
+            self.slow_blocks.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts output that is ready to be played and lasts ``duration``
+    fn record_output(&self, duration: Duration) {
+        self.decoded_nanos
+            .fetch_add(duration.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Marks the start of a new stream, which plays from the start of its output
+    pub(crate) fn start_stream(&self) {
+        self.decoded_nanos.store(0, Ordering::Relaxed);
+        self.played_nanos.store(0, Ordering::Relaxed);
+        self.streaming.store(true, Ordering::Relaxed);
+    }
+
+    /// Output time of the stream that has been played so far, as told by the sink
+    pub(crate) fn record_played(&self, played: Duration) {
+        self.played_nanos
+            .store(played.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn end_stream(&self) {
+        self.streaming.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns whether a stream is running, how much of its output was played and how much is decoded but not played yet
+    pub(crate) fn stream_stats(&self) -> (bool, Duration, Duration) {
+        let decoded = self.decoded_nanos.load(Ordering::Relaxed);
+        let played = self.played_nanos.load(Ordering::Relaxed);
+        (
+            self.streaming.load(Ordering::Relaxed),
+            Duration::from_nanos(played),
+            Duration::from_nanos(decoded.saturating_sub(played)),
+        )
+    }
+
+    pub(crate) fn record_decode_error(&self) {
+        self.decode_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counts a corrupt packet at ``position`` that the decoder skipped
+    pub(crate) fn record_decode_warning(&self, position: Duration, message: String) {
+        self.record_decode_error();
+        self.push_event(PlayerEvent::DecodeWarning { position, message });
+    }
+
+    /// Counts the error at ``position`` that the decoder could not go on after
+    pub(crate) fn record_decode_failure(&self, position: Duration, message: String) {
+        self.record_decode_error();
+        self.push_event(PlayerEvent::DecodeFailed { position, message });
+    }
+
+    fn push_event(&self, event: PlayerEvent) {
+        if let Ok(mut events) = self.events.lock()
+            && events.len() < MAX_PENDING_EVENTS
+        {
+            events.push(event);
+        }
+    }
+
+    /// Events reported since the last call
+    pub(crate) fn take_events(&self) -> Vec<PlayerEvent> {
+        self.events
+            .lock()
+            .map(|mut events| mem::take(&mut *events))
+            .unwrap_or_default()
+    }
+
+    /// Returns the amount of slow blocks, the amount of decode errors and the average time it took to decode a block
+    pub(crate) fn decode_stats(&self) -> (u64, u64, Duration) {
+        let blocks = self.blocks.load(Ordering::Relaxed).max(1);
+        (
+            self.slow_blocks.load(Ordering::Relaxed),
+            self.decode_errors.load(Ordering::Relaxed),
+            Duration::from_nanos(self.decode_nanos.load(Ordering::Relaxed) / blocks),
+        )
+    }
+
+    /// Whether the output would have clipped since the last call, with or without the [`Limiter`] catching it
+    pub(crate) fn take_clipped(&self) -> bool {
+        self.clipped.swap(false, Ordering::Relaxed)
+    }
+
+    /// Peak and RMS level of each channel measured since the last call
+    pub(crate) fn take_levels(&self) -> Vec<ChannelLevel> {
+        let Ok(mut state) = self.state.lock() else {
+            return vec![];
+        };
+        let frames = state.frames.max(1) as f32;
+        let levels = state
+            .peaks
+            .iter()
+            .zip(state.squares.iter())
+            .map(|(peak, squares)| ChannelLevel {
+                peak: *peak,
+                rms: (squares / frames).sqrt(),
+            })
+            .collect();
+        state.peaks.fill(0.0);
+        state.squares.fill(0.0);
+        state.frames = 0;
+        levels
+    }
+}
+
+/// Runs the user [`EffectsChain`] over blocks of a source, then downmixes them to mono if enabled in [`DspSettings`]
+///
+/// Finished blocks are written to the [`AudioTap`] so the UI can visualize what is being heard
+pub(crate) struct EffectsStage<S>
+where
+    S: Source<Item = f32>,
+{
+    input: S,
+    effects: Arc<Mutex<EffectsChain>>,
+    settings: Arc<DspSettings>,
+    tap: Arc<AudioTap>,
+    channels: u16,
+    sample_rate: u32,
+    block: Vec<f32>,
+    position: usize,
+    limiter: Limiter,
+}
+
+impl<S> EffectsStage<S>
+where
+    S: Source<Item = f32>,
+{
+    pub(crate) fn new(
+        input: S,
+        effects: Arc<Mutex<EffectsChain>>,
+        settings: Arc<DspSettings>,
+        tap: Arc<AudioTap>,
+    ) -> Self {
+        let channels = input.channels().max(1);
+        let sample_rate = input.sample_rate();
+        Self {
+            input,
+            effects,
+            settings,
+            tap,
+            channels,
+            sample_rate,
+            block: Vec::with_capacity(BLOCK_FRAMES * channels as usize),
+            position: 0,
+            limiter: Limiter::default(),
+        }
+    }
+
+    /// Reads and processes the next block, returns false when the input has run out
+    fn refill(&mut self) -> bool {
+        profiling::scope!("process_block");
+        self.block.clear();
+        self.position = 0;
+        self.block.extend(
+            self.input
+                .by_ref()
+                .take(BLOCK_FRAMES * self.channels as usize),
+        );
+        if self.block.is_empty() {
+            return false;
+        }
+        let frames = self.block.len() / self.channels as usize;
+        self.tap.record_output(Duration::from_secs_f64(
+            frames as f64 / self.sample_rate.max(1) as f64,
+        ));
+        if let Ok(mut effects) = self.effects.lock() {
+            effects.process(&mut self.block, self.channels, self.sample_rate);
+        }
+        let gain = self.settings.gain();
+        if gain != 1.0 {
+            for sample in &mut self.block {
+                *sample *= gain;
+            }
+        }
+        if self.settings.mono() && self.channels > 1 {
+            downmix(&mut self.block, self.channels as usize);
+        }
+        // Levels are compared with what comes out after the volume
+        let ceiling = 1.0 / self.settings.output_volume().max(0.001);
+        if self.block.iter().any(|sample| sample.abs() >= ceiling) {
+            self.tap.clipped.store(true, Ordering::Relaxed);
+        }
+        if self.settings.limiter() {
+            self.limiter.process(
+                &mut self.block,
+                self.channels as usize,
+                self.sample_rate,
+                ceiling,
+            );
+        }
+        self.tap.write(&self.block, self.channels, self.sample_rate);
+        true
+    }
+}
+
+impl<S> Iterator for EffectsStage<S>
+where
+    S: Source<Item = f32>,
+{
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.position >= self.block.len() && !self.refill() {
+            return None;
+        }
+        let sample = self.block[self.position];
+        self.position += 1;
+        Some(sample)
+    }
+}
+
+impl<S> Source for EffectsStage<S>
+where
+    S: Source<Item = f32>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.input.try_seek(pos)?;
+        self.block.clear();
+        self.position = 0;
+        if let Ok(mut effects) = self.effects.lock() {
+            effects.reset();
+        }
+        Ok(())
+    }
+}
+
+/// Limiter that keeps the output below full scale, only stepping in where it would otherwise clip
+///
+/// Frames louder than the ceiling quickly turn the gain down for all channels together, whatever still gets past
+/// the ceiling while the gain comes down is clamped to it. Audio that stays below the ceiling passes untouched,
+/// so files played at unity gain without boosts are never limited
+#[derive(Debug)]
+struct Limiter {
+    gain: f32,
+}
+
+impl Default for Limiter {
+    fn default() -> Self {
+        Self { gain: 1.0 }
+    }
+}
+
+impl Limiter {
+    fn process(&mut self, samples: &mut [f32], channels: usize, sample_rate: u32, ceiling: f32) {
+        let coefficient = |time: Duration| (-1.0 / (time.as_secs_f32() * sample_rate as f32)).exp();
+        let (attack, release) = (coefficient(LIMITER_ATTACK), coefficient(LIMITER_RELEASE));
+        for frame in samples.chunks_mut(channels.max(1)) {
+            let peak = frame
+                .iter()
+                .fold(0.0_f32, |peak, sample| peak.max(sample.abs()));
+            let wanted = if peak > ceiling { ceiling / peak } else { 1.0 };
+            let smoothing = if wanted < self.gain { attack } else { release };
+            self.gain = wanted + (self.gain - wanted) * smoothing;
+            for sample in frame {
+                *sample = (*sample * self.gain).clamp(-ceiling, ceiling);
+            }
+        }
+    }
+}
+
+/// Replaces every channel of each frame with the average of all channels
+fn downmix(samples: &mut [f32], channels: usize) {
+    for frame in samples.chunks_mut(channels) {
+        let average = frame.iter().sum::<f32>() / frame.len() as f32;
+        frame.fill(average);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+
+    const SAMPLE_RATE: u32 = 44_100;
+
+    /// One second of a mono sine wave at ``frequency``
+    fn sine(frequency: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE)
+            .map(|n| (2.0 * PI * frequency * n as f32 / SAMPLE_RATE as f32).sin() * 0.5)
+            .collect()
+    }
+
+    /// Plays ``samples`` through a [`TimeStretch`] at ``speed``
+    fn stretch(samples: Vec<f32>, speed: f32, mode: SpeedMode) -> Vec<f32> {
+        let settings = Arc::new(DspSettings::new());
+        settings.set_speed(speed);
+        settings.set_speed_mode(mode);
+        TimeStretch::new(SamplesBuffer::new(1, SAMPLE_RATE, samples), settings).collect()
+    }
+
+    /// Frequency of mono ``samples`` counted from how often they cross zero
+    fn frequency(samples: &[f32]) -> f32 {
+        let crossings = samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0.0) != (pair[1] < 0.0))
+            .count();
+        crossings as f32 / 2.0 / (samples.len() as f32 / SAMPLE_RATE as f32)
+    }
+
+    #[test]
+    fn stretching_plays_all_of_the_input() {
+        for speed in [0.5, 1.0, 2.0] {
+            for mode in [SpeedMode::Resample, SpeedMode::PreservePitch] {
+                let output = stretch(sine(440.0), speed, mode);
+                let expected = SAMPLE_RATE as f32 / speed;
+                assert!(
+                    (output.len() as f32 - expected).abs() <= 2.0,
+                    "{} samples at {speed}x with {mode:?}, expected {expected}",
+                    output.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn stretching_keeps_the_pitch() {
+        for speed in [0.5, 2.0] {
+            let output = stretch(sine(440.0), speed, SpeedMode::PreservePitch);
+            // The edges are left out, the count only holds over whole periods
+            let middle = &output[output.len() / 10..output.len() * 9 / 10];
+            let frequency = frequency(middle);
+            assert!(
+                (frequency - 440.0).abs() < 440.0 * 0.03,
+                "{frequency} Hz at {speed}x"
+            );
+        }
+    }
+
+    #[test]
+    fn normal_speed_passes_the_input_through() {
+        let input = sine(440.0);
+        for mode in [SpeedMode::Resample, SpeedMode::PreservePitch] {
+            let output = stretch(input.clone(), 1.0, mode);
+            assert_eq!(output.len(), input.len());
+            for (index, (output, input)) in output.iter().zip(&input).enumerate() {
+                assert!(
+                    (output - input).abs() < 1e-5,
+                    "sample {index} is {output} instead of {input} with {mode:?}"
+                );
+            }
+        }
+    }
+
+    /// One second of a mono square wave between ``-level`` and ``level``
+    fn square(level: f32) -> Vec<f32> {
+        (0..SAMPLE_RATE)
+            .map(|n| if n / 50 % 2 == 0 { level } else { -level })
+            .collect()
+    }
+
+    /// Plays ``samples`` through an [`EffectsStage`] without effects, returns the output and whether it clipped
+    fn limit(samples: Vec<f32>, settings: DspSettings) -> (Vec<f32>, bool) {
+        let tap = Arc::new(AudioTap::default());
+        let output = EffectsStage::new(
+            SamplesBuffer::new(1, SAMPLE_RATE, samples),
+            Arc::new(Mutex::new(EffectsChain::default())),
+            Arc::new(settings),
+            Arc::clone(&tap),
+        )
+        .collect();
+        (output, tap.take_clipped())
+    }
+
+    #[test]
+    fn boosted_audio_stays_below_the_ceiling() {
+        for output_volume in [1.0, 2.0] {
+            let settings = DspSettings::new();
+            settings.set_gain(2.0);
+            settings.set_output_volume(output_volume);
+            let ceiling = 1.0 / output_volume;
+            let (output, clipped) = limit(square(1.0), settings);
+            assert!(clipped);
+            assert!(output.iter().all(|sample| sample.abs() <= ceiling));
+            // Once the gain has come down the wave keeps its shape right at the ceiling
+            let settled = &output[SAMPLE_RATE as usize / 10..];
+            assert!(settled
+                .iter()
+                .all(|sample| (sample.abs() - ceiling).abs() < ceiling * 0.01));
+        }
+    }
+
+    #[test]
+    fn limiter_turns_the_gain_down_instead_of_clipping() {
+        let mut limiter = Limiter::default();
+        let mut samples = square(2.0);
+        limiter.process(&mut samples, 1, SAMPLE_RATE, 1.0);
+        // The gain alone keeps the peaks at the ceiling, the clamp only catches the first frames of the attack
+        assert!(limiter.gain * 2.0 <= 1.0 + f32::EPSILON);
+        assert!(limiter.gain * 2.0 > 0.99);
+    }
+
+    #[test]
+    fn unboosted_audio_passes_untouched() {
+        let input = square(0.9);
+        let (output, clipped) = limit(input.clone(), DspSettings::new());
+        assert!(!clipped);
+        assert_eq!(output, input);
+    }
+
+    #[test]
+    fn pending_events_are_capped() {
+        let tap = AudioTap::default();
+        for packet in 0..MAX_PENDING_EVENTS * 2 {
+            tap.record_decode_warning(Duration::from_millis(packet as u64), "corrupt".to_string());
+        }
+        assert_eq!(tap.take_events().len(), MAX_PENDING_EVENTS);
+        assert!(tap.take_events().is_empty());
+        assert_eq!(tap.decode_stats().1, MAX_PENDING_EVENTS as u64 * 2);
+    }
+
+    #[test]
+    fn decoding_is_timed_with_and_without_read_ahead() {
+        for read_ahead in [Duration::ZERO, Duration::from_secs(1)] {
+            let tap = Arc::new(AudioTap::default());
+            let samples = sine(440.0);
+            let input = Box::new(SamplesBuffer::new(1, SAMPLE_RATE, samples.clone()));
+            let played: Vec<f32> = ReadAhead::new(input, read_ahead, Arc::clone(&tap)).collect();
+            assert_eq!(played, samples);
+            assert_eq!(
+                tap.blocks.load(Ordering::Relaxed),
+                samples.len().div_ceil(BLOCK_FRAMES) as u64
+            );
+        }
+    }
+}
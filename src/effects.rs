@@ -0,0 +1,269 @@
+use std::{f32::consts::PI, fmt, time::Duration};
+
+/// A DSP node that can be inserted into an [`EffectsChain`]
+///
+/// Samples are passed in as interleaved blocks, so a stereo block is laid out as ``[L, R, L, R, ...]``
+///
+/// Any ``FnMut(&mut [f32])`` closure is also an [`Effect`], which is handy for quick custom processing
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::effects::{Filter, Gain};
+/// use egui_player::player::Player;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// let mut effects = player.effects_mut();
+/// effects.push(Filter::high_pass(120.0, 0.707));
+/// effects.push(Gain::from_db(-3.0));
+/// effects.push(|samples: &mut [f32]| samples.iter_mut().for_each(|sample| *sample = sample.clamp(-0.9, 0.9)));
+/// ```
+pub trait Effect: Send {
+    /// Processes a block of interleaved samples in place
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32);
+
+    /// Clears internal state such as delay lines. Called whenever the player seeks
+    fn reset(&mut self) {}
+}
+
+impl<F> Effect for F
+where
+    F: FnMut(&mut [f32]) + Send,
+{
+    fn process(&mut self, samples: &mut [f32], _channels: u16, _sample_rate: u32) {
+        self(samples)
+    }
+}
+
+/// Ordered list of [`Effect`]s applied to the audio before it is output
+///
+/// Get one from [`crate::player::Player::effects_mut`], effects run in the order they were inserted
+#[derive(Default)]
+pub struct EffectsChain {
+    effects: Vec<Box<dyn Effect>>,
+    /// Skips every effect in the chain while ``true``
+    pub bypass: bool,
+}
+
+impl EffectsChain {
+    /// Adds an effect at the end of the chain
+    pub fn push(&mut self, effect: impl Effect + 'static) {
+        self.effects.push(Box::new(effect));
+    }
+
+    /// Adds an effect at ``index``, shifting all effects after it
+    pub fn insert(&mut self, index: usize, effect: impl Effect + 'static) {
+        self.effects.insert(index, Box::new(effect));
+    }
+
+    /// Removes and returns the effect at ``index``
+    pub fn remove(&mut self, index: usize) -> Box<dyn Effect> {
+        self.effects.remove(index)
+    }
+
+    /// Removes every effect
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Runs every effect over a block of interleaved samples
+    pub fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        if self.bypass {
+            return;
+        }
+        for effect in &mut self.effects {
+            effect.process(samples, channels, sample_rate);
+        }
+    }
+
+    /// Resets the state of every effect
+    pub fn reset(&mut self) {
+        for effect in &mut self.effects {
+            effect.reset();
+        }
+    }
+}
+
+impl fmt::Debug for EffectsChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EffectsChain")
+            .field("effects", &self.effects.len())
+            .field("bypass", &self.bypass)
+            .finish()
+    }
+}
+
+/// Multiplies every sample by ``gain``
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Gain {
+    pub gain: f32,
+}
+
+impl Gain {
+    pub fn new(gain: f32) -> Self {
+        Self { gain }
+    }
+
+    /// Creates a [`Gain`] from decibels, ``-6.0`` roughly halves the amplitude
+    pub fn from_db(db: f32) -> Self {
+        Self::new(10.0_f32.powf(db / 20.0))
+    }
+}
+
+impl Effect for Gain {
+    fn process(&mut self, samples: &mut [f32], _channels: u16, _sample_rate: u32) {
+        for sample in samples {
+            *sample *= self.gain;
+        }
+    }
+}
+
+/// Response of a [`Filter`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FilterKind {
+    LowPass,
+    HighPass,
+    BandPass,
+}
+
+/// Second order (biquad) filter
+///
+/// ``cutoff`` is in Hz and ``q`` controls the resonance, ``0.707`` gives a flat response
+#[derive(Debug, Clone)]
+pub struct Filter {
+    pub kind: FilterKind,
+    pub cutoff: f32,
+    pub q: f32,
+    coefficients: [f32; 5],
+    designed_for: Option<(FilterKind, f32, f32, u32)>,
+    state: Vec<[f32; 4]>,
+}
+
+impl Filter {
+    pub fn new(kind: FilterKind, cutoff: f32, q: f32) -> Self {
+        Self {
+            kind,
+            cutoff,
+            q,
+            coefficients: [0.0; 5],
+            designed_for: None,
+            state: vec![],
+        }
+    }
+
+    pub fn low_pass(cutoff: f32, q: f32) -> Self {
+        Self::new(FilterKind::LowPass, cutoff, q)
+    }
+
+    pub fn high_pass(cutoff: f32, q: f32) -> Self {
+        Self::new(FilterKind::HighPass, cutoff, q)
+    }
+
+    pub fn band_pass(cutoff: f32, q: f32) -> Self {
+        Self::new(FilterKind::BandPass, cutoff, q)
+    }
+
+    /// Recalculates the coefficients (RBJ audio EQ cookbook) when the settings or sample rate changed
+    fn design(&mut self, sample_rate: u32) {
+        let design = (self.kind, self.cutoff, self.q, sample_rate);
+        if self.designed_for == Some(design) {
+            return;
+        }
+        let cutoff = self.cutoff.clamp(1.0, sample_rate as f32 * 0.49);
+        let omega = 2.0 * PI * cutoff / sample_rate as f32;
+        let alpha = omega.sin() / (2.0 * self.q.max(0.01));
+        let cos = omega.cos();
+        let (b0, b1, b2) = match self.kind {
+            FilterKind::LowPass => ((1.0 - cos) / 2.0, 1.0 - cos, (1.0 - cos) / 2.0),
+            FilterKind::HighPass => ((1.0 + cos) / 2.0, -(1.0 + cos), (1.0 + cos) / 2.0),
+            FilterKind::BandPass => (alpha, 0.0, -alpha),
+        };
+        let a0 = 1.0 + alpha;
+        self.coefficients = [
+            b0 / a0,
+            b1 / a0,
+            b2 / a0,
+            -2.0 * cos / a0,
+            (1.0 - alpha) / a0,
+        ];
+        self.designed_for = Some(design);
+    }
+}
+
+impl Effect for Filter {
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        self.design(sample_rate);
+        let channels = channels.max(1) as usize;
+        self.state.resize(channels, [0.0; 4]);
+        let [b0, b1, b2, a1, a2] = self.coefficients;
+        for frame in samples.chunks_mut(channels) {
+            for (sample, [x1, x2, y1, y2]) in frame.iter_mut().zip(self.state.iter_mut()) {
+                let output = b0 * *sample + b1 * *x1 + b2 * *x2 - a1 * *y1 - a2 * *y2;
+                *x2 = *x1;
+                *x1 = *sample;
+                *y2 = *y1;
+                *y1 = output;
+                *sample = output;
+            }
+        }
+    }
+
+    fn reset(&mut self) {
+        self.state.clear();
+    }
+}
+
+/// Echo effect that mixes a delayed copy of the signal back in
+///
+/// ``feedback`` controls how much of the echo is fed back into the delay line and ``mix`` how loud the echo is
+#[derive(Debug, Clone)]
+pub struct Delay {
+    pub time: Duration,
+    pub feedback: f32,
+    pub mix: f32,
+    buffer: Vec<f32>,
+    position: usize,
+}
+
+impl Delay {
+    pub fn new(time: Duration, feedback: f32, mix: f32) -> Self {
+        Self {
+            time,
+            feedback,
+            mix,
+            buffer: vec![],
+            position: 0,
+        }
+    }
+}
+
+impl Effect for Delay {
+    fn process(&mut self, samples: &mut [f32], channels: u16, sample_rate: u32) {
+        let channels = channels.max(1) as usize;
+        let length = ((self.time.as_secs_f32() * sample_rate as f32) as usize).max(1) * channels;
+        if self.buffer.len() != length {
+            self.buffer = vec![0.0; length];
+            self.position = 0;
+        }
+        let feedback = self.feedback.clamp(0.0, 0.95);
+        for sample in samples {
+            let delayed = self.buffer[self.position];
+            self.buffer[self.position] = *sample + delayed * feedback;
+            *sample += delayed * self.mix;
+            self.position = (self.position + 1) % length;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buffer.fill(0.0);
+        self.position = 0;
+    }
+}
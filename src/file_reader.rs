@@ -0,0 +1,83 @@
+use memmap2::Mmap;
+use std::{
+    fs::File,
+    io::{self, BufReader, Cursor, Read, Seek, SeekFrom},
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+};
+use symphonia::core::io::MediaSource;
+
+/// Files at least this large are memory mapped instead of read through a buffer, once mapping is enabled
+const MAP_THRESHOLD: u64 = 16 * 1024 * 1024;
+
+/// Whether large files are memory mapped, set with [`crate::media_information::set_memory_mapping`]
+static MEMORY_MAPPING: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn set_memory_mapping(enabled: bool) {
+    MEMORY_MAPPING.store(enabled, Ordering::Relaxed);
+}
+
+/// A local file opened for decoding or hashing
+///
+/// With memory mapping enabled large files are mapped, so every player and analysis of the same file reads from the
+/// pages the system already holds instead of filling buffers of their own. Other files, and files that can not be
+/// mapped, are read through a buffer
+pub(crate) enum FileReader {
+    Buffered {
+        reader: BufReader<File>,
+        length: u64,
+    },
+    Mapped(Cursor<Mmap>),
+}
+
+impl FileReader {
+    pub(crate) fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let length = file.metadata()?.len();
+        if length >= MAP_THRESHOLD && MEMORY_MAPPING.load(Ordering::Relaxed) {
+            // SAFETY: The map is only ever read. Mapping is opt in because a file that another process truncates while
+            // it is mapped makes reading past its new end crash instead of fail
+            match unsafe { Mmap::map(&file) } {
+                Ok(map) => return Ok(Self::Mapped(Cursor::new(map))),
+                Err(error) => {
+                    log::debug!("Could not memory map a file, reading it instead: {error}")
+                }
+            }
+        }
+        Ok(Self::Buffered {
+            reader: BufReader::new(file),
+            length,
+        })
+    }
+}
+
+impl Read for FileReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Buffered { reader, .. } => reader.read(buf),
+            Self::Mapped(map) => map.read(buf),
+        }
+    }
+}
+
+impl Seek for FileReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        match self {
+            Self::Buffered { reader, .. } => reader.seek(pos),
+            Self::Mapped(map) => map.seek(pos),
+        }
+    }
+}
+
+impl MediaSource for FileReader {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        match self {
+            Self::Buffered { length, .. } => Some(*length),
+            Self::Mapped(map) => Some(map.get_ref().len() as u64),
+        }
+    }
+}
@@ -0,0 +1,391 @@
+use rodio::Source;
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+use crate::media_information;
+
+/// Samples per channel in every frame but the last
+const BLOCK_SIZE: usize = 4096;
+
+/// Everything is encoded as 16 bit samples
+const BITS_PER_SAMPLE: u32 = 16;
+
+/// Largest Rice parameter that can be written with the 4 bit encoding, 15 is reserved as an escape code
+const MAX_RICE_PARAMETER: u32 = 14;
+
+/// Encodes ``source`` into a FLAC file at ``path``
+///
+/// Each channel of a frame picks the fixed predictor (order 0 to 4) with the smallest residual
+/// and Rice codes it, falling back to storing the samples verbatim when that is smaller
+pub(crate) fn write_flac(source: impl Source<Item = f32>, path: &Path) -> io::Result<()> {
+    let channels = source.channels().clamp(1, 8) as usize;
+    let sample_rate = source.sample_rate();
+    let mut file = BufWriter::new(File::create(path)?);
+
+    let mut info = StreamInfo {
+        sample_rate,
+        channels: channels as u32,
+        min_frame_size: u32::MAX,
+        max_frame_size: 0,
+        total_samples: 0,
+    };
+    file.write_all(b"fLaC")?;
+    // Last metadata block, type 0 (STREAMINFO), 34 bytes long
+    file.write_all(&[0x80, 0, 0, 34])?;
+    file.write_all(&info.to_bytes())?;
+
+    let mut block: Vec<Vec<i32>> = vec![Vec::with_capacity(BLOCK_SIZE); channels];
+    let mut frame_number = 0;
+    let mut channel = 0;
+    for sample in source {
+        block[channel].push(media_information::to_i16(sample) as i32);
+        channel = (channel + 1) % channels;
+        if channel == 0 && block[0].len() == BLOCK_SIZE {
+            write_frame(&mut file, &mut info, &block, frame_number)?;
+            frame_number += 1;
+            block.iter_mut().for_each(Vec::clear);
+        }
+    }
+    // Write what is left, dropping samples of a frame that is missing channels
+    let length = block.iter().map(Vec::len).min().unwrap_or(0);
+    if length > 0 {
+        block
+            .iter_mut()
+            .for_each(|samples| samples.truncate(length));
+        write_frame(&mut file, &mut info, &block, frame_number)?;
+    }
+
+    // Now that the length is known fill in the STREAMINFO block
+    let mut file = file.into_inner().map_err(|error| error.into_error())?;
+    if info.min_frame_size == u32::MAX {
+        info.min_frame_size = 0;
+    }
+    file.seek(SeekFrom::Start(8))?;
+    file.write_all(&info.to_bytes())?;
+    Ok(())
+}
+
+struct StreamInfo {
+    sample_rate: u32,
+    channels: u32,
+    min_frame_size: u32,
+    max_frame_size: u32,
+    total_samples: u64,
+}
+
+impl StreamInfo {
+    fn to_bytes(&self) -> [u8; 34] {
+        let mut writer = BitWriter::default();
+        writer.write(BLOCK_SIZE as u64, 16);
+        writer.write(BLOCK_SIZE as u64, 16);
+        writer.write(self.min_frame_size as u64, 24);
+        writer.write(self.max_frame_size as u64, 24);
+        writer.write(self.sample_rate as u64, 20);
+        writer.write(self.channels as u64 - 1, 3);
+        writer.write(BITS_PER_SAMPLE as u64 - 1, 5);
+        writer.write(self.total_samples, 36);
+        // MD5 of the audio, zero means it was not computed
+        writer.write(0, 64);
+        writer.write(0, 64);
+        writer.bytes.try_into().unwrap()
+    }
+}
+
+fn write_frame(
+    file: &mut impl Write,
+    info: &mut StreamInfo,
+    block: &[Vec<i32>],
+    frame_number: u64,
+) -> io::Result<()> {
+    let length = block[0].len();
+    let mut writer = BitWriter::default();
+    // Sync code with the fixed blocksize strategy
+    writer.write(0b1111_1111_1111_1000, 16);
+    // Block size stored as a 16 bit value after the frame number
+    writer.write(0b0111, 4);
+    // Sample rate taken from STREAMINFO
+    writer.write(0b0000, 4);
+    // Channels coded independently
+    writer.write(block.len() as u64 - 1, 4);
+    // 16 bits per sample
+    writer.write(0b100, 3);
+    writer.write(0, 1);
+    writer.write_utf8(frame_number);
+    writer.write(length as u64 - 1, 16);
+    let crc = crc8(&writer.bytes);
+    writer.write(crc as u64, 8);
+
+    for samples in block {
+        write_subframe(&mut writer, samples);
+    }
+    writer.align();
+    let crc = crc16(&writer.bytes);
+    writer.write(crc as u64, 16);
+
+    let size = writer.bytes.len() as u32;
+    info.min_frame_size = info.min_frame_size.min(size);
+    info.max_frame_size = info.max_frame_size.max(size);
+    info.total_samples += length as u64;
+    file.write_all(&writer.bytes)
+}
+
+fn write_subframe(writer: &mut BitWriter, samples: &[i32]) {
+    if samples.iter().all(|sample| *sample == samples[0]) {
+        // Constant subframe
+        writer.write(0b0000_0000, 8);
+        writer.write_signed(samples[0], BITS_PER_SAMPLE);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * BITS_PER_SAMPLE as u64;
+    let best = (0..=4.min(samples.len() - 1))
+        .map(|order| {
+            let residual = fixed_residual(samples, order);
+            let (parameter, bits) = rice_parameter(&residual);
+            (
+                order,
+                residual,
+                parameter,
+                bits + (order as u64 * BITS_PER_SAMPLE as u64),
+            )
+        })
+        .min_by_key(|(_, _, _, bits)| *bits);
+
+    match best {
+        Some((order, residual, parameter, bits)) if bits < verbatim_bits => {
+            // Fixed subframe of the chosen order
+            writer.write(0b0001_0000 | ((order as u64) << 1), 8);
+            for sample in &samples[..order] {
+                writer.write_signed(*sample, BITS_PER_SAMPLE);
+            }
+            // Rice coding with 4 bit parameters and a single partition
+            writer.write(0b00, 2);
+            writer.write(0, 4);
+            writer.write(parameter as u64, 4);
+            for value in residual {
+                writer.write_rice(value, parameter);
+            }
+        }
+        _ => {
+            // Verbatim subframe
+            writer.write(0b0000_0010, 8);
+            for sample in samples {
+                writer.write_signed(*sample, BITS_PER_SAMPLE);
+            }
+        }
+    }
+}
+
+/// Difference between the samples and the fixed polynomial predictor of ``order``
+fn fixed_residual(samples: &[i32], order: usize) -> Vec<i32> {
+    (order..samples.len())
+        .map(|index| {
+            let s = |back: usize| samples[index - back];
+            match order {
+                0 => s(0),
+                1 => s(0) - s(1),
+                2 => s(0) - 2 * s(1) + s(2),
+                3 => s(0) - 3 * s(1) + 3 * s(2) - s(3),
+                _ => s(0) - 4 * s(1) + 6 * s(2) - 4 * s(3) + s(4),
+            }
+        })
+        .collect()
+}
+
+/// Picks the Rice parameter that codes ``residual`` in the least amount of bits, returns it along with that amount
+fn rice_parameter(residual: &[i32]) -> (u32, u64) {
+    (0..=MAX_RICE_PARAMETER)
+        .map(|parameter| {
+            let bits = residual
+                .iter()
+                .map(|value| (zigzag(*value) >> parameter) as u64 + 1 + parameter as u64)
+                .sum::<u64>();
+            (parameter, bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap()
+}
+
+/// Maps signed values onto unsigned ones so small magnitudes stay small: 0, -1, 1, -2, 2...
+fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u64,
+    filled: u32,
+}
+
+impl BitWriter {
+    /// Appends the lowest ``bits`` bits of ``value``, most significant first
+    fn write(&mut self, value: u64, bits: u32) {
+        if bits > 32 {
+            self.write(value >> 32, bits - 32);
+            self.write(value & 0xFFFF_FFFF, 32);
+            return;
+        }
+        self.buffer = (self.buffer << bits) | (value & ((1 << bits) - 1));
+        self.filled += bits;
+        while self.filled >= 8 {
+            self.filled -= 8;
+            self.bytes.push((self.buffer >> self.filled) as u8);
+        }
+    }
+
+    fn write_signed(&mut self, value: i32, bits: u32) {
+        self.write(value as u32 as u64, bits);
+    }
+
+    fn write_rice(&mut self, value: i32, parameter: u32) {
+        let value = zigzag(value);
+        let mut quotient = value >> parameter;
+        while quotient >= 32 {
+            self.write(0, 32);
+            quotient -= 32;
+        }
+        self.write(1, quotient + 1);
+        self.write(value as u64, parameter);
+    }
+
+    /// Frame numbers use the same variable length coding as UTF-8
+    fn write_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+        let mut continuation = 1;
+        while value >= 1 << (5 * continuation + 6) {
+            continuation += 1;
+        }
+        let lead = (0xFF00_u64 >> (continuation + 1)) & 0xFF;
+        self.write(lead | (value >> (6 * continuation)), 8);
+        for index in (0..continuation).rev() {
+            self.write(0x80 | ((value >> (6 * index)) & 0x3F), 8);
+        }
+    }
+
+    /// Pads with zeros up to the next byte
+    fn align(&mut self) {
+        if self.filled > 0 {
+            self.write(0, 8 - self.filled);
+        }
+    }
+}
+
+fn crc8(bytes: &[u8]) -> u8 {
+    bytes.iter().fold(0, |mut crc, byte| {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+fn crc16(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(0, |mut crc, byte| {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::buffer::SamplesBuffer;
+    use std::{f32::consts::TAU, fs};
+    use symphonia::core::{
+        audio::SampleBuffer, codecs::DecoderOptions, errors::Error, formats::FormatOptions,
+        io::MediaSourceStream, meta::MetadataOptions, probe::Hint,
+    };
+
+    /// Decodes the FLAC file at ``path`` with symphonia into its channel count, sample rate and interleaved samples
+    fn decode(path: &Path) -> (usize, u32, Vec<i16>) {
+        let mut hint = Hint::new();
+        hint.with_extension("flac");
+        let probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                MediaSourceStream::new(Box::new(File::open(path).unwrap()), Default::default()),
+                &FormatOptions::default(),
+                &MetadataOptions::default(),
+            )
+            .unwrap();
+        let mut format = probed.format;
+        let params = format.default_track().unwrap().codec_params.clone();
+        let mut decoder = symphonia::default::get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .unwrap();
+        let mut samples = vec![];
+        loop {
+            let packet = match format.next_packet() {
+                Ok(packet) => packet,
+                Err(Error::IoError(error)) if error.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(error) => panic!("could not read the encoded file: {error}"),
+            };
+            let decoded = decoder.decode(&packet).unwrap();
+            let mut buffer = SampleBuffer::<i16>::new(decoded.capacity() as u64, *decoded.spec());
+            buffer.copy_interleaved_ref(decoded);
+            samples.extend_from_slice(buffer.samples());
+        }
+        (
+            params.channels.unwrap().count(),
+            params.sample_rate.unwrap(),
+            samples,
+        )
+    }
+
+    /// Encodes ``samples`` and checks that decoding them gives back exactly what was encoded
+    fn assert_round_trip(name: &str, channels: u16, samples: Vec<f32>) {
+        let path =
+            std::env::temp_dir().join(format!("egui_player_{name}_{}.flac", std::process::id()));
+        let expected: Vec<i16> = samples
+            .iter()
+            .map(|sample| media_information::to_i16(*sample))
+            .collect();
+        write_flac(SamplesBuffer::new(channels, 44_100, samples), &path).unwrap();
+        let decoded = decode(&path);
+        fs::remove_file(&path).unwrap();
+        assert_eq!(decoded, (channels as usize, 44_100, expected));
+    }
+
+    #[test]
+    fn stereo_round_trip_is_lossless() {
+        // One and a half blocks so the last frame is shorter than the others
+        let frames = BLOCK_SIZE * 3 / 2;
+        let samples = (0..frames)
+            .flat_map(|frame| {
+                let tone = (frame as f32 / 44_100.0 * 440.0 * TAU).sin() * 0.8;
+                // Jumps around too much to be predicted, so its subframes are stored verbatim
+                let noise = (frame * 7919 % 65_536) as f32 / 32_768.0 - 1.0;
+                [tone, noise]
+            })
+            .collect();
+        assert_round_trip("stereo", 2, samples);
+    }
+
+    #[test]
+    fn short_mono_round_trip_is_lossless() {
+        let samples = (0..100)
+            .map(|frame| frame as f32 / 50.0 - 1.0)
+            .chain([1.0, -1.0, 0.0, 1.5])
+            .collect();
+        assert_round_trip("mono", 1, samples);
+    }
+}
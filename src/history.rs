@@ -0,0 +1,151 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs, io,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use crate::{media_information, player::Player, InputMode};
+
+/// A single item of the [`History`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// File path, or a content hash for players made from bytes
+    pub source: String,
+    /// Where playback was last recorded
+    pub position: Duration,
+    /// Whether the item was played to the end
+    pub finished: bool,
+    pub last_played: SystemTime,
+}
+
+impl HistoryEntry {
+    /// Prefix of the ``source`` of entries identified by their content
+    const HASH_PREFIX: &str = "sha256:";
+
+    /// Whether this item can be reopened with [`History::reopen`], only file paths can be
+    pub fn is_file(&self) -> bool {
+        !self.source.starts_with(Self::HASH_PREFIX)
+    }
+}
+
+/// Recently played items along with the position they were left at, newest first
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::history::History;
+/// use egui_player::player::Player;
+///
+/// let mut history = History::load("history.json").unwrap_or_default();
+/// let player = Player::from_path("hello.mp3");
+/// // Whenever the player is closed or at a regular interval
+/// history.record(&player).unwrap();
+/// history.save("history.json").unwrap();
+///
+/// // Pick up where the newest item left off
+/// let player = history.reopen(0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    entries: Vec<HistoryEntry>,
+    /// Amount of entries kept, older entries are dropped first
+    pub capacity: usize,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        Self::new(50)
+    }
+}
+
+impl History {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: vec![],
+            capacity,
+        }
+    }
+
+    /// Reads a history written by [`History::save`]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        serde_json::from_slice(&contents).map_err(io::Error::from)
+    }
+
+    /// Writes the history as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let contents = serde_json::to_vec_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, contents)
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Identifier that [`History`] uses for ``file_input``
+    ///
+    /// Players made from bytes are identified by the hash of their content
+    pub fn source_id(file_input: &InputMode) -> io::Result<String> {
+        match file_input {
+            InputMode::FilePath(file_path) => Ok(file_path.clone()),
+            InputMode::Bytes(_) => Ok(Self::hash_id(&media_information::content_hash(file_input)?)),
+            #[cfg(feature = "streaming")]
+            InputMode::Url(url) => Ok(url.clone()),
+        }
+    }
+
+    fn hash_id(hash: &str) -> String {
+        format!("{}{hash}", HistoryEntry::HASH_PREFIX)
+    }
+
+    /// Entry of ``file_input`` if it has been played before
+    pub fn get(&self, file_input: &InputMode) -> Option<&HistoryEntry> {
+        let source = Self::source_id(file_input).ok()?;
+        self.entries.iter().find(|entry| entry.source == source)
+    }
+
+    /// Saves the current position of ``player``, moving its entry to the front
+    ///
+    /// Players made from bytes are hashed the first time they are recorded, see [`Player::content_hash`]
+    pub fn record(&mut self, player: &Player) -> io::Result<()> {
+        let source = match &player.file_input {
+            InputMode::FilePath(file_path) => file_path.clone(),
+            InputMode::Bytes(_) => Self::hash_id(player.content_hash()?),
+            #[cfg(feature = "streaming")]
+            InputMode::Url(url) => url.clone(),
+        };
+        self.entries.retain(|entry| entry.source != source);
+        self.entries.insert(
+            0,
+            HistoryEntry {
+                source,
+                position: player.elapsed_time,
+                finished: player.has_ended(),
+                last_played: SystemTime::now(),
+            },
+        );
+        self.entries.truncate(self.capacity);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, index: usize) -> HistoryEntry {
+        self.entries.remove(index)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Opens the entry at ``index`` in a new [`Player`] at its saved position
+    ///
+    /// Finished entries start over from the beginning. Returns ``None`` for entries that were not played from a file
+    pub fn reopen(&self, index: usize) -> Option<Player> {
+        let entry = self.entries.get(index).filter(|entry| entry.is_file())?;
+        let mut player = Player::from_path(&entry.source);
+        if !entry.finished {
+            player.seek(entry.position);
+        }
+        Some(player)
+    }
+}
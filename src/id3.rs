@@ -0,0 +1,337 @@
+use std::{
+    fs::File,
+    io::{self, Read},
+    time::Duration,
+};
+
+#[cfg(feature = "streaming")]
+use crate::remote_reader::RemoteReader;
+use crate::{Chapter, CoverArt, InputMode};
+
+/// A frame of an ID3v2 tag, such as ``SYLT`` for synced lyrics
+pub(crate) struct Frame {
+    pub(crate) id: [u8; 4],
+    pub(crate) data: Vec<u8>,
+    /// Minor version of the tag, which changes how sizes of nested frames are stored
+    version: u8,
+}
+
+impl Frame {
+    /// Frames nested inside of this one, starting at ``start`` of its data
+    fn sub_frames(&self, start: usize) -> Vec<Frame> {
+        frames_in(self.data.get(start..).unwrap_or_default(), self.version)
+    }
+}
+
+/// Chapters of the ``CHAP`` frames, sorted by their start
+///
+/// When there are ``CTOC`` frames only the chapters they list are kept, chapters outside of every table of contents are
+/// not meant to be shown
+pub(crate) fn chapters(frames: &[Frame]) -> Vec<Chapter> {
+    let listed: Vec<String> = frames
+        .iter()
+        .filter(|frame| &frame.id == b"CTOC")
+        .flat_map(|frame| {
+            let (_, rest) = terminated_text(0, &frame.data);
+            // Flags and the amount of entries, followed by the element ids of the entries
+            let count = rest.get(1).copied().unwrap_or(0) as usize;
+            let mut rest = rest.get(2..).unwrap_or_default();
+            let mut entries = vec![];
+            for _ in 0..count {
+                let (entry, after) = terminated_text(0, rest);
+                entries.push(entry);
+                rest = after;
+            }
+            entries
+        })
+        .collect();
+    let mut chapters: Vec<Chapter> = frames
+        .iter()
+        .filter(|frame| &frame.id == b"CHAP")
+        .filter_map(|frame| {
+            let (element, rest) = terminated_text(0, &frame.data);
+            if !listed.is_empty() && !listed.contains(&element) {
+                return None;
+            }
+            let millis = |at: usize| {
+                rest.get(at..at + 4)
+                    .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            };
+            let (start, end) = (millis(0)?, millis(4)?);
+            // Start time, end time, start offset and end offset come before the nested frames
+            let nested = frame.sub_frames(frame.data.len() - rest.len() + 16);
+            let title = nested
+                .iter()
+                .find(|frame| &frame.id == b"TIT2" && !frame.data.is_empty())
+                .map(|frame| terminated_text(frame.data[0], &frame.data[1..]).0)
+                .unwrap_or(element);
+            Some(Chapter {
+                start: Duration::from_millis(start as u64),
+                end: Duration::from_millis(end as u64),
+                title,
+                image: nested
+                    .iter()
+                    .find(|frame| &frame.id == b"APIC")
+                    .and_then(picture),
+            })
+        })
+        .collect();
+    chapters.sort_by_key(|chapter| chapter.start);
+    chapters
+}
+
+/// Picture of an ``APIC`` frame
+fn picture(frame: &Frame) -> Option<CoverArt> {
+    let encoding = *frame.data.first()?;
+    let (media_type, rest) = terminated_text(0, &frame.data[1..]);
+    // The picture type comes before the description
+    let (_, data) = terminated_text(encoding, rest.get(1..)?);
+    Some(CoverArt {
+        media_type: match media_type.as_str() {
+            "" | "-->" => "image/jpeg".to_string(),
+            _ if !media_type.contains('/') => format!("image/{}", media_type.to_lowercase()),
+            _ => media_type,
+        },
+        data: data.to_vec(),
+    })
+    .filter(|cover| !cover.data.is_empty())
+}
+
+/// Reads the frames of the ID3v2 tag at the start of a file, empty when there is none
+///
+/// Only version 2.3 and 2.4 tags are read, and compressed or encrypted frames are skipped
+pub(crate) fn read_frames(file_input: &InputMode) -> io::Result<Vec<Frame>> {
+    let tag = match file_input {
+        InputMode::FilePath(file_path) => {
+            let mut file = File::open(file_path)?;
+            let mut header = [0; 10];
+            file.read_exact(&mut header)?;
+            let Some(size) = tag_size(&header) else {
+                return Ok(vec![]);
+            };
+            let mut tag = header.to_vec();
+            file.take(size as u64).read_to_end(&mut tag)?;
+            tag
+        }
+        InputMode::Bytes(bytes) => {
+            let Some(size) = bytes.get(..10).and_then(tag_size) else {
+                return Ok(vec![]);
+            };
+            bytes[..(10 + size).min(bytes.len())].to_vec()
+        }
+        #[cfg(feature = "streaming")]
+        InputMode::Url(url) => {
+            let mut reader = RemoteReader::open(url)?;
+            let mut header = [0; 10];
+            reader.read_exact(&mut header)?;
+            let Some(size) = tag_size(&header) else {
+                return Ok(vec![]);
+            };
+            let mut tag = header.to_vec();
+            reader.take(size as u64).read_to_end(&mut tag)?;
+            tag
+        }
+    };
+    Ok(frames(&tag))
+}
+
+/// Size of the tag after its 10 byte ``header``, ``None`` when the header does not start a tag this can read
+fn tag_size(header: &[u8]) -> Option<usize> {
+    // Tags that are unsynchronised as a whole are rare enough to not be worth undoing
+    let readable =
+        header.starts_with(b"ID3") && matches!(header[3], 3 | 4) && header[5] & 0x80 == 0;
+    readable.then(|| syncsafe(&header[6..10]))
+}
+
+/// Frames of a whole ``tag``, starting with its header
+fn frames(tag: &[u8]) -> Vec<Frame> {
+    let version = tag[3];
+    let mut position = 10;
+    // An extended header comes before the frames when the flag for it is set
+    if tag[5] & 0x40 != 0 && tag.len() >= 14 {
+        position += match version {
+            4 => syncsafe(&tag[10..14]),
+            _ => u32::from_be_bytes([tag[10], tag[11], tag[12], tag[13]]) as usize + 4,
+        };
+    }
+    frames_in(&tag[position.min(tag.len())..], version)
+}
+
+/// Frames one after the other in ``bytes``, until the padding or the end. Also used for the frames nested inside of other frames
+pub(crate) fn frames_in(mut bytes: &[u8], version: u8) -> Vec<Frame> {
+    let mut frames = vec![];
+    while bytes.len() >= 10 && bytes[0] != 0 {
+        let id = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        let size = match version {
+            4 => syncsafe(&bytes[4..8]),
+            _ => u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]) as usize,
+        };
+        let Some(data) = bytes.get(10..10 + size) else {
+            break;
+        };
+        // Compression, encryption and unsynchronisation of single frames
+        let encoded = match version {
+            4 => bytes[9] & 0x0e != 0,
+            _ => bytes[9] & 0xc0 != 0,
+        };
+        if !encoded {
+            frames.push(Frame {
+                id,
+                data: data.to_vec(),
+                version,
+            });
+        }
+        bytes = &bytes[10 + size..];
+    }
+    frames
+}
+
+/// Number stored in 7 bits of each of 4 bytes
+fn syncsafe(bytes: &[u8]) -> usize {
+    bytes
+        .iter()
+        .fold(0, |size, byte| (size << 7) | (*byte & 0x7f) as usize)
+}
+
+/// Splits off a string terminated in ``encoding`` from the start of ``bytes``, returning the text and what is after it
+pub(crate) fn terminated_text(encoding: u8, bytes: &[u8]) -> (String, &[u8]) {
+    let end = match encoding {
+        // UTF-16 ends with two zero bytes at an even position
+        1 | 2 => (0..bytes.len().saturating_sub(1))
+            .step_by(2)
+            .find(|at| bytes[*at] == 0 && bytes[at + 1] == 0)
+            .map(|at| (at, at + 2)),
+        _ => bytes
+            .iter()
+            .position(|byte| *byte == 0)
+            .map(|at| (at, at + 1)),
+    };
+    match end {
+        Some((end, next)) => (text(encoding, &bytes[..end]), &bytes[next..]),
+        None => (text(encoding, bytes), &[]),
+    }
+}
+
+/// Decodes ``bytes`` as text in the ID3 ``encoding``, 0 is Latin-1, 1 UTF-16 with a byte order mark, 2 UTF-16BE and 3 UTF-8
+pub(crate) fn text(encoding: u8, bytes: &[u8]) -> String {
+    match encoding {
+        1 | 2 => {
+            let little_endian = encoding == 1 && bytes.starts_with(&[0xff, 0xfe]);
+            let bytes = match encoding {
+                1 if bytes.starts_with(&[0xff, 0xfe]) || bytes.starts_with(&[0xfe, 0xff]) => {
+                    &bytes[2..]
+                }
+                _ => bytes,
+            };
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|pair| match little_endian {
+                    true => u16::from_le_bytes([pair[0], pair[1]]),
+                    false => u16::from_be_bytes([pair[0], pair[1]]),
+                })
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        3 => String::from_utf8_lossy(bytes).into_owned(),
+        _ => bytes.iter().map(|byte| *byte as char).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn syncsafe_bytes(size: usize) -> [u8; 4] {
+        [21, 14, 7, 0].map(|shift| ((size >> shift) & 0x7f) as u8)
+    }
+
+    fn frame(version: u8, id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let size = match version {
+            4 => syncsafe_bytes(data.len()),
+            _ => (data.len() as u32).to_be_bytes(),
+        };
+        [id.as_slice(), &size, &[0, 0], data].concat()
+    }
+
+    fn chap(version: u8, element: &str, start: u32, end: u32, nested: &[Vec<u8>]) -> Vec<u8> {
+        let data = [
+            element.as_bytes(),
+            &[0],
+            &start.to_be_bytes(),
+            &end.to_be_bytes(),
+            // Byte offsets that are not used
+            &[0xff; 8],
+            nested.concat().as_slice(),
+        ]
+        .concat();
+        frame(version, b"CHAP", &data)
+    }
+
+    fn tag(version: u8, frames: &[Vec<u8>]) -> InputMode {
+        let body = frames.concat();
+        InputMode::Bytes(
+            [
+                b"ID3".as_slice(),
+                &[version, 0, 0],
+                &syncsafe_bytes(body.len()),
+                body.as_slice(),
+            ]
+            .concat()
+            .into(),
+        )
+    }
+
+    #[test]
+    fn chapters_are_sorted_with_their_title_and_picture() {
+        let title = frame(4, b"TIT2", b"\x03Intro");
+        let picture = frame(4, b"APIC", b"\x00image/png\x00\x03cover\x00\x01\x02\x03");
+        let file = tag(
+            4,
+            &[
+                chap(4, "chp2", 60_000, 120_000, &[]),
+                chap(4, "chp1", 0, 60_000, &[title, picture]),
+            ],
+        );
+        assert_eq!(
+            chapters(&read_frames(&file).unwrap()),
+            vec![
+                Chapter {
+                    start: Duration::ZERO,
+                    end: Duration::from_secs(60),
+                    title: "Intro".to_string(),
+                    image: Some(CoverArt {
+                        media_type: "image/png".to_string(),
+                        data: vec![1, 2, 3],
+                    }),
+                },
+                Chapter {
+                    start: Duration::from_secs(60),
+                    end: Duration::from_secs(120),
+                    title: "chp2".to_string(),
+                    image: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn only_chapters_in_the_table_of_contents_are_kept() {
+        let file = tag(
+            3,
+            &[
+                frame(3, b"CTOC", b"toc\x00\x03\x01chp1\x00"),
+                chap(3, "chp1", 0, 1000, &[]),
+                chap(3, "hidden", 1000, 2000, &[]),
+            ],
+        );
+        let chapters = chapters(&read_frames(&file).unwrap());
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, "chp1");
+    }
+
+    #[test]
+    fn files_without_a_tag_have_no_frames() {
+        let file = InputMode::Bytes(b"fLaC and then some audio".as_slice().into());
+        assert!(read_frames(&file).unwrap().is_empty());
+    }
+}
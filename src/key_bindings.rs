@@ -0,0 +1,109 @@
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use std::time::Duration;
+
+/// Something a [`crate::player::Player`] can do when a key is pressed
+///
+/// Chapters are the [`crate::Marker`]s of the player
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlayerAction {
+    PlayPause,
+    SeekForward(Duration),
+    SeekBackward(Duration),
+    VolumeUp,
+    VolumeDown,
+    ToggleMute,
+    NextChapter,
+    PreviousChapter,
+}
+
+/// Maps keys and modifiers to [`PlayerAction`]s, set with [`crate::player::Player::set_key_bindings`]
+///
+/// The default bindings are:
+///
+/// ``Space``: Play / pause
+///
+/// ``←`` / ``→``: Seek 5 seconds
+///
+/// ``↑`` / ``↓``: Volume up / down
+///
+/// ``M``: Mute
+///
+/// ``Shift + ←`` / ``Shift + →``: Previous / next chapter
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::key_bindings::{KeyBindings, PlayerAction};
+/// use egui_player::player::Player;
+/// use eframe::egui::{Key, Modifiers};
+/// use std::time::Duration;
+///
+/// let mut bindings = KeyBindings::default();
+/// bindings.bind(Modifiers::NONE, Key::K, PlayerAction::PlayPause);
+/// bindings.bind(Modifiers::NONE, Key::L, PlayerAction::SeekForward(Duration::from_secs(10)));
+/// bindings.unbind(Modifiers::NONE, Key::Space);
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_key_bindings(bindings);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyboardShortcut, PlayerAction)>,
+    /// Only react to keys while the pointer is over the player, keeps several players on screen from reacting together
+    pub require_hover: bool,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let mut bindings = Self::new();
+        let seek = Duration::from_secs(5);
+        bindings.bind(Modifiers::NONE, Key::Space, PlayerAction::PlayPause);
+        bindings.bind(
+            Modifiers::NONE,
+            Key::ArrowRight,
+            PlayerAction::SeekForward(seek),
+        );
+        bindings.bind(
+            Modifiers::NONE,
+            Key::ArrowLeft,
+            PlayerAction::SeekBackward(seek),
+        );
+        bindings.bind(Modifiers::NONE, Key::ArrowUp, PlayerAction::VolumeUp);
+        bindings.bind(Modifiers::NONE, Key::ArrowDown, PlayerAction::VolumeDown);
+        bindings.bind(Modifiers::NONE, Key::M, PlayerAction::ToggleMute);
+        bindings.bind(Modifiers::SHIFT, Key::ArrowRight, PlayerAction::NextChapter);
+        bindings.bind(
+            Modifiers::SHIFT,
+            Key::ArrowLeft,
+            PlayerAction::PreviousChapter,
+        );
+        bindings
+    }
+}
+
+impl KeyBindings {
+    /// Creates a map without any bindings
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![],
+            require_hover: true,
+        }
+    }
+
+    /// Binds ``key`` pressed with ``modifiers`` to ``action``, replacing what it was bound to before
+    pub fn bind(&mut self, modifiers: Modifiers, key: Key, action: PlayerAction) {
+        let shortcut = KeyboardShortcut::new(modifiers, key);
+        self.bindings.retain(|(bound, _)| *bound != shortcut);
+        self.bindings.push((shortcut, action));
+    }
+
+    pub fn unbind(&mut self, modifiers: Modifiers, key: Key) {
+        let shortcut = KeyboardShortcut::new(modifiers, key);
+        self.bindings.retain(|(bound, _)| *bound != shortcut);
+    }
+
+    /// Every shortcut along with the action it is bound to
+    pub fn bindings(&self) -> &[(KeyboardShortcut, PlayerAction)] {
+        &self.bindings
+    }
+}
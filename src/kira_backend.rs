@@ -0,0 +1,137 @@
+use kira::{
+    backend::Backend,
+    info::Info,
+    sound::{Sound, SoundData},
+    AudioManager, Frame, PlaySoundError,
+};
+use std::{
+    convert::Infallible,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use crate::audio_backend::{AudioBackend, BackendSource, PlaybackHandle, PullBackend, PullOutput};
+
+/// Sample rate players are mixed at before the sound converts the mix to the rate of the manager
+const MIX_SAMPLE_RATE: u32 = 48000;
+
+/// Frames pulled from the mix at a time
+const PULL_FRAMES: usize = 256;
+
+/// Plays through an ``AudioManager`` of kira that the app already runs, such as the one of a bevy or macroquad game
+///
+/// The players of the context are mixed by a [`PullBackend`] and played as a single sound of the manager, so they share the
+/// device and the mix with the sounds of the game. The sound stops once the backend is dropped
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::audio_context::AudioContext;
+/// use egui_player::kira_backend::KiraBackend;
+/// use egui_player::player::Player;
+/// use kira::{AudioManager, AudioManagerSettings, DefaultBackend};
+///
+/// let mut manager = AudioManager::<DefaultBackend>::new(AudioManagerSettings::default()).unwrap();
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_audio_context(AudioContext::with_backend(KiraBackend::new(&mut manager).unwrap()));
+/// ```
+pub struct KiraBackend {
+    backend: PullBackend,
+    stopped: Arc<AtomicBool>,
+}
+
+impl KiraBackend {
+    /// Starts playing the mix of the players as a sound of ``manager``
+    pub fn new<B: Backend>(
+        manager: &mut AudioManager<B>,
+    ) -> Result<Self, PlaySoundError<Infallible>> {
+        let (backend, output) = PullBackend::new(2, MIX_SAMPLE_RATE);
+        let stopped = Arc::new(AtomicBool::new(false));
+        manager.play(KiraSound {
+            output,
+            stopped: Arc::clone(&stopped),
+            buffer: vec![0.0; PULL_FRAMES * 2],
+            read: PULL_FRAMES,
+            previous: Frame::ZERO,
+            next: Frame::ZERO,
+            position: 1.0,
+        })?;
+        Ok(Self { backend, stopped })
+    }
+}
+
+impl AudioBackend for KiraBackend {
+    fn play(&self, source: BackendSource) -> Box<dyn PlaybackHandle> {
+        self.backend.play(source)
+    }
+}
+
+impl Drop for KiraBackend {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Sound of the manager that pulls the mix of a [`KiraBackend`]
+struct KiraSound {
+    output: PullOutput,
+    stopped: Arc<AtomicBool>,
+    /// Interleaved stereo frames pulled from the mix, read from ``read`` on
+    buffer: Vec<f32>,
+    read: usize,
+    /// Frames of the mix that the frame being played lies between
+    previous: Frame,
+    next: Frame,
+    /// Position between ``previous`` and ``next``, from ``0.0`` to ``1.0``
+    position: f64,
+}
+
+impl KiraSound {
+    fn next_frame(&mut self) -> Frame {
+        if self.read == PULL_FRAMES {
+            self.output.fill(&mut self.buffer);
+            self.read = 0;
+        }
+        let frame = Frame {
+            left: self.buffer[self.read * 2],
+            right: self.buffer[self.read * 2 + 1],
+        };
+        self.read += 1;
+        frame
+    }
+}
+
+impl SoundData for KiraSound {
+    type Error = Infallible;
+    type Handle = ();
+
+    fn into_sound(self) -> Result<(Box<dyn Sound>, Self::Handle), Self::Error> {
+        Ok((Box::new(self), ()))
+    }
+}
+
+impl Sound for KiraSound {
+    fn process(&mut self, out: &mut [Frame], dt: f64, _info: &Info) {
+        // Frames of the mix that pass during one frame of the manager
+        let step = dt * f64::from(MIX_SAMPLE_RATE);
+        for frame in out {
+            while self.position >= 1.0 {
+                self.previous = self.next;
+                self.next = self.next_frame();
+                self.position -= 1.0;
+            }
+            let amount = self.position as f32;
+            *frame = Frame {
+                left: self.previous.left + (self.next.left - self.previous.left) * amount,
+                right: self.previous.right + (self.next.right - self.previous.right) * amount,
+            };
+            self.position += step;
+        }
+    }
+
+    fn finished(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
@@ -1,5 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::{
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 /// Used throughout [`player`] to determine visual elements and populate relevant [`player::Player`] struct fields
 ///
@@ -17,11 +23,17 @@ pub enum MediaType {
 ///
 /// ``FilePath`` holds the path for the [`player::Player`] it is a String
 ///
-///  ``Bytes`` holds the file information in the form of Vec<u8>
+///  ``Bytes`` holds the file information in the form of ``Arc<[u8]>``, so players, analysis and transcription share a
+///  single copy of the file however often it is played. A ``Vec<u8>`` turns into one with ``.into()``
+///
+///  ``Url`` holds the address of a file that is played while it downloads, enabled by the ``streaming`` feature.
+///  Everything that reads the same URL at the same time shares one download, which is kept in memory while it is read
 #[derive(Debug, Clone)]
 pub enum InputMode {
     FilePath(String),
-    Bytes(Vec<u8>),
+    Bytes(Arc<[u8]>),
+    #[cfg(feature = "streaming")]
+    Url(String),
 }
 
 /// Configure if a transcript is outputted and displayed
@@ -41,6 +53,71 @@ pub enum TranscriptionSettings {
     ShowTimeStamps,
 }
 
+/// When a [`player::Player`] transcribes, set with [`player::Player::set_transcription_mode`]
+///
+/// ``WholeFile``: The whole file is transcribed from the start once transcription is asked for
+///
+/// ``FollowPlayback(window)``: Only the audio up to ``window`` ahead of the playback position is transcribed, so captions show up
+/// right after pressing play on a long file. Transcription starts on its own with playback and follows seeks,
+/// see [`transcriber::Transcriber::transcribe_following`]
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum TranscriptionMode {
+    #[default]
+    WholeFile,
+    FollowPlayback(Duration),
+}
+
+/// Limits how much of the machine Whisper transcription takes, so it does not cause audio dropouts on machines with few cores
+///
+/// Set with [`player::Player::set_transcription_resources`] or on a [`transcriber::WhisperTranscriber`]
+///
+/// ``threads``: Threads inference runs on, ``None`` uses every core. Inference runs on the global rayon pool, which is sized by the
+/// first model that is loaded as long as nothing else in the process used rayon before. Later values are logged and ignored
+///
+/// ``low_priority``: Runs inference at a lower scheduling priority so playback and the UI win whenever the CPU is busy.
+/// Only has an effect on Linux, other platforms log that it is not supported
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::TranscriptionResources;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcription_resources(TranscriptionResources {
+///     threads: Some(2),
+///     low_priority: true,
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct TranscriptionResources {
+    pub threads: Option<usize>,
+    pub low_priority: bool,
+}
+
+/// Settings every transcriber can use when turning speech into text, ``None`` keeps the choice of the transcriber
+///
+/// Set with [`player::Player::set_decoding_options`], on a [`transcriber::WhisperTranscriber`] or with [`transcriber::RemoteTranscriber::decoding`]
+///
+/// ``no_speech_threshold``: Pieces of audio whose probability of holding no speech is above this, from ``0.0`` to ``1.0``,
+/// are left out of the transcript. Lower values drop more music and noise but may drop quiet speech as well
+///
+/// The built in Whisper always decodes greedily, raising the temperature by itself when a piece fails to decode.
+/// Transcribers whose backend lets the sampling be picked take the temperature and beam size themselves,
+/// see [`transcriber::RemoteTranscriber::temperature`] and [`transcriber::RemoteTranscriber::beam_size`]
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct DecodingOptions {
+    pub no_speech_threshold: Option<f32>,
+}
+
+impl DecodingOptions {
+    /// Whether a piece with the ``no_speech_probability`` given by the model is left out
+    pub fn is_silent(&self, no_speech_probability: f32) -> bool {
+        self.no_speech_threshold
+            .is_some_and(|threshold| no_speech_probability > threshold)
+    }
+}
+
 /// Sent out for transcript Progress
 ///
 /// ``NoProgress`` : No transcript started
@@ -50,12 +127,15 @@ pub enum TranscriptionSettings {
 /// ``Reading``: Nothing is being sent back but words are being read
 ///
 /// ``Finished``: Done with Transcription
+///
+/// ``Failed``: The transcriber stopped without finishing, the transcript holds what was transcribed until then
 #[derive(Debug, Clone, PartialEq)]
 pub enum TranscriptionProgress {
     NoProgress,
     InProgress(TranscriptionData),
     Reading,
     Finished,
+    Failed,
 }
 
 /// Holds data produced when a file is transcribed
@@ -63,17 +143,631 @@ pub enum TranscriptionProgress {
 /// The ``text`` section is usually a word with a space and relevant punctuation detected
 ///
 /// The ``time`` section is when this word has started
+///
+/// The ``end`` section is when this word has been spoken, transcripts saved before it existed read it as zero
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TranscriptionData {
     pub text: String,
     pub time: Duration,
+    #[serde(default)]
+    pub end: Duration,
+}
+
+/// How a word of a generated transcript lines up with a reference transcript, see [`WerReport`]
+///
+/// ``Match``: The word is the same in both
+///
+/// ``Substitution``: A word of the reference was transcribed as another word
+///
+/// ``Insertion``: A word of the generated transcript that is not in the reference
+///
+/// ``Deletion``: A word of the reference that is missing from the generated transcript
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlignedWord {
+    Match(String),
+    Substitution {
+        generated: String,
+        reference: String,
+    },
+    Insertion(String),
+    Deletion(String),
+}
+
+/// Accuracy of a generated transcript against a reference, produced by [`media_information::compare_transcripts`]
+///
+/// ``reference_words``: Amount of words in the reference, which the error rate is relative to
+///
+/// ``alignment``: Every word of both transcripts in the order they are spoken, lined up with each other
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct WerReport {
+    pub substitutions: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub reference_words: usize,
+    pub alignment: Vec<AlignedWord>,
+}
+
+impl WerReport {
+    /// Substitutions, insertions and deletions over the words of the reference, ``0.0`` is a perfect transcript.
+    /// Can go above ``1.0`` when many words were inserted
+    pub fn word_error_rate(&self) -> f32 {
+        let errors = self.substitutions + self.insertions + self.deletions;
+        match self.reference_words {
+            0 if errors == 0 => 0.0,
+            0 => 1.0,
+            words => errors as f32 / words as f32,
+        }
+    }
+}
+
+/// Speed and cost of transcribing a file on this machine, produced by [`media_information::bench_transcription`]
+///
+/// ``model_load_time``: Time to load the model, including the download the first time it is used
+///
+/// ``real_time_factor``: Transcription time over the length of the audio, below ``1.0`` is faster than real time
+///
+/// ``peak_memory``: Most memory the process held in bytes since it started, only measured on Linux
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub model_load_time: Duration,
+    pub transcription_time: Duration,
+    pub audio_duration: Duration,
+    pub real_time_factor: f32,
+    pub peak_memory: Option<u64>,
+    pub transcript: Vec<TranscriptionData>,
 }
 
 /// Enables the user to control the location of the model. Useful for cloud based apps
+///
+/// ``Default``: Downloads the model into the default cache of kalosm
+///
+/// ``Custom``: Downloads the model into the given folder
+///
+/// ``Local``: Uses model files that are already on disk, for example a fine-tuned model, and never downloads anything
 #[derive(Debug, Clone)]
 pub enum ModelPath {
     Default,
     Custom(String),
+    Local(LocalModel),
+}
+
+/// Whisper model files on disk, used with [`ModelPath::Local`]
+///
+/// The files must have the architecture of the [`WhisperModel`] transcribing with them, so a model fine-tuned from ``Small``
+/// is used with [`WhisperModel::Small`]. Quantized models are ``.gguf`` files, the others are ``.safetensors`` files
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalModel {
+    pub model: PathBuf,
+    pub tokenizer: PathBuf,
+    pub config: PathBuf,
+}
+
+impl LocalModel {
+    /// Finds ``model.safetensors`` or ``model.gguf``, ``tokenizer.json`` and ``config.json`` in ``folder``,
+    /// the layout of models on Hugging Face
+    pub fn from_folder(folder: impl AsRef<Path>) -> Self {
+        let folder = folder.as_ref();
+        let safetensors = folder.join("model.safetensors");
+        Self {
+            model: if safetensors.exists() {
+                safetensors
+            } else {
+                folder.join("model.gguf")
+            },
+            tokenizer: folder.join("tokenizer.json"),
+            config: folder.join("config.json"),
+        }
+    }
+}
+
+/// Whisper model used for transcription, set with [`player::Player::set_whisper_model`] or on a [`transcriber::WhisperTranscriber`]
+///
+/// Larger models are more accurate but slower and take more memory. ``En`` models only understand English.
+/// ``Quantized`` models store their weights in 8 bits or less, which cuts memory use several times over for a small loss in accuracy,
+/// use [`WhisperModel::quantized`] to get the quantized version of a model
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum WhisperModel {
+    Tiny,
+    QuantizedTiny,
+    TinyEn,
+    QuantizedTinyEn,
+    Base,
+    BaseEn,
+    Small,
+    SmallEn,
+    Medium,
+    MediumEn,
+    Large,
+    LargeV2,
+    DistilMediumEn,
+    QuantizedDistilMediumEn,
+    DistilLargeV2,
+    DistilLargeV3,
+    QuantizedDistilLargeV3,
+    #[default]
+    QuantizedLargeV3Turbo,
+}
+
+impl WhisperModel {
+    pub fn is_quantized(&self) -> bool {
+        matches!(
+            self,
+            Self::QuantizedTiny
+                | Self::QuantizedTinyEn
+                | Self::QuantizedDistilMediumEn
+                | Self::QuantizedDistilLargeV3
+                | Self::QuantizedLargeV3Turbo
+        )
+    }
+
+    /// Quantized model closest to this one, for models that have no quantized version this is the closest smaller one
+    pub fn quantized(self) -> Self {
+        match self {
+            Self::Tiny | Self::QuantizedTiny | Self::Base | Self::Small => Self::QuantizedTiny,
+            Self::TinyEn | Self::QuantizedTinyEn | Self::BaseEn | Self::SmallEn => {
+                Self::QuantizedTinyEn
+            }
+            Self::Medium
+            | Self::DistilLargeV2
+            | Self::DistilLargeV3
+            | Self::QuantizedDistilLargeV3 => Self::QuantizedDistilLargeV3,
+            Self::MediumEn | Self::DistilMediumEn | Self::QuantizedDistilMediumEn => {
+                Self::QuantizedDistilMediumEn
+            }
+            Self::Large | Self::LargeV2 | Self::QuantizedLargeV3Turbo => {
+                Self::QuantizedLargeV3Turbo
+            }
+        }
+    }
+}
+
+/// Determines how playback speed changes are applied
+///
+/// ``Resample``: Audio is played faster or slower like a tape, which also raises or lowers the pitch
+///
+/// ``PreservePitch``: Audio is time stretched so voices keep their natural pitch at any speed
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpeedMode {
+    Resample,
+    PreservePitch,
+}
+
+/// Level of a single audio channel, both values are linear amplitudes where ``1.0`` is full scale
+///
+/// ``peak``: Highest absolute sample value, anything at or above ``1.0`` is clipping
+///
+/// ``rms``: Root mean square of the samples, which is closer to how loud the audio sounds
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ChannelLevel {
+    pub peak: f32,
+    pub rms: f32,
+}
+
+/// Visualization of the playing audio shown underneath the control bar
+///
+/// ``None``: No visualization
+///
+/// ``Oscilloscope``: Scrolling waveform of the last 100ms of audio
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Visualization {
+    None,
+    Oscilloscope,
+}
+
+/// Background drawn behind the seek bar
+///
+/// ``None``: Plain seek bar
+///
+/// ``Waveform``: Peaks of the whole file, see [`media_information::get_waveform_peaks`]
+///
+/// ``Spectrogram``: Frequencies of the whole file over time, see [`media_information::get_spectrogram`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SeekBarBackground {
+    None,
+    Waveform,
+    Spectrogram,
+}
+
+/// Intensity of frequency bands over time, produced by [`media_information::get_spectrogram`]
+///
+/// ``values`` holds ``width`` columns one after the other, each made of ``height`` values between 0.0 and 1.0 starting at the lowest frequency
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrogram {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f32>,
+}
+
+/// What the transcript view of [`player::Player`] shows, set with [`player::Player::set_caption_source`]
+///
+/// ``Transcript``: Words transcribed from the audio, see [`TranscriptionSettings`]
+///
+/// ``Lyrics``: Lines of time synced lyrics set with [`player::Player::set_lyrics`], or the lyrics embedded in the file
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum CaptionSource {
+    #[default]
+    Transcript,
+    Lyrics,
+}
+
+/// Title, artist and album of a file, read with [`media_information::get_track_info`] or found by a [`metadata::MetadataProvider`]
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TrackInfo {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+}
+
+/// Picture embedded in the tags of a file, read with [`media_information::get_cover_art`]
+///
+/// ``media_type``: MIME type of ``data``, such as ``image/jpeg``
+///
+/// ``data``: The encoded picture
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverArt {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// A chapter stored in the ID3 tag of a file, read with [`media_information::get_chapters`]
+///
+/// ``end``: Where the chapter stops, which is usually where the next one starts
+///
+/// ``image``: Picture of the chapter, such as the artwork of a segment of a podcast
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    pub start: Duration,
+    pub end: Duration,
+    pub title: String,
+    pub image: Option<CoverArt>,
+}
+
+/// A labelled point in time shown on the [`player::Player::timeline_ui`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Marker {
+    pub time: Duration,
+    pub label: String,
+}
+
+/// A region that is jumped over whenever playback reaches it, see [`player::Player::add_skip_segment`]
+///
+/// ``label``: What is skipped, shown as "Skipped ``label``" when it is jumped over
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkipSegment {
+    pub range: Range<Duration>,
+    pub label: String,
+}
+
+/// Glyphs used on the control bar of [`player::Player`], set with [`player::Player::set_icons`] or [`PlayerDefaults`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Icons {
+    pub play: String,
+    pub pause: String,
+    pub restart: String,
+    pub retry: String,
+    /// Shown on the placeholder of audio files without cover art, see [`player::Player::set_artwork_placeholder`]
+    pub artwork: String,
+    pub chapters: String,
+    pub volume_high: String,
+    pub volume_medium: String,
+    pub volume_low: String,
+    pub volume_muted: String,
+}
+
+impl Default for Icons {
+    fn default() -> Self {
+        Self {
+            play: "▶".to_string(),
+            pause: "⏸".to_string(),
+            restart: "↺".to_string(),
+            retry: "⚠".to_string(),
+            artwork: "🎵".to_string(),
+            chapters: "📑".to_string(),
+            volume_high: "🔊".to_string(),
+            volume_medium: "🔉".to_string(),
+            volume_low: "🔈".to_string(),
+            volume_muted: "🔇".to_string(),
+        }
+    }
+}
+
+/// Text shown by [`player::Player`] and the widgets around it, set with [`player::Player::set_locale`] or [`PlayerDefaults`]
+///
+/// The default is English, fill in every field to translate the interface
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{player::Player, PlayerLocale};
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_locale(PlayerLocale {
+///     play: "Lecture".to_string(),
+///     pause: "Pause".to_string(),
+///     transcribe_audio: "Transcrire l'audio".to_string(),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerLocale {
+    pub play: String,
+    pub pause: String,
+    pub restart: String,
+    pub seek: String,
+    pub of: String,
+    pub volume: String,
+    pub mono: String,
+    pub mute: String,
+    pub solo: String,
+    pub speed: String,
+    pub preserve_pitch: String,
+    pub pitch: String,
+    pub more: String,
+    pub transcribe_audio: String,
+    pub transcription_in_progress: String,
+    pub end_of_transcript: String,
+    pub transcription_failed: String,
+    pub loop_playback: String,
+    pub clear_ab_loop: String,
+    pub loop_selection: String,
+    pub clear_selection: String,
+    pub copy_timestamp: String,
+    pub loading_waveform: String,
+    pub remove: String,
+    pub audio_output_lost: String,
+    pub chapters: String,
+    pub skipped: String,
+    pub caption_delay: String,
+    pub clipping: String,
+    pub channel: String,
+    pub peak: String,
+    pub rms: String,
+}
+
+impl Default for PlayerLocale {
+    fn default() -> Self {
+        Self {
+            play: "Play".to_string(),
+            pause: "Pause".to_string(),
+            restart: "Restart".to_string(),
+            seek: "Seek".to_string(),
+            of: "of".to_string(),
+            volume: "Volume".to_string(),
+            mono: "Mono".to_string(),
+            mute: "Mute".to_string(),
+            solo: "Solo".to_string(),
+            speed: "Speed".to_string(),
+            preserve_pitch: "Preserve pitch".to_string(),
+            pitch: "Pitch".to_string(),
+            more: "More".to_string(),
+            transcribe_audio: "Transcribe audio".to_string(),
+            transcription_in_progress: "Transcription in Progress".to_string(),
+            end_of_transcript: "--- END OF TRANSCRIPT ---".to_string(),
+            transcription_failed: "Transcription failed".to_string(),
+            loop_playback: "Loop".to_string(),
+            clear_ab_loop: "Clear A/B loop".to_string(),
+            loop_selection: "Loop selection".to_string(),
+            clear_selection: "Clear selection".to_string(),
+            copy_timestamp: "Copy timestamp".to_string(),
+            loading_waveform: "Loading waveform…".to_string(),
+            remove: "Remove".to_string(),
+            audio_output_lost: "Audio output lost, click to retry".to_string(),
+            chapters: "Chapters".to_string(),
+            skipped: "Skipped".to_string(),
+            caption_delay: "Caption delay".to_string(),
+            clipping: "Clipping".to_string(),
+            channel: "Channel".to_string(),
+            peak: "peak".to_string(),
+            rms: "RMS".to_string(),
+        }
+    }
+}
+
+/// How times are written on the control bar and timeline, set with [`player::Player::set_time_format`] or [`PlayerDefaults`]
+///
+/// ``Auto``: ``MM:SS``, with hours in front once there are any, like [`media_information::format_duration`]
+///
+/// ``HoursMinutesSeconds``: Always ``HH:MM:SS``
+///
+/// ``Frames``: Number of frames at the given rate
+///
+/// ``Smpte``: ``HH:MM:SS:FF`` non drop frame timecode at the given rate
+///
+/// ``Custom``: Written by your own function
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{player::Player, TimeFormat};
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_time_format(TimeFormat::Smpte(25.0));
+/// // Or
+/// player.set_time_format(TimeFormat::custom(|time| format!("{:.3}s", time.as_secs_f64())));
+/// ```
+#[derive(Clone, Default)]
+pub enum TimeFormat {
+    #[default]
+    Auto,
+    HoursMinutesSeconds,
+    Frames(f32),
+    Smpte(f32),
+    Custom(Arc<dyn Fn(Duration) -> String + Send + Sync>),
+}
+
+impl TimeFormat {
+    pub fn custom(format: impl Fn(Duration) -> String + Send + Sync + 'static) -> Self {
+        Self::Custom(Arc::new(format))
+    }
+
+    /// Writes ``time`` in this format
+    pub fn format(&self, time: Duration) -> String {
+        let seconds = time.as_secs() % 60;
+        let minutes = (time.as_secs() / 60) % 60;
+        let hours = (time.as_secs() / 60) / 60;
+        match self {
+            Self::Auto => media_information::format_duration(time),
+            Self::HoursMinutesSeconds => format!("{hours:0>2}:{minutes:0>2}:{seconds:0>2}"),
+            Self::Frames(frame_rate) => {
+                format!("{}", (time.as_secs_f64() * *frame_rate as f64).floor())
+            }
+            Self::Smpte(frame_rate) => {
+                let frames = (time.subsec_nanos() as f64 / 1e9 * *frame_rate as f64).floor();
+                format!("{hours:0>2}:{minutes:0>2}:{seconds:0>2}:{frames:0>2}")
+            }
+            Self::Custom(format) => format(time),
+        }
+    }
+}
+
+impl fmt::Debug for TimeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => f.write_str("Auto"),
+            Self::HoursMinutesSeconds => f.write_str("HoursMinutesSeconds"),
+            Self::Frames(frame_rate) => f.debug_tuple("Frames").field(frame_rate).finish(),
+            Self::Smpte(frame_rate) => f.debug_tuple("Smpte").field(frame_rate).finish(),
+            Self::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+/// What happens when a word of the transcript is clicked, set with [`player::Player::set_transcript_seek_behavior`]
+///
+/// ``Pause``: Playback pauses at the start of the word
+///
+/// ``KeepPlaying``: Playback jumps to the start of the word and carries on, a paused player stays paused
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum SeekBehavior {
+    #[default]
+    Pause,
+    KeepPlaying,
+}
+
+/// How often a playing [`player::Player`] is redrawn, set with [`player::Player::set_repaint_policy`]
+///
+/// ``Adaptive``: Every frame while a visualization, level meters or the timeline are shown, otherwise whenever the time label changes
+///
+/// ``EveryFrame``: Every frame
+///
+/// ``NextSecond``: Whenever the seconds of the elapsed time change, even when more is shown
+///
+/// Paused players are only redrawn on input, or while a transcription or analysis is running
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum RepaintPolicy {
+    #[default]
+    Adaptive,
+    EveryFrame,
+    NextSecond,
+}
+
+/// Settings that every new [`player::Player`] starts out with
+///
+/// Set them once with [`PlayerDefaults::set_global`] instead of changing each player after it is made
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{PlayerDefaults, TranscriptionSettings};
+///
+/// PlayerDefaults::set_global(PlayerDefaults {
+///     volume: 80,
+///     transcription_settings: TranscriptionSettings::TranscriptLabel,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct PlayerDefaults {
+    /// Between 0 and 100
+    pub volume: i32,
+    pub speed: f32,
+    pub transcription_settings: TranscriptionSettings,
+    pub model_path: ModelPath,
+    pub icons: Icons,
+    pub locale: PlayerLocale,
+    pub time_format: TimeFormat,
+}
+
+impl Default for PlayerDefaults {
+    fn default() -> Self {
+        Self {
+            volume: 100,
+            speed: 1.0,
+            transcription_settings: TranscriptionSettings::None,
+            model_path: ModelPath::Default,
+            icons: Icons::default(),
+            locale: PlayerLocale::default(),
+            time_format: TimeFormat::Auto,
+        }
+    }
+}
+
+static GLOBAL_DEFAULTS: RwLock<Option<PlayerDefaults>> = RwLock::new(None);
+
+impl PlayerDefaults {
+    /// Makes every [`player::Player`] created from now on start with ``defaults``
+    pub fn set_global(defaults: PlayerDefaults) {
+        *GLOBAL_DEFAULTS.write().unwrap() = Some(defaults);
+    }
+
+    /// Defaults given to [`PlayerDefaults::set_global`], or the built in ones
+    pub fn global() -> PlayerDefaults {
+        GLOBAL_DEFAULTS.read().unwrap().clone().unwrap_or_default()
+    }
+}
+
+/// Playback state passed to the overlay set with [`player::Player::set_overlay`]
+///
+/// ``visible_start`` and ``visible_end`` are the times at the left and right edge of the drawn area
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OverlayState {
+    pub elapsed: Duration,
+    pub total: Duration,
+    pub playing: bool,
+    pub visible_start: Duration,
+    pub visible_end: Duration,
+}
+
+impl OverlayState {
+    /// Horizontal position of ``time`` inside of ``rect``, handy to line drawings up with the audio
+    pub fn time_to_x(&self, time: Duration, rect: eframe::egui::Rect) -> f32 {
+        let visible =
+            (self.visible_end.as_secs_f32() - self.visible_start.as_secs_f32()).max(0.001);
+        rect.left()
+            + (time.as_secs_f32() - self.visible_start.as_secs_f32()) / visible * rect.width()
+    }
+}
+
+/// Counters describing the playback of a [`player::Player`], returned by [`player::Player::stats`]
+///
+/// ``play_time``: Total time spent playing
+///
+/// ``seeks``: Amount of times the position was changed
+///
+/// ``slow_blocks``: Blocks of audio that took longer to decode than they take to play. They are only heard as
+/// stutters once the output has played everything that was buffered ahead, so they hint at stutters rather than count them
+///
+/// ``decode_errors``: Errors reported by the decoder, such as corrupt packets it skipped or seeks it could not perform
+///
+/// ``average_decode_time``: Average time it took to decode a block of 512 frames
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct PlaybackStats {
+    pub play_time: Duration,
+    pub seeks: u64,
+    pub slow_blocks: u64,
+    pub decode_errors: u64,
+    pub average_decode_time: Duration,
+}
+
+/// File format written by [`media_information::export_audio`] and [`media_information::export_clip`]
+///
+/// ``Wav``: 16 bit PCM WAV file
+///
+/// ``Flac``: 16 bit lossless FLAC file, about half the size of a WAV file
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ExportFormat {
+    Wav,
+    Flac,
 }
 
 /// Functions that populate data for [`player::Player`]
@@ -81,5 +775,97 @@ pub enum ModelPath {
 /// Functions from this module can also be used independently (refer to function documentation if you want to use these functions)
 pub mod media_information;
 
+/// Contains [`audio_context::AudioContext`], the audio output shared between players
+pub mod audio_context;
+
+/// Contains the [`audio_backend::AudioBackend`] trait that audio is played through, along with the built in backends
+pub mod audio_backend;
+
+/// Contains [`cpal_backend::CpalBackend`] which plays through cpal directly, enabled by the ``cpal-backend`` feature
+#[cfg(feature = "cpal-backend")]
+pub mod cpal_backend;
+
+/// Contains [`kira_backend::KiraBackend`] which plays through the ``AudioManager`` of kira, enabled by the ``kira`` feature
+#[cfg(feature = "kira")]
+pub mod kira_backend;
+
+/// Contains the [`clock::Clock`] trait that players keep time with, along with a [`clock::ManualClock`] for tests
+pub mod clock;
+
+/// Contains [`sync_group::SyncGroup`] which plays several players in lockstep
+pub mod sync_group;
+
+/// Contains [`playlist::Playlist`] and the [`playlist::PlaylistWidget`] that shows its queue
+pub mod playlist;
+
+/// Contains [`cue::CueSheet`] which reads the tracks of single file albums from ``.cue`` files
+pub mod cue;
+
+/// Contains [`lyrics::parse_lrc`] which reads time synced lyrics, and [`lyrics::read_embedded`] for lyrics stored in the tags of a file
+pub mod lyrics;
+
+/// Contains the [`metadata::MetadataProvider`] trait that looks up lyrics, artwork and track info for players
+pub mod metadata;
+
+/// Contains [`podcast::Podcast`] which reads the episodes of a podcast from its RSS feed, enabled by the ``podcast`` feature
+#[cfg(feature = "podcast")]
+pub mod podcast;
+
+/// Contains [`history::History`] which remembers recently played items and where they were left off
+pub mod history;
+
+/// Contains the [`preferences::PreferenceStore`] trait that remembers settings per file, along with the built in stores
+pub mod preferences;
+
+/// Contains [`key_bindings::KeyBindings`] which maps keyboard shortcuts to player actions
+pub mod key_bindings;
+
+/// Contains [`controls::ControlsConfig`] which customizes the control bar
+pub mod controls;
+
+/// Contains the [`transcriber::Transcriber`] trait that transcripts are made with, along with the built in Whisper transcriber
+pub mod transcriber;
+
+/// Contains [`transcription_queue::TranscriptionQueue`] which transcribes many files in the background
+pub mod transcription_queue;
+
+/// Contains [`transcript::TranscriptFilter`] and [`transcript::TranscriptNormalization`] which clean up transcripts before they are shown,
+/// [`transcript::TranscriptStyle`] which changes how they look and [`transcript::subtitles`] which turns a transcript into SRT or WebVTT subtitles
+pub mod transcript;
+
 /// Contains [`player::Player`] a struct that holds all info needed for the player to run
 pub mod player;
+
+/// On disk cache for waveform peaks and spectrograms
+mod analysis_cache;
+
+/// Audio processing applied to the source before it reaches the output
+mod dsp;
+
+/// Contains the [`visualizer::Visualizer`] trait along with the built in visualizations
+pub mod visualizer;
+
+/// Reader for local files that can memory map the large ones
+mod file_reader;
+
+/// Reader for files at a URL that are read while they download
+#[cfg(feature = "streaming")]
+mod remote_reader;
+
+/// Decoder that skips the corrupt parts of a file instead of stopping at them
+mod decoder;
+
+/// Shared threads that the background work of every player runs on
+mod worker_pool;
+
+/// Reader for the frames of ID3v2 tags that symphonia does not expose
+mod id3;
+
+/// Encoder behind [`ExportFormat::Flac`]
+mod flac;
+
+/// Zoomable waveform view drawn by [`player::Player::timeline_ui`]
+mod timeline;
+
+/// Contains [`effects::EffectsChain`] and the built in [`effects::Effect`]s that can be inserted into it
+pub mod effects;
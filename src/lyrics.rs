@@ -0,0 +1,179 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use crate::{id3, InputMode, TranscriptionData};
+
+/// Reads the lines of an ``.lrc`` file, see [`parse_lrc`]
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::lyrics;
+/// use egui_player::player::Player;
+/// use egui_player::CaptionSource;
+///
+/// let mut player = Player::from_path("song.mp3");
+/// player.set_lyrics(lyrics::read_lrc("song.lrc").unwrap());
+/// player.set_caption_source(CaptionSource::Lyrics);
+/// ```
+pub fn read_lrc(path: impl AsRef<Path>) -> io::Result<Vec<TranscriptionData>> {
+    Ok(parse_lrc(&String::from_utf8_lossy(&fs::read(path)?)))
+}
+
+/// Reads time synced lyrics in the LRC format, one [`TranscriptionData`] for each line sorted by time
+///
+/// Lines with several timestamps are repeated at each of them, the ``offset`` tag is applied and the word timings of
+/// enhanced LRC are dropped. Every line ends where the next one starts, the last line ends where it starts
+pub fn parse_lrc(contents: &str) -> Vec<TranscriptionData> {
+    let mut offset = 0;
+    let mut lines: Vec<(Duration, String)> = vec![];
+    for line in contents.trim_start_matches('\u{feff}').lines() {
+        let mut rest = line.trim();
+        let mut times = vec![];
+        while let Some(tag) = rest.strip_prefix('[')
+            && let Some((tag, after)) = tag.split_once(']')
+        {
+            if let Some(time) = lrc_timestamp(tag) {
+                times.push(time);
+            } else if let Some(value) = tag.strip_prefix("offset:") {
+                offset = value.trim().parse::<i64>().unwrap_or(0);
+            }
+            rest = after;
+        }
+        let text = strip_word_timings(rest.trim());
+        for time in times {
+            // A positive offset shows the lyrics sooner
+            let millis = i64::try_from(time.as_millis())
+                .unwrap_or(i64::MAX)
+                .saturating_sub(offset)
+                .max(0) as u64;
+            lines.push((Duration::from_millis(millis), text.clone()));
+        }
+    }
+    lines.sort_by_key(|(time, _)| *time);
+    timed_lines(lines)
+}
+
+/// Reads the synced lyrics of the ``SYLT`` frame of an ID3v2 tag, empty when the file has none
+///
+/// Lyrics timed in MPEG frames instead of milliseconds are skipped
+pub fn read_embedded(file_input: &InputMode) -> Vec<TranscriptionData> {
+    let frames = id3::read_frames(file_input).unwrap_or_default();
+    let Some(frame) = frames
+        .iter()
+        .find(|frame| &frame.id == b"SYLT" && frame.data.len() > 6)
+    else {
+        return vec![];
+    };
+    let encoding = frame.data[0];
+    // Encoding, language, timestamp format and content type come first
+    if frame.data[4] != 2 {
+        return vec![];
+    }
+    let (_, mut rest) = id3::terminated_text(encoding, &frame.data[6..]);
+    let mut lines = vec![];
+    while !rest.is_empty() {
+        let (text, after) = id3::terminated_text(encoding, rest);
+        let Some(time) = after.get(..4) else {
+            break;
+        };
+        let millis = u32::from_be_bytes([time[0], time[1], time[2], time[3]]);
+        lines.push((
+            Duration::from_millis(millis as u64),
+            text.trim().to_string(),
+        ));
+        rest = &after[4..];
+    }
+    lines.sort_by_key(|(time, _)| *time);
+    timed_lines(lines)
+}
+
+/// Turns sorted lines into [`TranscriptionData`] that last until the next line, dropping the empty lines that only end the one before
+fn timed_lines(lines: Vec<(Duration, String)>) -> Vec<TranscriptionData> {
+    let ends: Vec<Duration> = lines
+        .iter()
+        .skip(1)
+        .map(|(time, _)| *time)
+        .chain(lines.last().map(|(time, _)| *time))
+        .collect();
+    lines
+        .into_iter()
+        .zip(ends)
+        .filter(|((_, text), _)| !text.is_empty())
+        .map(|((time, text), end)| TranscriptionData { text, time, end })
+        .collect()
+}
+
+/// Parses a ``mm:ss.xx`` timestamp, some files use a colon in front of the hundredths as well
+fn lrc_timestamp(tag: &str) -> Option<Duration> {
+    let (minutes, seconds) = tag.split_once(':')?;
+    if minutes.is_empty() || !minutes.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+    let seconds = seconds
+        .replacen(':', ".", 1)
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)?;
+    Duration::try_from_secs_f64(minutes.parse::<f64>().ok()? * 60.0 + seconds).ok()
+}
+
+/// Removes the ``<mm:ss.xx>`` timings of single words used by enhanced LRC
+fn strip_word_timings(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        match rest[start..].find('>') {
+            Some(end) if lrc_timestamp(&rest[start + 1..start + end]).is_some() => {
+                stripped.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            _ => {
+                stripped.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str, time: u64, end: u64) -> TranscriptionData {
+        TranscriptionData {
+            text: text.to_string(),
+            time: Duration::from_millis(time),
+            end: Duration::from_millis(end),
+        }
+    }
+
+    #[test]
+    fn lines_are_repeated_sorted_and_end_at_the_next() {
+        let lyrics = parse_lrc("[00:12.50]Hello\n[00:10.00][00:20.00]Again\n[00:25.00]\n");
+        assert_eq!(
+            lyrics,
+            vec![
+                line("Again", 10_000, 12_500),
+                line("Hello", 12_500, 20_000),
+                line("Again", 20_000, 25_000),
+            ]
+        );
+    }
+
+    #[test]
+    fn offset_and_word_timings_are_applied() {
+        let lyrics = parse_lrc("[offset:-250]\n[01:01:00]<01:01.00>Hello <01:01.50>world");
+        assert_eq!(lyrics, vec![line("Hello world", 61_250, 61_250)]);
+    }
+
+    #[test]
+    fn out_of_range_times_and_offsets_do_not_panic() {
+        let lyrics = parse_lrc(
+            "[99999999999999999999:00]a\n[00:1e300]b\n[offset:-9223372036854775808]\n[00:02.00]c",
+        );
+        assert_eq!(lyrics.len(), 1);
+        assert_eq!(lyrics[0].text, "c");
+    }
+}
@@ -1,17 +1,51 @@
 use core::panic;
 use futures_util::stream::StreamExt;
 use kalosm_common::Cache;
-use kalosm_sound::{Whisper, WhisperBuilder};
-use rodio::{source::Source, Decoder};
+use kalosm_sound::{Whisper, WhisperBuilder, WhisperSource};
+use rodio::{
+    buffer::SamplesBuffer,
+    source::{Source, UniformSourceIterator},
+    Decoder,
+};
+use rustfft::{num_complex::Complex, FftPlanner};
+use sha2::{Digest, Sha256};
 use std::{
-    fs::File,
-    io::{BufReader, Cursor},
+    f32::consts::PI,
+    fs::{self, File},
+    io::{self, Cursor},
+    ops::Range,
     path::{Path, PathBuf},
     str::FromStr,
-    time::Duration,
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+use symphonia::core::{
+    formats::FormatOptions,
+    io::{MediaSource, MediaSourceStream},
+    meta::{MetadataOptions, StandardTagKey, StandardVisualKey, Tag, Visual},
+    probe::Hint,
+};
+
+#[cfg(feature = "streaming")]
+use crate::remote_reader::{self, RemoteReader};
+use crate::{
+    decoder::SkippingDecoder,
+    file_reader::{self, FileReader},
+    flac, id3,
+    transcriber::WhisperTranscriber,
+    AlignedWord, BenchResult, Chapter, CoverArt, DecodingOptions, ExportFormat, InputMode,
+    LocalModel, MediaType, ModelPath, Spectrogram, TrackInfo, TranscriptionData,
+    TranscriptionProgress, TranscriptionResources, WerReport, WhisperModel,
 };
 
-use crate::{InputMode, MediaType, ModelPath, TranscriptionData, TranscriptionProgress};
+/// Amount of samples analysed by each FFT in [`get_spectrogram`]
+const SPECTROGRAM_WINDOW: usize = 1024;
+
+/// Peak below which 10ms of audio counts as silence in [`content_bounds`], about -50 dBFS
+const SILENCE_THRESHOLD: f32 = 0.003;
+
+/// Silence kept around the content found by [`content_bounds`] so the first and last sounds are not cut off
+const SILENCE_PADDING: Duration = Duration::from_millis(50);
 
 /// Formats [`Duration`] into a [`String`] with HH:MM:SS or MM:SS depending on inputted [`Duration`]
 ///
@@ -45,6 +79,38 @@ pub fn format_duration(duration: Duration) -> String {
     }
 }
 
+/// Formats duration the way it is read out by screen readers
+/// # Examples
+///
+/// ``` rust
+/// use egui_player::media_information;
+/// use std::time::Duration;
+///
+/// let spoken_duration = media_information::spoken_duration(Duration::from_secs(64));
+/// assert_eq!(spoken_duration, "1 minute 4 seconds");
+/// ```
+pub fn spoken_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs() % 60;
+    let minutes = (duration.as_secs() / 60) % 60;
+    let hours = (duration.as_secs() / 60) / 60;
+    let parts: Vec<String> = [(hours, "hour"), (minutes, "minute"), (seconds, "second")]
+        .into_iter()
+        .filter(|(amount, _)| *amount > 0)
+        .map(|(amount, unit)| {
+            if amount == 1 {
+                format!("{amount} {unit}")
+            } else {
+                format!("{amount} {unit}s")
+            }
+        })
+        .collect();
+    if parts.is_empty() {
+        "0 seconds".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
 /// Checks file extension of passed in file path / extension to determine if it is an audio or video file
 /// # Examples
 ///
@@ -78,49 +144,59 @@ pub fn get_media_type(file_path: &str) -> MediaType {
 /// For supported types, look at the *[README](https://github.com/AravDesai/egui-player/blob/master/README.md)*
 pub fn get_total_time(media_type: MediaType, input_mode: InputMode) -> Duration {
     match media_type {
-        MediaType::Audio => {
-            let mut duration: Duration = match input_mode {
-                InputMode::FilePath(file_path) => {
-                    let file = BufReader::new(File::open(&file_path).unwrap());
-                    match Path::new(&file_path)
-                        .extension()
-                        .and_then(|ext| ext.to_str())
-                    {
-                        Some(ext) => match ext.to_lowercase().as_str() {
-                            "mp3" => mp3_duration::from_path(file_path).unwrap_or(Duration::ZERO),
-                            _ => {
-                                let source = Decoder::new(file).unwrap();
-                                Source::total_duration(&source).unwrap_or(Duration::ZERO)
-                            }
-                        },
-                        None => Duration::ZERO,
-                    }
+        MediaType::Audio => match input_mode {
+            InputMode::FilePath(file_path) => {
+                let file = FileReader::open(&file_path).unwrap();
+                match Path::new(&file_path)
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                {
+                    Some(ext) => match ext.to_lowercase().as_str() {
+                        "mp3" => mp3_duration::from_path(file_path).unwrap_or(Duration::ZERO),
+                        _ => {
+                            let source = Decoder::new(file).unwrap();
+                            Source::total_duration(&source).unwrap_or(Duration::ZERO)
+                        }
+                    },
+                    None => Duration::ZERO,
                 }
-                InputMode::Bytes(bytes) => {
-                    if let Some(kind) = infer::get(&bytes) {
-                        let ext = kind.extension();
-                        match ext {
-                            "mp3" => mp3_duration::from_read(&mut Cursor::new(bytes))
-                                .unwrap_or(Duration::ZERO),
-                            _ => {
-                                if let Ok(decoder) = Decoder::new(Cursor::new(bytes)) {
-                                    decoder.total_duration().unwrap_or(Duration::ZERO)
-                                } else {
-                                    Duration::ZERO
-                                }
+            }
+            InputMode::Bytes(bytes) => {
+                if let Some(kind) = infer::get(&bytes) {
+                    let ext = kind.extension();
+                    match ext {
+                        "mp3" => mp3_duration::from_read(&mut Cursor::new(bytes))
+                            .unwrap_or(Duration::ZERO),
+                        _ => {
+                            if let Ok(decoder) = Decoder::new(Cursor::new(bytes)) {
+                                decoder.total_duration().unwrap_or(Duration::ZERO)
+                            } else {
+                                Duration::ZERO
                             }
                         }
-                    } else {
-                        Duration::ZERO
                     }
+                } else {
+                    Duration::ZERO
                 }
-            };
-
-            if duration != Duration::ZERO {
-                duration += Duration::from_secs(1);
             }
-            duration
-        }
+            #[cfg(feature = "streaming")]
+            InputMode::Url(url) => {
+                let Ok(mut reader) = RemoteReader::open(&url) else {
+                    return Duration::ZERO;
+                };
+                match remote_reader::extension(&url)
+                    .map(str::to_lowercase)
+                    .as_deref()
+                {
+                    // Finding the length of an MP3 reads all of it, which waits for the whole download
+                    Some("mp3") => mp3_duration::from_read(&mut reader).unwrap_or(Duration::ZERO),
+                    _ => Decoder::new(reader)
+                        .ok()
+                        .and_then(|decoder| decoder.total_duration())
+                        .unwrap_or(Duration::ZERO),
+                }
+            }
+        },
         MediaType::Video => todo!(),
         MediaType::Error => panic!("Can not get time because of unsupported format"),
     }
@@ -149,70 +225,1389 @@ pub async fn transcribe_audio(
     progress_sender: Option<tokio::sync::mpsc::UnboundedSender<TranscriptionProgress>>,
     model_path: ModelPath,
 ) -> Vec<TranscriptionData> {
-    let model = match model_path {
-        ModelPath::Default => Whisper::new().await.unwrap(),
+    let Some(model) = load_whisper(
+        model_path,
+        WhisperModel::default(),
+        TranscriptionResources::default(),
+    )
+    .await
+    else {
+        return vec![];
+    };
+    transcribe_with_model(
+        &model,
+        file_input,
+        is_timestamped,
+        DecodingOptions::default(),
+        progress_sender,
+    )
+    .await
+}
+
+/// Loads the model of ``config`` and transcribes ``file_input`` with it, timing both and measuring memory use
+///
+/// Run it on the hardware an app targets to pick the model and resources that keep up with it. Decoding the file is not
+/// part of the transcription time. Fails if the model could not be loaded
+///
+/// The peak memory is the most the process held since it started, so bench each model in its own process to compare them
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::transcriber::WhisperTranscriber;
+/// use egui_player::{media_information, DecodingOptions, InputMode, ModelPath, TranscriptionResources, WhisperModel};
+///
+/// # async fn run() {
+/// for model in [WhisperModel::QuantizedTiny, WhisperModel::Base] {
+///     let config = WhisperTranscriber {
+///         model_path: ModelPath::Default,
+///         model,
+///         resources: TranscriptionResources::default(),
+///         decoding: DecodingOptions::default(),
+///     };
+///     let result = media_information::bench_transcription(InputMode::FilePath("hello.mp3".to_string()), &config)
+///         .await
+///         .unwrap();
+///     println!("{model:?}: {:.2}x real time", result.real_time_factor);
+/// }
+/// # }
+/// ```
+pub async fn bench_transcription(
+    file_input: InputMode,
+    config: &WhisperTranscriber,
+) -> io::Result<BenchResult> {
+    let started = Instant::now();
+    let model = load_whisper(config.model_path.clone(), config.model, config.resources)
+        .await
+        .ok_or_else(|| io::Error::other("the transcription model could not be loaded"))?;
+    let model_load_time = started.elapsed();
+
+    let samples = whisper_samples(file_input)?;
+    let audio_duration = Duration::from_secs_f64(samples.len() as f64 / WHISPER_SAMPLE_RATE as f64);
+    let mut transcript = vec![];
+    let started = Instant::now();
+    transcribe_samples(
+        &model,
+        &samples,
+        0.0,
+        false,
+        config.decoding,
+        &None,
+        &mut transcript,
+    )
+    .await;
+    let transcription_time = started.elapsed();
+    Ok(BenchResult {
+        model_load_time,
+        transcription_time,
+        audio_duration,
+        real_time_factor: transcription_time.as_secs_f32()
+            / audio_duration.as_secs_f32().max(0.001),
+        peak_memory: peak_memory(),
+        transcript,
+    })
+}
+
+/// Peak resident memory of the process since it started in bytes
+#[cfg(target_os = "linux")]
+fn peak_memory() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    let kilobytes = status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn peak_memory() -> Option<u64> {
+    None
+}
+
+/// [`transcribe_audio`] with an already loaded ``model``
+pub(crate) async fn transcribe_with_model(
+    model: &Whisper,
+    file_input: InputMode,
+    is_timestamped: bool,
+    decoding: DecodingOptions,
+    progress_sender: Option<tokio::sync::mpsc::UnboundedSender<TranscriptionProgress>>,
+) -> Vec<TranscriptionData> {
+    let mut transcript: Vec<TranscriptionData> = vec![];
+    let samples = match whisper_samples(file_input) {
+        Ok(samples) => samples,
+        Err(error) => {
+            log::error!("Could not decode the audio to transcribe: {error}");
+            return transcript;
+        }
+    };
+    transcribe_samples(
+        model,
+        &samples,
+        0.0,
+        is_timestamped,
+        decoding,
+        &progress_sender,
+        &mut transcript,
+    )
+    .await;
+    log::info!("Transcription finished with {} chunks", transcript.len());
+    if let Some(progress) = progress_sender {
+        let _ = progress.send(TranscriptionProgress::Finished);
+    }
+    transcript
+}
+
+/// Transcribes the part of ``file_input`` between ``start`` and ``end`` with an already loaded ``model``
+///
+/// Times are from the start of the file, an ``end`` of ``Duration::MAX`` goes on to the end of the file.
+/// Unlike [`transcribe_audio`] this does not send [`TranscriptionProgress::Finished`]
+pub(crate) async fn transcribe_range(
+    model: &Whisper,
+    file_input: InputMode,
+    start: Duration,
+    end: Duration,
+    is_timestamped: bool,
+    decoding: DecodingOptions,
+    progress_sender: &Option<tokio::sync::mpsc::UnboundedSender<TranscriptionProgress>>,
+) -> Vec<TranscriptionData> {
+    let mut transcript: Vec<TranscriptionData> = vec![];
+    let source: Box<dyn Source<Item = f32> + Send> = match open_source_at(file_input, start) {
+        // Taking ``Duration::MAX`` would overflow when it is turned into a count of samples
+        Ok(source) if end == Duration::MAX => source,
+        Ok(source) => Box::new(source.take_duration(end.saturating_sub(start))),
+        Err(error) => {
+            log::error!("Could not decode the audio to transcribe: {error}");
+            return transcript;
+        }
+    };
+    let samples: Vec<f32> =
+        UniformSourceIterator::<_, f32>::new(source, 1, WHISPER_SAMPLE_RATE).collect();
+    transcribe_samples(
+        model,
+        &samples,
+        start.as_secs_f32(),
+        is_timestamped,
+        decoding,
+        progress_sender,
+        &mut transcript,
+    )
+    .await;
+    transcript
+}
+
+/// Loads the Whisper model at ``model_path``, downloading it the first time
+///
+/// ``model`` is downloaded to the cache the first time it is used.
+/// The model runs on a thread of its own that is started here, so it is started from a thread with the priority of ``resources``.
+/// ``None`` when the files of a [`ModelPath::Local`] can not be staged or the model can not be loaded, the error is logged
+pub(crate) async fn load_whisper(
+    model_path: ModelPath,
+    model: WhisperModel,
+    resources: TranscriptionResources,
+) -> Option<Whisper> {
+    log::info!("Loading the transcription model, it is downloaded the first time");
+    let mut builder = WhisperBuilder::default().with_source(whisper_source(model));
+    match model_path {
+        ModelPath::Default => {}
         ModelPath::Custom(model_path) => {
-            let builder = WhisperBuilder::default();
-            builder
-                .with_cache(Cache::new(PathBuf::from_str(&model_path).unwrap()))
+            log::debug!("Using the transcription model cache at {model_path}");
+            builder = builder.with_cache(Cache::new(PathBuf::from_str(&model_path).unwrap()));
+        }
+        ModelPath::Local(local) => match stage_local_model(&local, model) {
+            Ok(cache) => builder = builder.with_cache(Cache::new(cache)),
+            Err(error) => {
+                log::error!("The local transcription model can not be used: {error}");
+                return None;
+            }
+        },
+    }
+    let model = if resources.low_priority {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        thread::spawn(move || {
+            lower_thread_priority();
+            // The threads of the pool take the priority of the thread that starts them
+            limit_inference_threads(resources.threads);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
                 .build()
-                .await
-                .unwrap()
+                .unwrap();
+            let _ = sender.send(runtime.block_on(builder.build()));
+        });
+        receiver.await.unwrap()
+    } else {
+        limit_inference_threads(resources.threads);
+        builder.build().await
+    };
+    match model {
+        Ok(model) => {
+            log::info!("Transcription model loaded");
+            Some(model)
+        }
+        Err(error) => {
+            log::error!("The transcription model could not be loaded: {error}");
+            None
+        }
+    }
+}
+
+/// Repository and names of the model, tokenizer and config files kalosm looks for ``model`` under in its cache
+fn hub_files(model: WhisperModel) -> (&'static str, [&'static str; 3]) {
+    const FILES: [&str; 3] = ["model.safetensors", "tokenizer.json", "config.json"];
+    const QUANTIZED_FILES: [&str; 3] = ["model.gguf", "tokenizer.json", "config.json"];
+    match model {
+        WhisperModel::Tiny => ("openai/whisper-tiny", FILES),
+        WhisperModel::QuantizedTiny => (
+            "lmz/candle-whisper",
+            [
+                "model-tiny-q80.gguf",
+                "tokenizer-tiny.json",
+                "config-tiny.json",
+            ],
+        ),
+        WhisperModel::TinyEn => ("openai/whisper-tiny.en", FILES),
+        WhisperModel::QuantizedTinyEn => (
+            "lmz/candle-whisper",
+            [
+                "model-tiny-en-q80.gguf",
+                "tokenizer-tiny-en.json",
+                "config-tiny-en.json",
+            ],
+        ),
+        WhisperModel::Base => ("openai/whisper-base", FILES),
+        WhisperModel::BaseEn => ("openai/whisper-base.en", FILES),
+        WhisperModel::Small => ("openai/whisper-small", FILES),
+        WhisperModel::SmallEn => ("openai/whisper-small.en", FILES),
+        WhisperModel::Medium => ("openai/whisper-medium", FILES),
+        WhisperModel::MediumEn => ("openai/whisper-medium.en", FILES),
+        WhisperModel::QuantizedDistilMediumEn => (
+            "Demonthos/candle-quantized-whisper-medium-distil",
+            QUANTIZED_FILES,
+        ),
+        WhisperModel::Large => ("openai/whisper-large", FILES),
+        WhisperModel::LargeV2 => ("openai/whisper-large-v2", FILES),
+        WhisperModel::DistilMediumEn => ("distil-whisper/distil-medium.en", FILES),
+        WhisperModel::DistilLargeV2 => ("distil-whisper/distil-large-v2", FILES),
+        WhisperModel::DistilLargeV3 => ("distil-whisper/distil-large-v3", FILES),
+        WhisperModel::QuantizedDistilLargeV3 => (
+            "Demonthos/candle-quantized-whisper-distil-v3",
+            QUANTIZED_FILES,
+        ),
+        WhisperModel::QuantizedLargeV3Turbo => (
+            "Demonthos/candle-quantized-whisper-large-v3-turbo",
+            QUANTIZED_FILES,
+        ),
+    }
+}
+
+/// Links the files of ``local`` into a cache folder laid out the way kalosm expects ``model`` to be and returns that folder
+///
+/// kalosm only takes models from its cache. The folder is named after the paths, sizes and modification times of the
+/// files, so a local file that changed is staged again instead of the old one being used.
+/// The files are symlinked, or hard linked where symlinks are not allowed, and only copied when neither works.
+///
+/// kalosm replaces a cached file when the one on Hugging Face is newer. Copies are dated far in the future so they are
+/// always kept, links keep the date of the local file, which is newer than the upload for models made from it.
+/// kalosm replaces a link by renaming the download over it, so the local file itself is never written to
+fn stage_local_model(local: &LocalModel, model: WhisperModel) -> io::Result<PathBuf> {
+    let (model_id, files) = hub_files(model);
+    let sources = [&local.model, &local.tokenizer, &local.config];
+    let mut hasher = Sha256::new();
+    for source in sources {
+        let metadata = fs::metadata(source)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        hasher.update(source.as_os_str().as_encoded_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+        hasher.update(modified.as_nanos().to_le_bytes());
+    }
+    let key: String = hasher.finalize()[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    let cache = std::env::temp_dir()
+        .join("egui_player_local_model")
+        .join(key);
+    let folder = cache.join(model_id).join("main");
+    fs::create_dir_all(&folder)?;
+    for (source, file) in sources.into_iter().zip(files) {
+        let staged = folder.join(file);
+        if fs::metadata(&staged).is_ok() {
+            continue;
+        }
+        let source = fs::canonicalize(source)?;
+        if link_file(&source, &staged).is_err() {
+            log::debug!("Copying the local model file {}", source.display());
+            let never_outdated = SystemTime::now() + Duration::from_secs(100 * 365 * 24 * 60 * 60);
+            fs::copy(&source, &staged)?;
+            File::options()
+                .write(true)
+                .open(&staged)?
+                .set_modified(never_outdated)?;
         }
+    }
+    Ok(cache)
+}
+
+/// Symlinks ``staged`` to ``source``, or hard links it where symlinks can not be made
+fn link_file(source: &Path, staged: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    if std::os::unix::fs::symlink(source, staged).is_ok() {
+        return Ok(());
+    }
+    #[cfg(windows)]
+    if std::os::windows::fs::symlink_file(source, staged).is_ok() {
+        return Ok(());
+    }
+    fs::hard_link(source, staged)
+}
+
+/// Source kalosm downloads ``model`` from
+fn whisper_source(model: WhisperModel) -> WhisperSource {
+    match model {
+        WhisperModel::Tiny => WhisperSource::Tiny,
+        WhisperModel::QuantizedTiny => WhisperSource::QuantizedTiny,
+        WhisperModel::TinyEn => WhisperSource::TinyEn,
+        WhisperModel::QuantizedTinyEn => WhisperSource::QuantizedTinyEn,
+        WhisperModel::Base => WhisperSource::Base,
+        WhisperModel::BaseEn => WhisperSource::BaseEn,
+        WhisperModel::Small => WhisperSource::Small,
+        WhisperModel::SmallEn => WhisperSource::SmallEn,
+        WhisperModel::Medium => WhisperSource::Medium,
+        WhisperModel::MediumEn => WhisperSource::MediumEn,
+        WhisperModel::Large => WhisperSource::Large,
+        WhisperModel::LargeV2 => WhisperSource::LargeV2,
+        WhisperModel::DistilMediumEn => WhisperSource::DistilMediumEn,
+        WhisperModel::QuantizedDistilMediumEn => WhisperSource::QuantizedDistilMediumEn,
+        WhisperModel::DistilLargeV2 => WhisperSource::DistilLargeV2,
+        WhisperModel::DistilLargeV3 => WhisperSource::DistilLargeV3,
+        WhisperModel::QuantizedDistilLargeV3 => WhisperSource::QuantizedDistilLargeV3,
+        WhisperModel::QuantizedLargeV3Turbo => WhisperSource::QuantizedLargeV3Turbo,
+    }
+}
+
+/// Sizes the global rayon pool that inference runs on to ``threads``, which only works before anything used it
+fn limit_inference_threads(threads: Option<usize>) {
+    let Some(threads) = threads else {
+        return;
     };
-    let mut text_stream;
-    let mut transcript: Vec<TranscriptionData> = vec![];
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(threads.max(1))
+        .build_global()
+    {
+        Ok(()) => log::info!("Transcription runs on {threads} threads"),
+        Err(error) => log::warn!("Could not limit transcription to {threads} threads: {error}"),
+    }
+}
+
+/// Makes the scheduler prefer every other thread over the current one and threads started from it
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    // SAFETY: gettid and setpriority only read their arguments, 0 is the calling thread
+    let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, libc::gettid() as libc::id_t, 10) };
+    if result != 0 {
+        log::warn!(
+            "Could not lower the priority of transcription: {}",
+            io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_thread_priority() {
+    log::debug!("Lowering the priority of transcription is only supported on Linux");
+}
+
+/// Transcribes mono 16 kHz ``samples`` that start ``offset`` seconds into the file
+async fn transcribe_samples(
+    model: &Whisper,
+    samples: &[f32],
+    offset: f32,
+    is_timestamped: bool,
+    decoding: DecodingOptions,
+    progress_sender: &Option<tokio::sync::mpsc::UnboundedSender<TranscriptionProgress>>,
+    transcript: &mut Vec<TranscriptionData>,
+) {
+    // Only speech is transcribed, in pieces that are split at pauses and short enough for a single Whisper window
+    let speech = speech_chunks(samples);
+    log::debug!("Transcribing {} pieces of speech", speech.len());
 
+    for range in speech {
+        let offset = offset + range.start as f32 / WHISPER_SAMPLE_RATE as f32;
+        let audio = SamplesBuffer::new(1, WHISPER_SAMPLE_RATE, samples[range].to_vec());
+        let mut text_stream = model.transcribe(audio).timestamped();
+        let mut segment_counter = 0.0;
+
+        while let Some(segment) = text_stream.next().await {
+            if decoding.is_silent(segment.probability_of_no_speech() as f32) {
+                log::debug!("Leaving out a piece without speech at {offset:.2}s");
+                segment_counter += 1.0;
+                continue;
+            }
+            transcribe_segment(
+                &segment,
+                offset + 30.0 * segment_counter,
+                is_timestamped,
+                progress_sender,
+                transcript,
+            );
+            segment_counter += 1.0;
+        }
+        log::debug!("Transcribed the speech at {offset:.2}s");
+    }
+}
+
+/// Sends and collects the words of a segment that starts ``offset`` seconds into the file
+fn transcribe_segment(
+    segment: &kalosm_sound::Segment,
+    offset: f32,
+    is_timestamped: bool,
+    progress_sender: &Option<tokio::sync::mpsc::UnboundedSender<TranscriptionProgress>>,
+    transcript: &mut Vec<TranscriptionData>,
+) {
+    // Whisper times tokens, which are often pieces of a word. A token starting with a space starts a new word
+    let mut words: Vec<(String, Range<f32>)> = vec![];
+    for chunk in segment.chunks() {
+        let Some(time_range) = chunk.timestamp() else {
+            continue;
+        };
+        let text = chunk.text();
+        match words.last_mut() {
+            Some((word, range)) if !text.starts_with(char::is_whitespace) => {
+                word.push_str(text);
+                range.end = time_range.end;
+            }
+            _ => words.push((text.to_string(), time_range)),
+        }
+    }
+
+    for (word, time_range) in words {
+        let true_start = time_range.start + offset;
+        let true_end = time_range.end.max(time_range.start) + offset;
+        let transcription_data = TranscriptionData {
+            text: transcript_text(&word, true_start, true_end, is_timestamped),
+            time: Duration::from_secs_f32(true_start),
+            end: Duration::from_secs_f32(true_end),
+        };
+        log::trace!("Transcribed {true_start:.2}s-{true_end:.2}s: {word}");
+        profiling::scope!("send_transcription_chunk");
+        if let Some(progress) = progress_sender {
+            let _ = progress.send(TranscriptionProgress::InProgress(
+                transcription_data.clone(),
+            ));
+        }
+        transcript.push(transcription_data);
+    }
+    if let Some(progress) = progress_sender {
+        let _ = progress.send(TranscriptionProgress::Reading);
+    }
+}
+
+/// Sample rate Whisper listens at
+const WHISPER_SAMPLE_RATE: u32 = 16000;
+
+/// Longest piece of speech handed to Whisper at once, just under its 30 second window
+const MAX_SPEECH_CHUNK: Duration = Duration::from_secs(29);
+
+/// Length of the frames that [`speech_regions`] measures the loudness of
+const VAD_FRAME: Duration = Duration::from_millis(30);
+
+/// Decodes ``file_input`` to the mono 16 kHz samples Whisper listens to
+pub(crate) fn whisper_samples(file_input: InputMode) -> io::Result<Vec<f32>> {
+    Ok(
+        UniformSourceIterator::<_, f32>::new(open_source(file_input)?, 1, WHISPER_SAMPLE_RATE)
+            .collect(),
+    )
+}
+
+/// Finds the stretches of ``file_input`` that contain speech, as start and end times
+///
+/// Frames well above the noise floor of the file count as speech. Short pauses are bridged and every region is padded slightly,
+/// so words are not cut off
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// for (start, end) in media_information::speech_regions(InputMode::FilePath("interview.mp3".to_string())).unwrap() {
+///     println!("{start:?} - {end:?}");
+/// }
+/// ```
+pub fn speech_regions(file_input: InputMode) -> io::Result<Vec<(Duration, Duration)>> {
+    let to_time =
+        |sample: usize| Duration::from_secs_f64(sample as f64 / WHISPER_SAMPLE_RATE as f64);
+    Ok(speech_chunks(&whisper_samples(file_input)?)
+        .into_iter()
+        .map(|range| (to_time(range.start), to_time(range.end)))
+        .collect())
+}
+
+/// Sample ranges of mono 16 kHz ``samples`` that contain speech, each at most [`MAX_SPEECH_CHUNK`] long
+///
+/// Silence longer than a second between regions is skipped, shorter pauses are kept so chunks split at natural pauses
+fn speech_chunks(samples: &[f32]) -> Vec<Range<usize>> {
+    let frame = (VAD_FRAME.as_secs_f64() * WHISPER_SAMPLE_RATE as f64) as usize;
+    let frames_in =
+        |duration: Duration| (duration.as_secs_f64() / VAD_FRAME.as_secs_f64()) as usize;
+    let loudness: Vec<f32> = samples
+        .chunks(frame)
+        .map(|frame| {
+            let rms = (frame.iter().map(|sample| sample * sample).sum::<f32>()
+                / frame.len() as f32)
+                .sqrt();
+            20.0 * rms.max(1e-6).log10()
+        })
+        .collect();
+    if loudness.is_empty() {
+        return vec![];
+    }
+
+    // Speech is anything 12 dB over the quietest tenth of the file
+    let mut sorted = loudness.clone();
+    sorted.sort_by(f32::total_cmp);
+    let threshold = (sorted[sorted.len() / 10] + 12.0).max(-55.0);
+
+    let mut regions: Vec<Range<usize>> = vec![];
+    for (index, level) in loudness.iter().enumerate() {
+        if *level < threshold {
+            continue;
+        }
+        match regions.last_mut() {
+            // Bridge pauses shorter than a second
+            Some(last) if index - last.end <= frames_in(Duration::from_secs(1)) => {
+                last.end = index + 1
+            }
+            _ => regions.push(index..index + 1),
+        }
+    }
+
+    // Pad so the start and end of words are kept, then split regions that do not fit in one Whisper window
+    let padding = frames_in(Duration::from_millis(200));
+    let max_frames = frames_in(MAX_SPEECH_CHUNK);
+    let mut chunks = vec![];
+    for region in regions {
+        let mut start = region.start.saturating_sub(padding);
+        let end = (region.end + padding).min(loudness.len());
+        while end - start > max_frames {
+            // Cut at the quietest frame of the last five seconds that fit
+            let search = start + max_frames - frames_in(Duration::from_secs(5))..start + max_frames;
+            let cut = search
+                .min_by(|a, b| loudness[*a].total_cmp(&loudness[*b]))
+                .unwrap_or(start + max_frames);
+            chunks.push(start..cut);
+            start = cut;
+        }
+        chunks.push(start..end);
+    }
+    chunks
+        .into_iter()
+        .map(|range| range.start * frame..(range.end * frame).min(samples.len()))
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// Text of a piece of transcript, with its start and end time in front when ``is_timestamped`` is set
+pub(crate) fn transcript_text(text: &str, start: f32, end: f32, is_timestamped: bool) -> String {
+    if is_timestamped {
+        format!(
+            "{}-{}: {}\n",
+            format_duration(Duration::from_secs_f32(start)),
+            format_duration(Duration::from_secs_f32(end)),
+            text
+        )
+    } else {
+        text.to_string()
+    }
+}
+
+/// Lines ``generated`` up with the ``reference`` text word by word and counts the errors, for comparing model sizes or
+/// chunking settings against a transcript that is known to be right
+///
+/// Words are compared without case and without the punctuation around them. The alignment is the one with the least
+/// errors, memory use grows with the product of the lengths of both transcripts
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::media_information;
+/// use egui_player::TranscriptionData;
+/// use std::time::Duration;
+///
+/// let generated = vec![TranscriptionData {
+///     text: " Hello world".to_string(),
+///     time: Duration::ZERO,
+///     end: Duration::from_secs(1),
+/// }];
+/// let report = media_information::compare_transcripts(&generated, "Hello, wide world!");
+/// println!("{:.1}% word error rate", report.word_error_rate() * 100.0);
+/// ```
+pub fn compare_transcripts(generated: &[TranscriptionData], reference: &str) -> WerReport {
+    let words = |text: &str| -> Vec<String> {
+        text.split_whitespace()
+            .map(|word| {
+                word.trim_matches(|character: char| !character.is_alphanumeric())
+                    .to_lowercase()
+            })
+            .filter(|word| !word.is_empty())
+            .collect()
+    };
+    let generated: Vec<String> = generated
+        .iter()
+        .flat_map(|data| words(&data.text))
+        .collect();
+    let reference = words(reference);
+    let width = reference.len() + 1;
+    // Which step reached every cell, 0 is a match or substitution, 1 an insertion and 2 a deletion
+    let mut steps = vec![0_u8; (generated.len() + 1) * width];
+    let mut previous: Vec<usize> = (0..width).collect();
+    steps[1..width].fill(2);
+    for (row, word) in generated.iter().enumerate() {
+        let mut current = vec![row + 1; width];
+        steps[(row + 1) * width] = 1;
+        for column in 1..width {
+            let diagonal = previous[column - 1] + usize::from(*word != reference[column - 1]);
+            let insertion = previous[column] + 1;
+            let deletion = current[column - 1] + 1;
+            let (cost, step) = if diagonal <= insertion && diagonal <= deletion {
+                (diagonal, 0)
+            } else if insertion <= deletion {
+                (insertion, 1)
+            } else {
+                (deletion, 2)
+            };
+            current[column] = cost;
+            steps[(row + 1) * width + column] = step;
+        }
+        previous = current;
+    }
+
+    let mut report = WerReport {
+        reference_words: reference.len(),
+        ..Default::default()
+    };
+    let (mut row, mut column) = (generated.len(), reference.len());
+    while row > 0 || column > 0 {
+        let word = match steps[row * width + column] {
+            0 => {
+                row -= 1;
+                column -= 1;
+                if generated[row] == reference[column] {
+                    AlignedWord::Match(reference[column].clone())
+                } else {
+                    report.substitutions += 1;
+                    AlignedWord::Substitution {
+                        generated: generated[row].clone(),
+                        reference: reference[column].clone(),
+                    }
+                }
+            }
+            1 => {
+                row -= 1;
+                report.insertions += 1;
+                AlignedWord::Insertion(generated[row].clone())
+            }
+            _ => {
+                column -= 1;
+                report.deletions += 1;
+                AlignedWord::Deletion(reference[column].clone())
+            }
+        };
+        report.alignment.push(word);
+    }
+    report.alignment.reverse();
+    report
+}
+
+/// Memory maps local files of 16 MB and more instead of reading them through a buffer, off by default
+///
+/// Every player and analysis of the same large file then reads from the pages the system already holds, which saves
+/// memory and copies for long recordings that are opened more than once. This applies to every file opened afterwards.
+///
+/// Only enable this when files are not changed while they are open: when another process truncates a mapped file, for
+/// example a download that is restarted or a recording that is overwritten, reading past its new end crashes the whole
+/// process (``SIGBUS`` on Unix) instead of returning an error
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::media_information;
+///
+/// media_information::set_memory_mapping(true);
+/// ```
+pub fn set_memory_mapping(enabled: bool) {
+    file_reader::set_memory_mapping(enabled);
+}
+
+/// Hashes the content of an [`InputMode`] with SHA-256 and returns it as a hex string
+///
+/// The same audio gives the same hash whether it is passed in as a path or as bytes, which makes it a good key for caches
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let hash = media_information::content_hash(&InputMode::FilePath("hello.mp3".to_string())).unwrap();
+/// ```
+pub fn content_hash(file_input: &InputMode) -> io::Result<String> {
+    let mut hasher = Sha256::new();
     match file_input {
         InputMode::FilePath(file_path) => {
-            let file = BufReader::new(File::open(file_path).unwrap());
-            let audio = Decoder::new(file).unwrap();
-            text_stream = model.transcribe(audio).timestamped();
+            io::copy(&mut FileReader::open(file_path)?, &mut hasher)?;
         }
-        InputMode::Bytes(bytes) => {
-            let cursor = Cursor::new(bytes);
-            let audio = Decoder::new(cursor).unwrap();
-            text_stream = model.transcribe(audio).timestamped();
+        InputMode::Bytes(bytes) => hasher.update(bytes),
+        #[cfg(feature = "streaming")]
+        InputMode::Url(url) => {
+            io::copy(&mut RemoteReader::open(url)?, &mut hasher)?;
         }
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+/// Reads the cover art from the tags of a file, preferring the front cover when there are several pictures
+///
+/// ``None`` when the file has no pictures or its tags can not be read
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// if let Some(cover) = media_information::get_cover_art(InputMode::FilePath("hello.mp3".to_string())) {
+///     println!("{} bytes of {}", cover.data.len(), cover.media_type);
+/// }
+/// ```
+pub fn get_cover_art(file_input: InputMode) -> Option<CoverArt> {
+    let (_, visuals) = read_tags(file_input)?;
+    let visual = visuals
+        .iter()
+        .find(|visual| visual.usage == Some(StandardVisualKey::FrontCover))
+        .or(visuals.first())?;
+    Some(CoverArt {
+        media_type: visual.media_type.clone(),
+        data: visual.data.to_vec(),
+    })
+}
+
+/// Reads the chapters of a file from the ``CHAP`` and ``CTOC`` frames of its ID3v2 tag, as used by podcasts
+///
+/// Empty when the file has no chapters. Players of audio files add these as chapters on their own
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// for chapter in media_information::get_chapters(InputMode::FilePath("episode.mp3".to_string())) {
+///     println!("{:?} {}", chapter.start, chapter.title);
+/// }
+/// ```
+pub fn get_chapters(file_input: InputMode) -> Vec<Chapter> {
+    id3::chapters(&id3::read_frames(&file_input).unwrap_or_default())
+}
+
+/// Reads the title, artist and album from the tags of a file, fields are ``None`` when the file does not have them
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let info = media_information::get_track_info(InputMode::FilePath("hello.mp3".to_string()));
+/// println!("{:?} by {:?}", info.title, info.artist);
+/// ```
+pub fn get_track_info(file_input: InputMode) -> TrackInfo {
+    let mut info = TrackInfo::default();
+    for tag in read_tags(file_input)
+        .map(|(tags, _)| tags)
+        .unwrap_or_default()
+    {
+        let field = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => &mut info.title,
+            Some(StandardTagKey::Artist) => &mut info.artist,
+            Some(StandardTagKey::Album) => &mut info.album,
+            _ => continue,
+        };
+        field.get_or_insert_with(|| tag.value.to_string());
+    }
+    info
+}
+
+/// Tags and pictures of a file. Tags in front of the container, such as ID3v2, come before the tags of the container itself
+fn read_tags(file_input: InputMode) -> Option<(Vec<Tag>, Vec<Visual>)> {
+    let source: Box<dyn MediaSource> = match file_input {
+        InputMode::FilePath(file_path) => Box::new(FileReader::open(file_path).ok()?),
+        InputMode::Bytes(bytes) => Box::new(Cursor::new(bytes)),
+        #[cfg(feature = "streaming")]
+        InputMode::Url(url) => Box::new(RemoteReader::open(&url).ok()?),
     };
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &Hint::new(),
+            MediaSourceStream::new(source, Default::default()),
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .ok()?;
+    let mut tags = vec![];
+    let mut visuals = vec![];
+    if let Some(metadata) = probed.metadata.get()
+        && let Some(revision) = metadata.current()
+    {
+        tags.extend(revision.tags().iter().cloned());
+        visuals.extend(revision.visuals().iter().cloned());
+    }
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.extend(revision.tags().iter().cloned());
+        visuals.extend(revision.visuals().iter().cloned());
+    }
+    Some((tags, visuals))
+}
 
-    let mut segment_counter = 0.0;
-
-    while let Some(segment) = text_stream.next().await {
-        for chunk in segment.chunks() {
-            if let Some(time_range) = chunk.timestamp() {
-                let true_start = time_range.start + (30.0 * segment_counter);
-                let true_end = time_range.end + (30.0 * segment_counter);
-                let transcription_data = TranscriptionData {
-                    text: {
-                        if is_timestamped {
-                            format!(
-                                "{}-{}: {}\n",
-                                format_duration(Duration::from_secs_f32(true_start)),
-                                format_duration(Duration::from_secs_f32(true_end)),
-                                chunk
-                            )
-                        } else {
-                            format!("{chunk}")
-                        }
-                    },
-                    time: Duration::from_secs_f32(true_start),
-                };
-                if let Some(ref progress) = progress_sender {
-                    let _ = progress.send(TranscriptionProgress::InProgress(
-                        transcription_data.clone(),
-                    ));
-                }
-                transcript.push(transcription_data);
+/// Opens an [`InputMode`] as a decoded source of ``f32`` samples
+///
+/// Corrupt packets are skipped with a warning in the log
+pub(crate) fn open_source(file_input: InputMode) -> io::Result<Box<dyn Source<Item = f32> + Send>> {
+    let decoder = SkippingDecoder::open(file_input, None).map_err(symphonia_error)?;
+    Ok(Box::new(decoder))
+}
+
+/// [`open_source`] starting at ``start``
+fn open_source_at(
+    file_input: InputMode,
+    start: Duration,
+) -> io::Result<Box<dyn Source<Item = f32> + Send>> {
+    let mut source = open_source(file_input.clone())?;
+    if source.try_seek(start).is_err() {
+        // Not every decoder can seek, fall back to decoding up to the start
+        source = Box::new(open_source(file_input)?.skip_duration(start));
+    }
+    Ok(source)
+}
+
+fn symphonia_error(error: symphonia::core::errors::Error) -> io::Error {
+    match error {
+        symphonia::core::errors::Error::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+/// Reduces a stream of equally sized columns to a bounded amount without knowing the length of the audio up front
+///
+/// Whenever twice the target is reached neighbouring columns are merged by taking their maximum,
+/// which doubles the amount of audio every stored column covers
+struct ColumnReducer {
+    height: usize,
+    target: usize,
+    columns: Vec<f32>,
+    span: usize,
+    pending: Vec<f32>,
+    pending_count: usize,
+}
+
+impl ColumnReducer {
+    fn new(height: usize, target: usize) -> Self {
+        Self {
+            height,
+            target: target.max(1),
+            columns: vec![],
+            span: 1,
+            pending: vec![0.0; height],
+            pending_count: 0,
+        }
+    }
+
+    fn push(&mut self, column: &[f32]) {
+        if self.pending_count == 0 {
+            self.pending.copy_from_slice(column);
+        } else {
+            for (pending, value) in self.pending.iter_mut().zip(column) {
+                *pending = pending.max(*value);
             }
-            if let Some(ref progress) = progress_sender {
-                let _ = progress.send(TranscriptionProgress::Reading);
+        }
+        self.pending_count += 1;
+        if self.pending_count == self.span {
+            self.columns.extend_from_slice(&self.pending);
+            self.pending_count = 0;
+            if self.columns.len() / self.height >= self.target * 2 {
+                self.halve();
             }
         }
-        segment_counter += 1.0;
     }
-    if let Some(progress) = progress_sender {
-        let _ = progress.send(TranscriptionProgress::Finished);
+
+    fn halve(&mut self) {
+        self.columns = self
+            .columns
+            .chunks(self.height * 2)
+            .flat_map(|pair| {
+                let (first, second) = pair.split_at(self.height.min(pair.len()));
+                (0..self.height)
+                    .map(move |row| first[row].max(second.get(row).copied().unwrap_or_default()))
+            })
+            .collect();
+        self.span *= 2;
+    }
+
+    /// Returns at most ``target`` columns, flattened so each column's values are next to each other
+    fn finish(mut self) -> Vec<f32> {
+        if self.pending_count > 0 {
+            self.columns.extend_from_slice(&self.pending);
+        }
+        let count = self.columns.len() / self.height;
+        if count <= self.target {
+            return self.columns;
+        }
+        (0..self.target)
+            .flat_map(|column| {
+                let start = column * count / self.target;
+                let end = ((column + 1) * count / self.target).max(start + 1);
+                let columns = &self.columns;
+                let height = self.height;
+                (0..height).map(move |row| {
+                    (start..end)
+                        .map(|index| columns[index * height + row])
+                        .fold(0.0, f32::max)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Gets the loudest sample of evenly spaced parts of the audio, useful for drawing a waveform
+///
+/// Returns up to ``columns`` values between 0.0 and 1.0. Very short files return fewer values
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let peaks = media_information::get_waveform_peaks(InputMode::FilePath("hello.mp3".to_string()), 500).unwrap();
+/// ```
+pub fn get_waveform_peaks(file_input: InputMode, columns: usize) -> io::Result<Vec<f32>> {
+    profiling::function_scope!();
+    let source = open_source(file_input)?;
+    let channels = source.channels().max(1) as usize;
+    // Peaks are first taken over 10ms of audio and then reduced to the wanted amount of columns
+    let chunk = (source.sample_rate() as usize / 100).max(1) * channels;
+    let mut reducer = ColumnReducer::new(1, columns);
+    let mut peak = 0.0_f32;
+    for (index, sample) in source.enumerate() {
+        peak = peak.max(sample.abs());
+        if (index + 1) % chunk == 0 {
+            reducer.push(&[peak.min(1.0)]);
+            peak = 0.0;
+        }
+    }
+    if peak > 0.0 {
+        reducer.push(&[peak.min(1.0)]);
+    }
+    Ok(reducer.finish())
+}
+
+/// Start and end of the audible part of a file, leaving out the silence before and after it
+///
+/// Audio that is silent all the way through gives the whole file
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let (start, end) = media_information::content_bounds(InputMode::FilePath("memo.m4a".to_string())).unwrap();
+/// ```
+pub fn content_bounds(file_input: InputMode) -> io::Result<(Duration, Duration)> {
+    profiling::function_scope!();
+    let source = open_source(file_input)?;
+    let channels = source.channels().max(1) as usize;
+    let sample_rate = source.sample_rate().max(1) as f64;
+    let chunk = (sample_rate as usize / 100).max(1) * channels;
+    let mut audible: Option<(usize, usize)> = None;
+    let mut samples = 0;
+    let mut peak = 0.0_f32;
+    for (index, sample) in source.enumerate() {
+        peak = peak.max(sample.abs());
+        samples = index + 1;
+        if samples % chunk == 0 {
+            if peak > SILENCE_THRESHOLD {
+                let start = audible.map_or(samples - chunk, |(start, _)| start);
+                audible = Some((start, samples));
+            }
+            peak = 0.0;
+        }
+    }
+    if peak > SILENCE_THRESHOLD {
+        let start = audible.map_or(samples - samples % chunk, |(start, _)| start);
+        audible = Some((start, samples));
+    }
+    let to_time = |sample: usize| Duration::from_secs_f64((sample / channels) as f64 / sample_rate);
+    let length = to_time(samples);
+    Ok(match audible {
+        Some((start, end)) => (
+            to_time(start).saturating_sub(SILENCE_PADDING),
+            (to_time(end) + SILENCE_PADDING).min(length),
+        ),
+        None => (Duration::ZERO, length),
+    })
+}
+
+/// Integrated loudness of the whole file in LUFS as defined by EBU R128, ``f32::NEG_INFINITY`` for silence
+///
+/// The audio is K-weighted and measured in 400ms blocks overlapping by 75%, blocks quieter than -70 LUFS and
+/// than 10 LU below the mean of the remaining blocks are left out. Every channel is weighted the same
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let loudness = media_information::integrated_loudness(InputMode::FilePath("hello.mp3".to_string())).unwrap();
+/// // Gain that brings the file to -16 LUFS
+/// let gain = -16.0 - loudness;
+/// ```
+pub fn integrated_loudness(file_input: InputMode) -> io::Result<f32> {
+    profiling::function_scope!();
+    let source = open_source(file_input)?;
+    let channels = source.channels().max(1) as usize;
+    let sample_rate = source.sample_rate().max(1) as f64;
+    let mut filters: Vec<[Biquad; 2]> = (0..channels)
+        .map(|_| {
+            [
+                Biquad::high_shelf(sample_rate),
+                Biquad::high_pass(sample_rate),
+            ]
+        })
+        .collect();
+    // Mean square of every 100ms, four of them make up a block
+    let quarter = (sample_rate / 10.0) as usize * channels;
+    let mut quarters = vec![];
+    let mut sum = 0.0;
+    for (index, sample) in source.enumerate() {
+        let [shelf, pass] = &mut filters[index % channels];
+        let weighted = pass.process(shelf.process(sample as f64));
+        sum += weighted * weighted;
+        if (index + 1) % quarter == 0 {
+            quarters.push(sum / (quarter / channels) as f64);
+            sum = 0.0;
+        }
+    }
+    let blocks: Vec<f64> = quarters
+        .windows(4)
+        .map(|window| window.iter().sum::<f64>() / 4.0)
+        .collect();
+    let loudness = |power: f64| -0.691 + 10.0 * power.log10();
+    let mean = |blocks: &[f64]| blocks.iter().sum::<f64>() / blocks.len() as f64;
+    let audible: Vec<f64> = blocks
+        .into_iter()
+        .filter(|power| loudness(*power) > -70.0)
+        .collect();
+    if audible.is_empty() {
+        return Ok(f32::NEG_INFINITY);
+    }
+    let relative_gate = loudness(mean(&audible)) - 10.0;
+    let gated: Vec<f64> = audible
+        .into_iter()
+        .filter(|power| loudness(*power) > relative_gate)
+        .collect();
+    Ok(loudness(mean(&gated)) as f32)
+}
+
+/// Second order filter of the K-weighting used by [`integrated_loudness`], coefficients as given by ITU-R BS.1770
+/// for any sample rate
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    state: [f64; 2],
+}
+
+impl Biquad {
+    /// Boosts the highs by about 4 dB to account for the acoustic effect of the head
+    fn high_shelf(sample_rate: f64) -> Self {
+        let k = (std::f64::consts::PI * 1681.974450955533 / sample_rate).tan();
+        let q = 0.7071752369554196;
+        let vh = 10.0_f64.powf(3.999843853973347 / 20.0);
+        let vb = vh.powf(0.4996667741545416);
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            state: [0.0; 2],
+        }
+    }
+
+    /// Removes the lows below about 38 Hz
+    fn high_pass(sample_rate: f64) -> Self {
+        let k = (std::f64::consts::PI * 38.13547087602444 / sample_rate).tan();
+        let q = 0.5003270373238773;
+        let a0 = 1.0 + k / q + k * k;
+        Self {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            state: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, input: f64) -> f64 {
+        let output = self.b[0] * input + self.state[0];
+        self.state[0] = self.b[1] * input - self.a[0] * output + self.state[1];
+        self.state[1] = self.b[2] * input - self.a[1] * output;
+        output
+    }
+}
+
+/// Computes a [`Spectrogram`] of the whole audio with up to ``columns`` slices of time and ``bins`` log spaced frequency bands
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, InputMode};
+///
+/// let spectrogram = media_information::get_spectrogram(InputMode::FilePath("hello.mp3".to_string()), 500, 64).unwrap();
+/// ```
+pub fn get_spectrogram(
+    file_input: InputMode,
+    columns: usize,
+    bins: usize,
+) -> io::Result<Spectrogram> {
+    profiling::function_scope!();
+    let source = open_source(file_input)?;
+    let channels = source.channels().max(1) as usize;
+    let sample_rate = source.sample_rate() as f32;
+    let bins = bins.max(1);
+
+    let fft = FftPlanner::new().plan_fft_forward(SPECTROGRAM_WINDOW);
+    let window: Vec<f32> = (0..SPECTROGRAM_WINDOW)
+        .map(|n| 0.5 - 0.5 * (2.0 * PI * n as f32 / SPECTROGRAM_WINDOW as f32).cos())
+        .collect();
+    let window_gain = window.iter().sum::<f32>() / 2.0;
+
+    // Each band spans the same ratio of frequencies, this matches how pitch is heard
+    let lowest = 30.0_f32;
+    let highest = (sample_rate / 2.0).min(16_000.0);
+    let hz_per_bin = sample_rate / SPECTROGRAM_WINDOW as f32;
+    let band_edges: Vec<usize> = (0..=bins)
+        .map(|band| {
+            let frequency = lowest * (highest / lowest).powf(band as f32 / bins as f32);
+            ((frequency / hz_per_bin) as usize).clamp(1, SPECTROGRAM_WINDOW / 2)
+        })
+        .collect();
+
+    let mut reducer = ColumnReducer::new(bins, columns);
+    let mut mono: Vec<f32> = Vec::with_capacity(SPECTROGRAM_WINDOW);
+    let mut buffer = vec![Complex::default(); SPECTROGRAM_WINDOW];
+    let mut column = vec![0.0; bins];
+    let mut frame_sum = 0.0;
+    for (index, sample) in source.enumerate() {
+        frame_sum += sample;
+        if (index + 1) % channels != 0 {
+            continue;
+        }
+        mono.push(frame_sum / channels as f32);
+        frame_sum = 0.0;
+        if mono.len() < SPECTROGRAM_WINDOW {
+            continue;
+        }
+
+        for ((value, sample), weight) in buffer.iter_mut().zip(&mono).zip(&window) {
+            *value = Complex::new(sample * weight, 0.0);
+        }
+        fft.process(&mut buffer);
+        for (band, value) in column.iter_mut().enumerate() {
+            let start = band_edges[band];
+            let end = band_edges[band + 1].max(start + 1);
+            let magnitude = buffer[start..end]
+                .iter()
+                .map(|bin| bin.norm())
+                .fold(0.0, f32::max);
+            let db = 20.0 * (magnitude / window_gain).max(1e-9).log10();
+            *value = ((db + 90.0) / 90.0).clamp(0.0, 1.0);
+        }
+        reducer.push(&column);
+        // Windows overlap by half
+        mono.drain(..SPECTROGRAM_WINDOW / 2);
+    }
+
+    let values = reducer.finish();
+    Ok(Spectrogram {
+        width: values.len() / bins,
+        height: bins,
+        values,
+    })
+}
+
+/// Decodes the audio between ``start`` and ``end`` and writes it to ``path``
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, ExportFormat, InputMode};
+/// use std::time::Duration;
+///
+/// media_information::export_clip(
+///     InputMode::FilePath("hello.mp3".to_string()),
+///     Duration::from_secs(5),
+///     Duration::from_secs(12),
+///     "clip.wav",
+///     ExportFormat::Wav,
+/// )
+/// .unwrap();
+/// ```
+/// This would write the 7 seconds starting at 00:05 to ``clip.wav``
+pub fn export_clip(
+    file_input: InputMode,
+    start: Duration,
+    end: Duration,
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> io::Result<()> {
+    write_audio(
+        open_source_at(file_input, start)?.take_duration(end.saturating_sub(start)),
+        path.as_ref(),
+        format,
+    )
+}
+
+/// Decodes the whole file and re-encodes it to ``path``, handy to turn any supported input into a standard format
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{media_information, ExportFormat, InputMode};
+///
+/// media_information::export_audio(
+///     InputMode::FilePath("hello.m4a".to_string()),
+///     "hello.flac",
+///     ExportFormat::Flac,
+/// )
+/// .unwrap();
+/// ```
+pub fn export_audio(
+    file_input: InputMode,
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> io::Result<()> {
+    write_audio(open_source(file_input)?, path.as_ref(), format)
+}
+
+pub(crate) fn write_audio(
+    source: impl Source<Item = f32>,
+    path: &Path,
+    format: ExportFormat,
+) -> io::Result<()> {
+    log::info!("Exporting {format:?} to {}", path.display());
+    let result = match format {
+        ExportFormat::Wav => write_wav(source, path),
+        ExportFormat::Flac => flac::write_flac(source, path),
+    };
+    if let Err(error) = &result {
+        log::error!("Exporting to {} failed: {error}", path.display());
+    }
+    result
+}
+
+/// Converts a sample to 16 bits, clipping anything outside of -1.0 to 1.0
+pub(crate) fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16
+}
+
+fn write_wav(source: impl Source<Item = f32>, path: &Path) -> io::Result<()> {
+    let spec = hound::WavSpec {
+        channels: source.channels(),
+        sample_rate: source.sample_rate(),
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(hound_error)?;
+    for sample in source {
+        writer.write_sample(to_i16(sample)).map_err(hound_error)?;
+    }
+    writer.finalize().map_err(hound_error)
+}
+
+fn hound_error(error: hound::Error) -> io::Error {
+    match error {
+        hound::Error::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript(words: &[&str]) -> Vec<TranscriptionData> {
+        words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| TranscriptionData {
+                text: format!(" {word}"),
+                time: Duration::from_secs(index as u64),
+                end: Duration::from_secs(index as u64 + 1),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn missing_words_are_deletions() {
+        let report = compare_transcripts(&transcript(&["Hello", "world"]), "Hello, wide world!");
+        assert_eq!(
+            report.alignment,
+            vec![
+                AlignedWord::Match("hello".to_string()),
+                AlignedWord::Deletion("wide".to_string()),
+                AlignedWord::Match("world".to_string()),
+            ]
+        );
+        assert_eq!(report.deletions, 1);
+        assert_eq!(report.word_error_rate(), 1.0 / 3.0);
+    }
+
+    #[test]
+    fn substitutions_and_insertions_are_counted() {
+        let report =
+            compare_transcripts(&transcript(&["the", "cat", "sat", "down"]), "The bat sat.");
+        assert_eq!(
+            report,
+            WerReport {
+                substitutions: 1,
+                insertions: 1,
+                deletions: 0,
+                reference_words: 3,
+                alignment: vec![
+                    AlignedWord::Match("the".to_string()),
+                    AlignedWord::Substitution {
+                        generated: "cat".to_string(),
+                        reference: "bat".to_string(),
+                    },
+                    AlignedWord::Match("sat".to_string()),
+                    AlignedWord::Insertion("down".to_string()),
+                ],
+            }
+        );
+        assert_eq!(report.word_error_rate(), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn empty_transcripts_compare() {
+        assert_eq!(compare_transcripts(&[], "").word_error_rate(), 0.0);
+        assert_eq!(compare_transcripts(&[], "two words").deletions, 2);
+        assert_eq!(
+            compare_transcripts(&transcript(&["extra"]), "").word_error_rate(),
+            1.0
+        );
     }
-    transcript
 }
@@ -0,0 +1,68 @@
+use futures_util::future::BoxFuture;
+use std::time::Duration;
+
+use crate::{CoverArt, InputMode, TrackInfo, TranscriptionData};
+
+/// What a player knows about its file, handed to a [`MetadataProvider`] to look the file up with
+///
+/// ``info``: Title, artist and album from the tags of the file, see [`crate::media_information::get_track_info`]
+#[derive(Debug, Clone)]
+pub struct TrackQuery {
+    pub file_input: InputMode,
+    pub duration: Duration,
+    pub info: TrackInfo,
+}
+
+/// Everything a [`MetadataProvider`] found, fields left at ``None`` keep what the player already shows
+///
+/// ``info``: Shown above the control bar
+///
+/// ``lyrics``: Synced lyrics, shown with [`crate::CaptionSource::Lyrics`]
+///
+/// ``artwork``: Shown as the poster, unless one was set with [`crate::player::Player::set_poster`]
+#[derive(Debug, Clone, Default)]
+pub struct Metadata {
+    pub info: Option<TrackInfo>,
+    pub lyrics: Option<Vec<TranscriptionData>>,
+    pub artwork: Option<CoverArt>,
+}
+
+/// Looks up lyrics, artwork and track info for the file of a [`crate::player::Player`], set with
+/// [`crate::player::Player::set_metadata_provider`]
+///
+/// The crate does not talk to any service on its own. Implement this to ask your own backend or an online database,
+/// the lookup runs on the tokio runtime so it can take as long as it needs
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::metadata::{Metadata, MetadataProvider, TrackQuery};
+/// use egui_player::player::Player;
+/// use egui_player::TrackInfo;
+/// use futures_util::future::BoxFuture;
+///
+/// struct Catalogue;
+///
+/// impl MetadataProvider for Catalogue {
+///     fn fetch(&self, query: TrackQuery) -> BoxFuture<'_, Metadata> {
+///         Box::pin(async move {
+///             // Ask your service about query.info or query.file_input
+///             Metadata {
+///                 info: Some(TrackInfo {
+///                     title: Some("Dreamweaver".to_string()),
+///                     ..query.info
+///                 }),
+///                 ..Default::default()
+///             }
+///         })
+///     }
+/// }
+///
+/// # async fn run() {
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_metadata_provider(Catalogue);
+/// # }
+/// ```
+pub trait MetadataProvider: Send + Sync {
+    fn fetch(&self, query: TrackQuery) -> BoxFuture<'_, Metadata>;
+}
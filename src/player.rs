@@ -1,31 +1,343 @@
 use core::panic;
-use eframe::egui::{Label, Response, ScrollArea, Sense, Slider, Ui, Vec2};
+use eframe::egui::{
+    ecolor::Hsva, layers::ShapeIdx, pos2, Align, Align2, Color32, ColorImage, DragValue, FontId,
+    Grid, Image, ImageSource, Label, Layout, Mesh, Painter, Pos2, Rect, Response, RichText,
+    ScrollArea, Sense, Shape, Slider, Stroke, TextStyle, TextureHandle, TextureOptions, Ui,
+    UiBuilder, Vec2, ViewportCommand, WidgetInfo, WidgetType,
+};
 use infer;
-use rodio::{Decoder, OutputStream, Sink};
+use rodio::{
+    source::{Done, Empty},
+    Source,
+};
 use std::{
-    fs::File,
-    io::{BufReader, Cursor},
+    fmt, fs, io, mem,
+    ops::Range,
+    path::{Path, PathBuf},
     sync::{
-        atomic::{AtomicBool, AtomicI32, Ordering},
-        Arc,
+        atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering},
+        mpsc::{self, Receiver},
+        Arc, Mutex, MutexGuard, OnceLock,
     },
     thread::{self},
     time::{Duration, Instant},
 };
+use tokio::sync::oneshot::error::TryRecvError;
 
 use crate::{
-    media_information, InputMode, MediaType, ModelPath, TranscriptionData, TranscriptionProgress,
-    TranscriptionSettings,
+    analysis_cache::AnalysisCache,
+    audio_context::{AudioContext, SoloGuard},
+    clock::{Clock, SystemClock},
+    controls::ControlsConfig,
+    cue::CueSheet,
+    decoder::SkippingDecoder,
+    dsp::{AudioTap, DspSettings, EffectsStage, ReadAhead, TimeStretch},
+    effects::EffectsChain,
+    key_bindings::{KeyBindings, PlayerAction},
+    lyrics, media_information,
+    metadata::{Metadata, MetadataProvider, TrackQuery},
+    preferences::{FilePreferences, PreferenceStore},
+    sync_group::SyncRequest,
+    timeline::{Timeline, TimelineContent},
+    transcriber::{PlaybackPosition, Transcriber, TranscriptionCheckpoint, WhisperTranscriber},
+    transcript::{
+        self, SubtitleFormat, SubtitleOptions, TranscriptFilter, TranscriptLayout,
+        TranscriptNormalization, TranscriptStyle,
+    },
+    visualizer::{Oscilloscope, Visualizer},
+    worker_pool, CaptionSource, ChannelLevel, Chapter, CoverArt, DecodingOptions, ExportFormat,
+    Icons, InputMode, LocalModel, Marker, MediaType, ModelPath, OverlayState, PlaybackStats,
+    PlayerDefaults, PlayerLocale, RepaintPolicy, SeekBarBackground, SeekBehavior, SkipSegment,
+    Spectrogram, SpeedMode, TimeFormat, TrackInfo, TranscriptionData, TranscriptionMode,
+    TranscriptionProgress, TranscriptionResources, TranscriptionSettings, Visualization,
+    WhisperModel,
 };
 
+/// Speeds offered in the speed menu of the control bar
+const SPEED_PRESETS: [f32; 7] = [0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// How long a player whose audio output failed waits before trying again, see [`Player::set_audio_retry`]
+const AUDIO_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the audio thread of a player applies the volume and checks on its output
+const AUDIO_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long the settings have to stay the same before they are written to the [`PreferenceStore`]
+const PREFERENCE_SAVE_DELAY: Duration = Duration::from_secs(1);
+
+/// How far a swipe across the player seeks in [`ControlsConfig::touch_mode`]
+const SWIPE_SEEK: Duration = Duration::from_secs(10);
+
+/// Horizontal distance in points a drag has to cover to count as a swipe
+const SWIPE_DISTANCE: f32 = 60.0;
+
+/// Furthest captions can be moved with [`Player::set_caption_offset`], in milliseconds either way
+const MAX_CAPTION_OFFSET: i64 = 10_000;
+
+/// How long the clip indicator of the level meters stays lit after the output clipped
+const CLIP_HOLD: Duration = Duration::from_secs(2);
+
+/// How long the message about a skipped segment stays up, the last part of it fading out
+const SKIP_TOAST_DURATION: Duration = Duration::from_secs(2);
+
+/// Distance in points a drag has to cover before [`ControlsConfig::gestures`] decides between scrubbing and volume
+const GESTURE_THRESHOLD: f32 = 12.0;
+
+/// Vertical distance in points of a volume gesture that changes the volume by one percent
+const GESTURE_VOLUME_DRAG: f32 = 2.0;
+
+/// Height of the poster at a player scale of 1.0, see [`Player::set_poster`]
+const POSTER_HEIGHT: f32 = 160.0;
+
+/// Amount of peaks computed for the waveform, enough for the seek bar and a zoomed in [`Timeline`]
+const WAVEFORM_COLUMNS: usize = 8192;
+
+/// Amount of time slices computed for the seek bar spectrogram
+const SPECTROGRAM_COLUMNS: usize = 1000;
+
+/// Amount of frequency bands in the seek bar spectrogram
+const SPECTROGRAM_BINS: usize = 64;
+
+/// Result of the background analysis needed to draw a [`SeekBarBackground`]
+#[derive(Debug)]
+enum SeekBarAnalysis {
+    Waveform(Vec<f32>),
+    Spectrogram(Spectrogram),
+}
+
+/// Keeps a texture alive while being printable, [`TextureHandle`] does not implement [`fmt::Debug`]
+struct CachedTexture(TextureHandle);
+
+impl fmt::Debug for CachedTexture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CachedTexture").field(&self.0.id()).finish()
+    }
+}
+
+/// Closure that adds host provided entries to the context menu, see [`Player::context_menu`]
+type ContextMenuContents = dyn FnMut(&mut Ui, &mut Player) + Send;
+
+struct ContextMenu(Box<ContextMenuContents>);
+
+/// Closure that paints over the waveform, see [`Player::set_overlay`]
+type OverlayPainter = dyn FnMut(&Painter, Rect, &OverlayState) + Send;
+
+struct Overlay(Box<OverlayPainter>);
+
+impl fmt::Debug for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Overlay")
+    }
+}
+
+/// Store given to [`Player::set_preference_store`] with the hash of the file and what it remembered for it,
+/// sent back once the file was hashed on the [`worker_pool`]
+type LoadedPreferences = (
+    Box<dyn PreferenceStore>,
+    Option<String>,
+    Option<FilePreferences>,
+);
+
+/// Closure run once the end of the media is reached, see [`OnEnd::Callback`]
+type EndCallback = dyn FnMut(&mut Player) + Send;
+
+/// What a [`Player`] does once the end of the media is reached, set with [`Player::set_on_end`]
+///
+/// ``Stop``: Stays at the end, the play button restarts playback
+///
+/// ``Repeat``: Starts again from the beginning, same as [`Player::set_looping`]
+///
+/// ``Advance``: Stays at the end and lets the [`crate::playlist::Playlist`] holding the player move on to its next entry
+///
+/// ``Callback``: Stays at the end and runs the closure with the player, which can seek, load other media or play again
+///
+/// Whichever it is, it happens exactly once each time playback reaches the end
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::{OnEnd, Player};
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_on_end(OnEnd::Callback(Box::new(|player| {
+///     println!("Finished after {:?}", player.elapsed_time);
+/// })));
+/// ```
+#[derive(Default)]
+pub enum OnEnd {
+    #[default]
+    Stop,
+    Repeat,
+    Advance,
+    Callback(Box<EndCallback>),
+}
+
+impl fmt::Debug for OnEnd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnEnd::Stop => f.write_str("Stop"),
+            OnEnd::Repeat => f.write_str("Repeat"),
+            OnEnd::Advance => f.write_str("Advance"),
+            OnEnd::Callback(_) => f.write_str("Callback"),
+        }
+    }
+}
+
+/// Closure told about every change of [`PlayerState`], see [`Player::on_state_change`]
+type StateChange = dyn FnMut(PlayerState, PlayerState) + Send;
+
+struct StateHook(Box<StateChange>);
+
+impl fmt::Debug for StateHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("StateHook")
+    }
+}
+
+/// Something that happened while playing, given to the hook of [`Player::on_event`]
+///
+/// ``DecodeWarning``: A corrupt part of the file at ``position`` was skipped, playback went on after it
+///
+/// ``DecodeFailed``: The file could not be decoded any further from ``position``, so playback ended there
+#[derive(Debug, Clone, PartialEq)]
+pub enum PlayerEvent {
+    DecodeWarning { position: Duration, message: String },
+    DecodeFailed { position: Duration, message: String },
+}
+
+/// Closure told about every [`PlayerEvent`], see [`Player::on_event`]
+type EventHandler = dyn FnMut(&PlayerEvent) + Send;
+
+struct EventHook(Box<EventHandler>);
+
+impl fmt::Debug for EventHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventHook")
+    }
+}
+
+/// Drag across the player that [`ControlsConfig::gestures`] is following
+///
+/// ``Scrub``: Seeks to ``to`` once let go, the position was ``from`` when the drag started
+///
+/// ``Volume``: Changes the volume while dragging, it was ``from`` when the drag started
+#[derive(Debug, Copy, Clone)]
+enum SurfaceGesture {
+    Scrub { from: Duration, to: Duration },
+    Volume { from: i32 },
+}
+
+impl fmt::Debug for ContextMenu {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ContextMenu")
+    }
+}
+
+/// Everything needed to build the audio of a [`Player`], so decoding can start on the audio thread
+struct PlaybackSource {
+    file_input: InputMode,
+    start_at: Duration,
+    dsp_settings: Arc<DspSettings>,
+    effects: Arc<Mutex<EffectsChain>>,
+    audio_tap: Arc<AudioTap>,
+    /// Brought down to zero once the decoder runs out of audio
+    audio_left: Arc<AtomicUsize>,
+    read_ahead: Duration,
+}
+
+impl PlaybackSource {
+    fn build(self) -> impl Source<Item = f32> + Send {
+        // The decoder seeks before anything reads from it, so the stages after it start out empty
+        let mut decoded: Box<dyn Source<Item = f32> + Send> =
+            match SkippingDecoder::open(self.file_input, Some(Arc::clone(&self.audio_tap))) {
+                Ok(decoder) => Box::new(decoder),
+                Err(error) => {
+                    // Ends the stream right away, the same as a file that is over
+                    log::error!("Could not open the file for playback: {error}");
+                    self.audio_tap
+                        .record_decode_failure(Duration::ZERO, error.to_string());
+                    Box::new(Empty::<f32>::new())
+                }
+            };
+        if let Err(error) = decoded.try_seek(self.start_at) {
+            log::warn!(
+                "Could not seek to {:?}, playing from the start: {error}",
+                self.start_at
+            );
+            self.audio_tap.record_decode_error();
+        }
+        if let Ok(mut effects) = self.effects.lock() {
+            effects.reset();
+        }
+        let source = EffectsStage::new(
+            TimeStretch::new(
+                ReadAhead::new(decoded, self.read_ahead, Arc::clone(&self.audio_tap)),
+                Arc::clone(&self.dsp_settings),
+            ),
+            self.effects,
+            self.dsp_settings,
+            Arc::clone(&self.audio_tap),
+        );
+        Done::new(source, self.audio_left)
+    }
+}
+
+/// Audio of a [`crate::sync_group::SyncGroup`] member, follows the volume, mute and solo of the player and ends once the player is paused
+pub(crate) struct SyncedSource {
+    playback_source: PlaybackSource,
+    volume: Arc<AtomicI32>,
+    muted: Arc<AtomicBool>,
+    solo: Arc<AtomicBool>,
+    audio_context: AudioContext,
+    stop_audio: Arc<AtomicBool>,
+}
+
+impl SyncedSource {
+    pub(crate) fn build(self) -> impl Source<Item = f32> + Send {
+        let Self {
+            playback_source,
+            volume,
+            muted,
+            solo,
+            audio_context,
+            stop_audio,
+        } = self;
+        let dsp_settings = Arc::clone(&playback_source.dsp_settings);
+        playback_source
+            .build()
+            .amplify(1.0)
+            .stoppable()
+            .periodic_access(Duration::from_millis(5), move |source| {
+                let audible = audio_context
+                    .is_audible(muted.load(Ordering::Acquire), solo.load(Ordering::Acquire));
+                let factor = if audible {
+                    volume.load(Ordering::Acquire) as f32 / 100.0
+                } else {
+                    0.0
+                };
+                if audible {
+                    dsp_settings.set_output_volume(factor);
+                }
+                source.inner_mut().set_factor(factor);
+                if stop_audio.load(Ordering::Relaxed) {
+                    source.stop();
+                }
+            })
+    }
+}
+
 /// Reflects the current form of the [`Player`]
 ///
 /// Playing: The Player
+///
+/// ``Error``: The audio output stopped working, for example because the device was unplugged. The position is kept
+/// and playback starts again on the new default device, see [`Player::set_audio_retry`]
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PlayerState {
     Playing,
     Paused,
     Ended,
+    Error,
 }
 
 /// Holds relevant info to run the player
@@ -42,22 +354,154 @@ pub struct Player {
 
     /// Info related to control bar
     pub elapsed_time: Duration,
+    /// Probed on the worker pool for audio files, zero until the [`Player::update`] after it is known
     pub total_time: Duration,
 
     /// Playback information
     playback_guard: bool,
     start_playback: bool,
     stop_playback: Arc<AtomicBool>,
+    /// Zero once the audio of the current playback has all been played, see [`PlaybackSource`]
+    audio_left: Arc<AtomicUsize>,
+    /// Set by the audio thread once the output stops working
+    audio_failed: Arc<AtomicBool>,
+    /// Set by the audio thread once the output moved to another device
+    audio_moved: Arc<AtomicBool>,
+    audio_retry: bool,
+    /// When the audio output last failed, retries wait a while after it
+    audio_failed_at: Option<Instant>,
     stopwatch_instant: Option<Instant>,
     pub start_time: Duration,
 
     /// Audio related info
     pub volume: Arc<AtomicI32>,
+    dsp_settings: Arc<DspSettings>,
+    effects: Arc<Mutex<EffectsChain>>,
+    audio_tap: Arc<AudioTap>,
+    show_level_meters: bool,
+    ambient: bool,
+    read_ahead: Duration,
+    trim_silence: bool,
+    /// Start and end of the audible part of the file, see [`Player::content_bounds`]
+    content_bounds: Option<(Duration, Duration)>,
+    bounds_receiver: Option<Receiver<(Duration, Duration)>>,
+    /// Length and chapters of the file being read on the [`worker_pool`]
+    media_receiver: Option<Receiver<(Duration, Vec<Chapter>)>>,
+    show_countdown: bool,
+    show_debug_panel: bool,
+    repaint_policy: RepaintPolicy,
+    volume_scroll_step: i32,
+    /// Scrolled distance over the volume icon that has not added up to a step yet
+    volume_scroll: f32,
+    levels: Vec<ChannelLevel>,
+    /// When the output last reached full scale, lights the clip indicator of the level meters
+    clipped_at: Option<Instant>,
+    visualizer: Option<Box<dyn Visualizer>>,
+    tap_cursor: u64,
+    seek_bar_background: SeekBarBackground,
+    waveform_peaks: Option<Vec<f32>>,
+    spectrogram: Option<Spectrogram>,
+    poster: Option<ImageSource<'static>>,
+    /// Whether the cover art of the file has been looked for, see [`Player::set_poster`]
+    poster_loaded: bool,
+    /// Whether the poster was set with [`Player::set_poster`], which artwork of a [`MetadataProvider`] does not replace
+    custom_poster: bool,
+    /// Whether the player has been played at all, the poster is only shown until then
+    has_played: bool,
+    artwork_placeholder: bool,
+    /// Background of the artwork placeholder, derived from the hash of the file so every file keeps its own color
+    placeholder_color: Option<Color32>,
+    spectrogram_texture: Option<CachedTexture>,
+    analysis_receiver: Option<Receiver<SeekBarAnalysis>>,
+    /// Whether the file could not be analysed for the [`SeekBarBackground`], so it is not tried again every frame
+    analysis_failed: bool,
+    /// Cover art or placeholder color being looked for on the [`worker_pool`]
+    poster_receiver: Option<Receiver<(Option<ImageSource<'static>>, Option<Color32>)>>,
+    analysis_cache: Option<AnalysisCache>,
+    timeline: Timeline,
+    timeline_shown: bool,
+    pub markers: Vec<Marker>,
+    show_chapters: bool,
+    /// Pictures of chapters read from the file, by the start of their chapter
+    chapter_images: Vec<(Duration, ImageSource<'static>)>,
+    skip_segments: Vec<SkipSegment>,
+    /// Message about the last skipped segment and when it was skipped
+    skip_toast: Option<(String, Instant)>,
+    audio_context: Option<AudioContext>,
+    muted: Arc<AtomicBool>,
+    solo: Arc<AtomicBool>,
+    solo_guard: Option<SoloGuard>,
+    show_mix_controls: bool,
+    preference_store: Option<Box<dyn PreferenceStore>>,
+    preference_receiver: Option<Receiver<LoadedPreferences>>,
+    /// Hash of ``file_input``, computed the first time it is needed, see [`Player::content_hash`]
+    content_hash: OnceLock<String>,
+    saved_preferences: Option<FilePreferences>,
+    /// Settings that differ from the saved ones and when they were last changed, see [`PREFERENCE_SAVE_DELAY`]
+    unsaved_preferences: Option<(FilePreferences, Instant)>,
+    /// Volume the player had when the preference store was set, which ``volume_offset`` is relative to
+    preference_base_volume: i32,
+    play_time: Duration,
+    play_started: Option<Instant>,
+    clock: Arc<dyn Clock>,
+    seeks: u64,
+    /// Set while the player is part of a [`crate::sync_group::SyncGroup`], which then owns its audio
+    pub(crate) sync_member: bool,
+    /// Controls used on a [`crate::sync_group::SyncGroup`] member, applied to every member by the group
+    pub(crate) sync_requests: Vec<SyncRequest>,
     transcription_settings: TranscriptionSettings,
     pub transcript: Vec<TranscriptionData>,
+    transcript_filter: Option<TranscriptFilter>,
+    transcript_normalization: TranscriptNormalization,
+    transcript_seek_behavior: SeekBehavior,
+    transcription_mode: TranscriptionMode,
+    transcription_resources: TranscriptionResources,
+    decoding_options: DecodingOptions,
+    whisper_model: WhisperModel,
+    /// Where a resumed transcription carries on from
+    transcription_resume_from: Duration,
+    /// Shared with a transcriber following playback
+    transcription_position: PlaybackPosition,
+    transcript_style: TranscriptStyle,
+    /// Set once [`Player::transcript_ui`] shows the transcript somewhere else
+    transcript_detached: bool,
+    /// Rows of the shown transcript, see [`TranscriptLayout`]
+    transcript_layout: Option<TranscriptLayout>,
+    caption_source: CaptionSource,
+    /// Milliseconds every caption is shown later, earlier when negative
+    caption_offset: i64,
+    lyrics: Vec<TranscriptionData>,
+    /// Whether the file has been looked at for embedded lyrics, see [`Player::set_caption_source`]
+    lyrics_loaded: bool,
+    /// Embedded lyrics being read on the [`worker_pool`]
+    lyrics_receiver: Option<Receiver<Vec<TranscriptionData>>>,
+    /// Time spanned by the words selected in the transcript
+    transcript_selection: Option<(Duration, Duration)>,
+    /// Span of the word a selection is being dragged from
+    transcript_drag_anchor: Option<(Duration, Duration)>,
     pub model_path: ModelPath,
+    transcriber: Option<Arc<dyn Transcriber>>,
+    icons: Icons,
+    locale: PlayerLocale,
+    time_format: TimeFormat,
+    key_bindings: KeyBindings,
+    context_menu: Option<ContextMenu>,
+    controls: ControlsConfig,
+    overlay: Option<Overlay>,
+    on_end: OnEnd,
+    /// Distance covered by the current drag across the player, see [`ControlsConfig::touch_mode`]
+    surface_drag: Vec2,
+    surface_gesture: Option<SurfaceGesture>,
+    state_hook: Option<StateHook>,
+    event_hook: Option<EventHook>,
+    /// State last given to the hook of [`Player::on_state_change`]
+    reported_state: PlayerState,
+    /// A and B points set by right clicking the seek bar, playback loops between them once both are set
+    ab_points: (Option<Duration>, Option<Duration>),
     pub transcription_progress: TranscriptionProgress,
     transcript_receiver: Option<tokio::sync::mpsc::UnboundedReceiver<TranscriptionProgress>>,
+    metadata_receiver: Option<tokio::sync::oneshot::Receiver<Metadata>>,
+    track_info: Option<TrackInfo>,
 }
 
 impl Player {
@@ -87,44 +531,201 @@ impl Player {
     ///
     /// Look at the *[README](https://github.com/AravDesai/egui-player/blob/master/README.md)* to have a more in depth approach to adding a [`Player`] to your egui project
     /// Or look at the example in examples/main.rs
-    pub fn from_bytes(bytes: Vec<u8>) -> Self {
-        Self::new(InputMode::Bytes(bytes))
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>) -> Self {
+        Self::new(InputMode::Bytes(bytes.into()))
+    }
+
+    /// Plays the file at ``url`` while it downloads, see [`InputMode::Url`]
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::Player;
+    ///
+    /// let player = Player::from_url("https://example.com/episode.mp3");
+    /// ```
+    #[cfg(feature = "streaming")]
+    pub fn from_url(url: &str) -> Self {
+        Self::new(InputMode::Url(url.to_string()))
+    }
+
+    /// Opens the file of the CUE sheet at ``path`` with every track of the sheet as a chapter, see [`Player::set_cue_sheet`]
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::Player;
+    ///
+    /// let player = Player::from_cue("album.cue").unwrap();
+    /// ```
+    pub fn from_cue(path: impl AsRef<Path>) -> io::Result<Self> {
+        let sheet = CueSheet::open(path)?;
+        let file = sheet.file.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidData, "The CUE sheet names no file")
+        })?;
+        let mut player = Self::from_path(&file.to_string_lossy());
+        player.set_cue_sheet(&sheet);
+        Ok(player)
     }
 
     /// Accepts
     fn new(file: InputMode) -> Self {
         // gets relevant information that can only be taken from the filepath
-        let media_type = match file.clone() {
-            InputMode::FilePath(file_path) => media_information::get_media_type(&file_path),
+        let media_type = match &file {
+            InputMode::FilePath(file_path) => media_information::get_media_type(file_path),
             InputMode::Bytes(bytes) => {
-                if let Some(kind) = infer::get(&bytes) {
+                if let Some(kind) = infer::get(bytes) {
                     media_information::get_media_type(kind.extension())
                 } else {
                     panic!("Invalid File")
                 }
             }
+            // Video is not decoded yet, so whatever is at the URL is played as audio
+            #[cfg(feature = "streaming")]
+            InputMode::Url(_) => MediaType::Audio,
+        };
+        let defaults = PlayerDefaults::global();
+        // Probing the length and reading the chapters of audio can take a while, so it is done on the worker pool
+        let (total_time, media_receiver) = match media_type {
+            MediaType::Audio => {
+                let file_input = file.clone();
+                let (tx_media, rx_media) = mpsc::channel();
+                worker_pool::spawn(move || {
+                    let total_time =
+                        media_information::get_total_time(media_type, file_input.clone());
+                    let _ =
+                        tx_media.send((total_time, media_information::get_chapters(file_input)));
+                });
+                (Duration::ZERO, Some(rx_media))
+            }
+            _ => (
+                media_information::get_total_time(media_type, file.clone()),
+                None,
+            ),
         };
-        Self {
+        let mut player = Self {
             media_type,
             player_size: Vec2::default(),
             player_state: PlayerState::Paused,
             elapsed_time: Duration::ZERO,
-            total_time: media_information::get_total_time(media_type, file.clone()),
+            total_time,
             player_scale: 1.0,
             playback_guard: false,
             stop_playback: Arc::new(AtomicBool::new(false)),
+            audio_left: Arc::new(AtomicUsize::new(1)),
+            audio_failed: Arc::new(AtomicBool::new(false)),
+            audio_moved: Arc::new(AtomicBool::new(false)),
+            audio_retry: true,
+            audio_failed_at: None,
             file_input: file,
 
             start_playback: false,
             stopwatch_instant: None,
             start_time: Duration::ZERO,
-            volume: Arc::new(AtomicI32::new(100)),
+            volume: Arc::new(AtomicI32::new(defaults.volume.clamp(0, 100))),
+            dsp_settings: Arc::new(DspSettings::new()),
+            effects: Arc::new(Mutex::new(EffectsChain::default())),
+            audio_tap: Arc::new(AudioTap::default()),
+            show_level_meters: false,
+            ambient: false,
+            show_countdown: false,
+            show_debug_panel: false,
+            repaint_policy: RepaintPolicy::Adaptive,
+            volume_scroll_step: 5,
+            volume_scroll: 0.0,
+            levels: vec![],
+            clipped_at: None,
+            visualizer: None,
+            tap_cursor: 0,
+            seek_bar_background: SeekBarBackground::None,
+            waveform_peaks: None,
+            spectrogram: None,
+            spectrogram_texture: None,
+            analysis_receiver: None,
+            analysis_failed: false,
+            poster_receiver: None,
+            read_ahead: Duration::ZERO,
+            trim_silence: false,
+            content_bounds: None,
+            bounds_receiver: None,
+            media_receiver,
+            analysis_cache: None,
+            timeline: Timeline::default(),
+            timeline_shown: false,
+            markers: vec![],
+            show_chapters: false,
+            chapter_images: vec![],
+            skip_segments: vec![],
+            skip_toast: None,
+            audio_context: None,
+            muted: Arc::new(AtomicBool::new(false)),
+            solo: Arc::new(AtomicBool::new(false)),
+            solo_guard: None,
+            show_mix_controls: false,
+            preference_store: None,
+            preference_receiver: None,
+            content_hash: OnceLock::new(),
+            saved_preferences: None,
+            unsaved_preferences: None,
+            preference_base_volume: 100,
+            play_time: Duration::ZERO,
+            play_started: None,
+            clock: Arc::new(SystemClock),
+            seeks: 0,
+            sync_member: false,
+            sync_requests: vec![],
             transcript: vec![],
+            transcript_filter: None,
+            transcript_normalization: TranscriptNormalization::default(),
+            transcript_seek_behavior: SeekBehavior::default(),
+            transcription_mode: TranscriptionMode::default(),
+            transcription_resources: TranscriptionResources::default(),
+            decoding_options: DecodingOptions::default(),
+            whisper_model: WhisperModel::default(),
+            transcription_resume_from: Duration::ZERO,
+            transcription_position: PlaybackPosition::default(),
+            transcript_style: TranscriptStyle::default(),
+            transcript_detached: false,
+            transcript_layout: None,
+            caption_source: CaptionSource::default(),
+            caption_offset: 0,
+            lyrics: vec![],
+            lyrics_loaded: false,
+            lyrics_receiver: None,
+            transcript_selection: None,
+            transcript_drag_anchor: None,
             transcript_receiver: None,
-            transcription_settings: TranscriptionSettings::None,
+            metadata_receiver: None,
+            track_info: None,
+            transcription_settings: defaults.transcription_settings,
             transcription_progress: TranscriptionProgress::NoProgress,
-            model_path: ModelPath::Default,
-        }
+            model_path: defaults.model_path,
+            transcriber: None,
+            icons: defaults.icons,
+            locale: defaults.locale,
+            time_format: defaults.time_format,
+            key_bindings: KeyBindings::default(),
+            context_menu: None,
+            controls: ControlsConfig::default(),
+            overlay: None,
+            on_end: OnEnd::default(),
+            surface_drag: Vec2::ZERO,
+            poster: None,
+            poster_loaded: false,
+            custom_poster: false,
+            has_played: false,
+            artwork_placeholder: true,
+            placeholder_color: None,
+            surface_gesture: None,
+            state_hook: None,
+            event_hook: None,
+            reported_state: PlayerState::Paused,
+            ab_points: (None, None),
+        };
+        player
+            .dsp_settings
+            .set_speed(defaults.speed.clamp(0.25, 4.0));
+        player
     }
 
     /// Configure transcription settings by changing the [`TranscriptionSettings`] enum
@@ -132,249 +733,2744 @@ impl Player {
         self.transcription_settings = setting;
     }
 
-    /// Configure where model is downloaded
-    pub fn set_model_download_path(&mut self, file_path: String) {
-        self.model_path = ModelPath::Custom(file_path);
+    /// Picture shown above the controls before the player is first played and once it has ended
+    ///
+    /// Audio files show their embedded cover art when no poster is set, or the placeholder of [`Player::set_artwork_placeholder`].
+    /// Anything but textures needs the image loaders of egui to be installed, for example with ``egui_extras::install_image_loaders``
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::Player;
+    ///
+    /// let mut player = Player::from_path("episode.mp3");
+    /// player.set_poster("file://poster.png");
+    /// ```
+    pub fn set_poster(&mut self, poster: impl Into<ImageSource<'static>>) {
+        self.poster = Some(poster.into());
+        self.poster_loaded = true;
+        self.custom_poster = true;
     }
 
-    /// Allows you to rescale the player ``(Note: Currently non-functional)``
-    pub fn set_player_scale(&mut self, scale: f32) {
-        self.player_scale = scale;
-        if self.player_size.eq(&Vec2::default()) {
-            match self.media_type {
-                MediaType::Audio => {
-                    self.player_size = Vec2 { x: 50.0, y: 10.0 } * self.player_scale
-                }
-                MediaType::Video => self.player_size = Vec2 { x: 0.0, y: 0.0 } * self.player_scale,
-                MediaType::Error => panic!("No size since it is an unsupported type"),
-            }
-        } else {
-            self.player_size *= self.player_scale;
-        }
+    /// Shows a music note on a color picked from the content of the file in place of the poster of audio files
+    /// that have no cover art, on by default
+    pub fn set_artwork_placeholder(&mut self, show: bool) {
+        self.artwork_placeholder = show;
     }
 
-    /// Displays bar containing pause/play, video time, draggable bar and volume control
-    fn control_bar(&mut self, ui: &mut Ui) {
-        ui.horizontal(|ui| {
-            let pause_icon = match self.player_state {
-                PlayerState::Playing => "⏸",
-                PlayerState::Paused => "▶",
-                PlayerState::Ended => "↺",
-            };
-            if ui.button(pause_icon).clicked() {
-                match self.player_state {
-                    // Pausing the player
-                    PlayerState::Playing => {
-                        self.pause_player();
-                    }
-                    // Playing the player
-                    PlayerState::Paused => {
-                        self.play_player();
-                    }
-                    // Restarting the player
-                    PlayerState::Ended => {
-                        self.elapsed_time = Duration::ZERO;
-                        self.play_player();
-                    }
-                }
-            }
+    /// Masks words of the shown transcript with ``filter``, ``None`` shows the transcript as it was heard
+    ///
+    /// [`Player::transcript`] keeps the unfiltered words, use [`Player::display_transcript`] for what is shown
+    pub fn set_transcript_filter(&mut self, filter: Option<TranscriptFilter>) {
+        self.transcript_filter = filter;
+        self.transcript_layout = None;
+    }
 
-            if self.elapsed_time >= self.total_time {
-                self.pause_player();
-                self.player_state = PlayerState::Ended;
-            }
+    /// Changes whether the whole file is transcribed up front or just ahead of playback, see [`TranscriptionMode`]
+    pub fn set_transcription_mode(&mut self, mode: TranscriptionMode) {
+        self.transcription_mode = mode;
+    }
 
-            ui.label(
-                media_information::format_duration(self.elapsed_time)
-                    + " / "
-                    + &media_information::format_duration(self.total_time),
-            );
+    /// Changes the model of the built in Whisper transcription, see [`WhisperModel`]
+    ///
+    /// Has no effect on a transcriber set with [`Player::set_transcriber`]
+    pub fn set_whisper_model(&mut self, model: WhisperModel) {
+        self.whisper_model = model;
+    }
 
-            let mut slider_value = self.elapsed_time.as_secs_f32();
-            let slider = Slider::new(&mut slider_value, 0.0..=self.total_time.as_secs_f32())
-                .show_value(false);
-            let slider_response = ui.add(slider);
-            if slider_response.drag_started() {
-                self.player_state = PlayerState::Paused;
-                self.pause_player();
-            }
-            if slider_response.dragged() {
-                self.elapsed_time = Duration::from_secs_f32(slider_value);
-            }
+    /// Limits the threads and priority of the built in Whisper transcription, see [`TranscriptionResources`]
+    ///
+    /// Lowering the priority only works on Linux. Has no effect on a transcriber set with [`Player::set_transcriber`]
+    pub fn set_transcription_resources(&mut self, resources: TranscriptionResources) {
+        self.transcription_resources = resources;
+    }
 
-            let mut volume = self.volume.load(Ordering::Acquire);
+    /// Sets what the built in Whisper transcription leaves out of the transcript, see [`DecodingOptions`]
+    ///
+    /// Has no effect on a transcriber set with [`Player::set_transcriber`]
+    pub fn set_decoding_options(&mut self, decoding: DecodingOptions) {
+        self.decoding_options = decoding;
+    }
 
-            let volume_icon = if volume > 70 {
-                "🔊"
-            } else if volume > 40 {
-                "🔉"
-            } else if volume > 0 {
-                "🔈"
-            } else {
-                "🔇"
-            };
+    /// Changes whether clicking a word of the transcript pauses playback, see [`SeekBehavior`]
+    pub fn set_transcript_seek_behavior(&mut self, behavior: SeekBehavior) {
+        self.transcript_seek_behavior = behavior;
+    }
 
-            ui.menu_button(volume_icon, |ui| {
-                ui.add(Slider::new(&mut volume, 0..=100).vertical())
-            });
+    /// Changes the font size, spacing and height of the shown transcript, see [`TranscriptStyle`]
+    pub fn set_transcript_style(&mut self, style: TranscriptStyle) {
+        self.transcript_style = style;
+        self.transcript_layout = None;
+    }
 
-            self.volume.store(volume, Ordering::Relaxed);
+    /// Cleans up the shown transcript, see [`TranscriptNormalization`] for the options
+    pub fn set_transcript_normalization(&mut self, normalization: TranscriptNormalization) {
+        self.transcript_normalization = normalization;
+        self.transcript_layout = None;
+    }
 
-            let is_timestamped = matches!(
-                self.transcription_settings,
-                TranscriptionSettings::ShowTimeStamps
-            );
+    /// Transcript as it is shown by the player, normalized and with the [`TranscriptFilter`] applied. Use this when exporting the transcript
+    pub fn display_transcript(&self) -> Vec<TranscriptionData> {
+        let transcript = self.transcript_normalization.apply(&self.transcript);
+        match &self.transcript_filter {
+            Some(filter) => filter.apply(&transcript),
+            None => transcript,
+        }
+    }
 
-            match self.transcription_settings {
-                TranscriptionSettings::None => {}
-                TranscriptionSettings::Allow
-                | TranscriptionSettings::TranscriptLabel
-                | TranscriptionSettings::ShowTimeStamps => {
-                    ui.menu_button("…", |ui| {
-                        if ui.button("Transcribe audio").clicked()
-                            && self.transcript_receiver.is_none()
-                        {
-                            self.transcription_progress = TranscriptionProgress::Reading;
-                            let file_input = self.file_input.clone();
-                            let model_path = self.model_path.clone();
-                            let (tx_transcript, rx_transcript) =
-                                tokio::sync::mpsc::unbounded_channel();
-                            self.transcript_receiver = Some(rx_transcript);
-
-                            tokio::spawn(async move {
-                                let _ = media_information::transcribe_audio(
-                                    file_input,
-                                    is_timestamped,
-                                    Some(tx_transcript),
-                                    model_path,
-                                )
-                                .await;
-                            });
-                        }
-                    });
-                }
-            }
+    /// Picks between the transcript and synced lyrics for the transcript view, see [`CaptionSource`]
+    ///
+    /// Lyrics are shown with one line per row in the same view as the transcript, highlighted while they are sung and
+    /// seeking when clicked. They are shown whatever the [`TranscriptionSettings`] are, only [`TranscriptionSettings::ShowTimeStamps`]
+    /// adds their times. Without lyrics from [`Player::set_lyrics`] the lyrics embedded in the file are used
+    pub fn set_caption_source(&mut self, source: CaptionSource) {
+        self.caption_source = source;
+        self.transcript_layout = None;
+    }
 
-            if let Some(receiver) = &mut self.transcript_receiver {
-                if let Ok(progress) = receiver.try_recv() {
-                    self.transcription_progress = progress;
-                };
+    /// Time synced lyrics, one [`TranscriptionData`] for each line, such as those read by [`crate::lyrics::read_lrc`]
+    pub fn set_lyrics(&mut self, lyrics: Vec<TranscriptionData>) {
+        self.lyrics = lyrics;
+        // The last line has nothing after it to end at
+        if let Some(last) = self.lyrics.last_mut()
+            && last.end <= last.time
+        {
+            last.end = self.total_time.max(last.time);
+        }
+        self.lyrics_loaded = true;
+        self.transcript_layout = None;
+    }
 
-                match &self.transcription_progress {
-                    TranscriptionProgress::NoProgress => {}
-                    TranscriptionProgress::InProgress(transcription_data) => {
-                        if !self.transcript.contains(transcription_data) {
-                            self.transcript.push(transcription_data.clone());
-                        }
-                        ui.label("Transcription in Progress");
-                        ui.spinner();
-                    }
-                    TranscriptionProgress::Reading => {
-                        ui.label("Transcription in Progress");
-                        ui.spinner();
-                    }
-                    TranscriptionProgress::Finished => {
-                        self.transcript_receiver = None;
-                    }
-                };
-            }
-        });
+    pub fn lyrics(&self) -> &[TranscriptionData] {
+        &self.lyrics
+    }
 
-        match self.transcription_settings {
-            TranscriptionSettings::TranscriptLabel | TranscriptionSettings::ShowTimeStamps => {
-                if !self.transcript.is_empty() {
-                    ScrollArea::vertical().show(ui, |ui| {
-                        ui.horizontal_wrapped(|ui| {
-                            ui.style_mut().spacing.item_spacing.x = 0.0;
-                            for word in self.transcript.clone() {
-                                let response = ui.add(Label::new(word.text).sense(Sense::click()));
-                                if response.clicked() {
-                                    self.pause_player();
-                                    self.elapsed_time = word.time;
-                                }
-                            }
-                        });
-                        if self.transcription_progress == TranscriptionProgress::Finished {
-                            ui.label("--- END OF TRANSCRIPT ---");
-                        }
-                    });
-                }
-            }
-            _ => {}
-        }
+    /// Shows every caption of the transcript view ``offset_ms`` milliseconds later, or earlier when negative,
+    /// for lyrics or transcripts that are out of sync with the audio. Limited to 10 seconds either way
+    ///
+    /// Only the view is moved, the transcript itself and exported subtitles keep their times
+    pub fn set_caption_offset(&mut self, offset_ms: i64) {
+        self.caption_offset = offset_ms.clamp(-MAX_CAPTION_OFFSET, MAX_CAPTION_OFFSET);
+        self.transcript_layout = None;
     }
 
-    // TODO fix this eventually
-    fn display_player(&mut self, ui: &mut Ui) {
-        match self.media_type {
-            MediaType::Audio => self.control_bar(ui),
-            MediaType::Video => self.control_bar(ui),
-            MediaType::Error => panic!("Can't display due to invalid file type"),
-        }
+    pub fn caption_offset(&self) -> i64 {
+        self.caption_offset
     }
 
-    /// Audio playback
+    /// Entries of the transcript view for the [`CaptionSource`] along with the length of the source they come from
     ///
-    /// A stream to play audio is started. It is only stopped when the file reaches the end or the [`Player`] is paused
-    fn audio_stream(&mut self) {
-        if self.playback_guard {
-            let start_at = self.elapsed_time;
-            let file_input = self.file_input.clone();
-            let stop_audio = Arc::clone(&self.stop_playback);
-            let volume = Arc::clone(&self.volume);
-            thread::spawn(move || {
-                let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-                let sink: Sink = match file_input {
-                    InputMode::FilePath(file_path) => {
-                        let file = File::open(file_path).unwrap();
-                        stream_handle.play_once(BufReader::new(file)).unwrap()
-                    }
-                    InputMode::Bytes(bytes) => {
-                        let sound_data: Arc<[u8]> = Arc::from(bytes);
-                        let cursor = Cursor::new(Arc::clone(&sound_data));
-                        let try_sink = Sink::try_new(&stream_handle).unwrap();
-                        let source = Decoder::new(cursor).unwrap();
-                        try_sink.append(source);
-                        try_sink
-                    }
+    /// Entries are moved by the [`Player::caption_offset`]
+    fn caption_entries(&self) -> (Vec<TranscriptionData>, usize) {
+        let (mut entries, source_length) = match self.caption_source {
+            CaptionSource::Transcript => (self.display_transcript(), self.transcript.len()),
+            CaptionSource::Lyrics => {
+                let lyrics = match &self.transcript_filter {
+                    Some(filter) => filter.apply(&self.lyrics),
+                    None => self.lyrics.clone(),
                 };
-                sink.try_seek(start_at).unwrap();
-                loop {
-                    sink.set_volume(volume.load(Ordering::Acquire) as f32 / 100.0);
-                    if stop_audio.load(Ordering::Relaxed) {
-                        break;
-                    }
+                (lyrics, self.lyrics.len())
+            }
+        };
+        if self.caption_offset != 0 {
+            let offset = Duration::from_millis(self.caption_offset.unsigned_abs());
+            let shift = |time: Duration| {
+                if self.caption_offset > 0 {
+                    time + offset
+                } else {
+                    time.saturating_sub(offset)
                 }
-            });
+            };
+            for entry in &mut entries {
+                entry.time = shift(entry.time);
+                entry.end = shift(entry.end);
+            }
         }
+        (entries, source_length)
     }
 
-    /// Starts visual/ audio stream by redirecting to the correct function
-    fn start_stream(&mut self) {
-        match self.media_type {
-            MediaType::Audio => self.audio_stream(),
-            MediaType::Video => todo!(),
-            MediaType::Error => todo!(),
-        }
+    /// Writes the [`Player::display_transcript`] to ``path`` as subtitles, see [`transcript::subtitles`]
+    pub fn export_subtitles(
+        &self,
+        path: impl AsRef<Path>,
+        format: SubtitleFormat,
+        options: SubtitleOptions,
+    ) -> io::Result<()> {
+        let subtitles =
+            transcript::subtitles(&self.display_transcript(), self.total_time, format, options);
+        fs::write(path, subtitles)
     }
 
-    fn play_player(&mut self) {
-        self.player_state = PlayerState::Playing;
-        self.start_playback = true;
-        self.playback_guard = true;
-        self.stop_playback = Arc::new(AtomicBool::new(false));
-        self.start_stream();
+    /// Replaces the keyboard shortcuts of the player, see [`KeyBindings`] for the defaults
+    pub fn set_key_bindings(&mut self, key_bindings: KeyBindings) {
+        self.key_bindings = key_bindings;
     }
 
-    fn pause_player(&mut self) {
-        self.player_state = PlayerState::Paused;
-        self.start_playback = false;
-        self.stop_playback.swap(true, Ordering::Relaxed);
+    /// Adds entries to the menu shown when right clicking the player, below the built in loop, speed and copy timestamp entries
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::Player;
+    ///
+    /// let mut player = Player::from_path("hello.mp3");
+    /// player.context_menu(|ui, player| {
+    ///     if ui.button("Add marker").clicked() {
+    ///         player.add_marker(player.elapsed_time, "Marker");
+    ///         ui.close_menu();
+    ///     }
+    /// });
+    /// ```
+    pub fn context_menu(
+        &mut self,
+        add_contents: impl FnMut(&mut Ui, &mut Player) + Send + 'static,
+    ) {
+        self.context_menu = Some(ContextMenu(Box::new(add_contents)));
     }
 
-    fn get_elapsed_time(&mut self) -> Duration {
+    /// Paints on top of the waveform of [`Player::timeline_ui`] every time it is drawn
+    ///
+    /// Use it to draw annotations or watermarks that follow playback, [`OverlayState::time_to_x`] maps a time to the drawn area
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use eframe::egui::{Color32, Stroke};
+    /// use egui_player::player::Player;
+    /// use std::time::Duration;
+    ///
+    /// let mut player = Player::from_path("hello.mp3");
+    /// player.set_overlay(|painter, rect, state| {
+    ///     // Mark where the chorus starts
+    ///     let x = state.time_to_x(Duration::from_secs(30), rect);
+    ///     painter.vline(x, rect.y_range(), Stroke::new(2.0, Color32::GOLD));
+    /// });
+    /// ```
+    pub fn set_overlay(
+        &mut self,
+        paint: impl FnMut(&Painter, Rect, &OverlayState) + Send + 'static,
+    ) {
+        self.overlay = Some(Overlay(Box::new(paint)));
+    }
+
+    /// Customizes the control bar, see [`ControlsConfig`]
+    pub fn set_controls(&mut self, controls: ControlsConfig) {
+        self.controls = controls;
+    }
+
+    /// Restarts from the beginning once the end is reached, turning it off makes the player stop at the end
+    pub fn set_looping(&mut self, looping: bool) {
+        if looping {
+            self.on_end = OnEnd::Repeat;
+        } else if self.is_looping() {
+            self.on_end = OnEnd::Stop;
+        }
+    }
+
+    pub fn is_looping(&self) -> bool {
+        matches!(self.on_end, OnEnd::Repeat)
+    }
+
+    /// Runs ``on_change`` with the previous and the new state whenever the player starts playing, pauses or ends
+    ///
+    /// Use it to mirror playback into your app, such as pausing other media or updating a tray icon.
+    /// Seeking and restarting because of [`OnEnd::Repeat`] are not changes of state
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::{Player, PlayerState};
+    ///
+    /// let mut player = Player::from_path("hello.mp3");
+    /// player.on_state_change(|previous, state| {
+    ///     if state == PlayerState::Playing {
+    ///         println!("Started playing, was {previous:?}");
+    ///     }
+    /// });
+    /// ```
+    pub fn on_state_change(
+        &mut self,
+        on_change: impl FnMut(PlayerState, PlayerState) + Send + 'static,
+    ) {
+        self.state_hook = Some(StateHook(Box::new(on_change)));
+    }
+
+    /// Runs ``on_event`` for every [`PlayerEvent`], from the next [`Player::update`] after it happened
+    ///
+    /// Corrupt parts of a file are skipped instead of ending playback, this tells your app where they were so it can
+    /// point them out or offer to download the file again
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use egui_player::player::{Player, PlayerEvent};
+    ///
+    /// let mut player = Player::from_path("hello.mp3");
+    /// player.on_event(|event| {
+    ///     if let PlayerEvent::DecodeWarning { position, message } = event {
+    ///         println!("Skipped a bad frame at {position:?}: {message}");
+    ///     }
+    /// });
+    /// ```
+    pub fn on_event(&mut self, on_event: impl FnMut(&PlayerEvent) + Send + 'static) {
+        self.event_hook = Some(EventHook(Box::new(on_event)));
+    }
+
+    /// Whether playback starts again by itself after the audio output failed, on by default
+    ///
+    /// The player tries again every second, which picks up the new default device once the backend has switched to it.
+    /// While turned off the player stays in [`PlayerState::Error`] until the play button is clicked
+    pub fn set_audio_retry(&mut self, audio_retry: bool) {
+        self.audio_retry = audio_retry;
+    }
+
+    /// Decides what happens once the end of the media is reached, see [`OnEnd`]
+    ///
+    /// Members of a [`crate::sync_group::SyncGroup`] never repeat on their own, the group decides when they play
+    pub fn set_on_end(&mut self, on_end: OnEnd) {
+        self.on_end = on_end;
+    }
+
+    /// Loops playback between ``start`` and ``end``, ``None`` removes the loop
+    ///
+    /// The loop can also be set by right clicking the seek bar, once for the A point and once for the B point
+    pub fn set_ab_loop(&mut self, ab_loop: Option<(Duration, Duration)>) {
+        self.ab_points = match ab_loop {
+            Some((start, end)) => (Some(start.min(end)), Some(start.max(end))),
+            None => (None, None),
+        };
+    }
+
+    /// Start and end of the A/B loop once both points are set
+    pub fn ab_loop(&self) -> Option<(Duration, Duration)> {
+        match self.ab_points {
+            (Some(start), Some(end)) => Some((start, end)),
+            _ => None,
+        }
+    }
+
+    /// Sets the next A/B point at ``time``, a third point starts a new loop
+    fn set_ab_point(&mut self, time: Duration) {
+        self.ab_points = match self.ab_points {
+            (Some(start), None) => (Some(start.min(time)), Some(start.max(time))),
+            _ => (Some(time), None),
+        };
+    }
+
+    /// Replaces the glyphs used on the control bar
+    pub fn set_icons(&mut self, icons: Icons) {
+        self.icons = icons;
+    }
+
+    /// Replaces the text shown on the player, see [`PlayerLocale`]
+    pub fn set_locale(&mut self, locale: PlayerLocale) {
+        self.locale = locale;
+    }
+
+    /// Changes how times are written on the control bar and timeline, see [`TimeFormat`]
+    pub fn set_time_format(&mut self, time_format: TimeFormat) {
+        self.time_format = time_format;
+    }
+
+    /// Configure where model is downloaded
+    pub fn set_model_download_path(&mut self, file_path: String) {
+        self.model_path = ModelPath::Custom(file_path);
+    }
+
+    /// Transcribes with model files that are already on disk instead of downloading them, see [`LocalModel`]
+    ///
+    /// Pair it with [`Player::set_whisper_model`] when the files are not a ``QuantizedLargeV3Turbo`` model
+    pub fn set_local_model(&mut self, local: LocalModel) {
+        self.model_path = ModelPath::Local(local);
+    }
+
+    /// Transcribes with ``transcriber`` instead of the [`WhisperTranscriber`], see [`Transcriber`]
+    pub fn set_transcriber(&mut self, transcriber: impl Transcriber + 'static) {
+        self.transcriber = Some(Arc::new(transcriber));
+    }
+
+    /// Looks up lyrics, artwork and track info for the file with ``provider``, see [`MetadataProvider`]
+    ///
+    /// The lookup is spawned on the tokio runtime right away, so this has to be called from inside of one.
+    /// What it finds is applied by the next [`Player::update`]
+    pub fn set_metadata_provider(&mut self, provider: impl MetadataProvider + 'static) {
+        let file_input = self.file_input.clone();
+        let media_type = self.media_type;
+        let duration = self.total_time;
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        self.metadata_receiver = Some(receiver);
+        tokio::spawn(async move {
+            // Reading the tags blocks, so they are parsed on the worker pool instead of the runtime
+            let (tx_info, rx_info) = tokio::sync::oneshot::channel();
+            let tags_input = file_input.clone();
+            worker_pool::spawn(move || {
+                // The length may not be probed yet
+                let duration = if duration == Duration::ZERO {
+                    media_information::get_total_time(media_type, tags_input.clone())
+                } else {
+                    duration
+                };
+                let _ = tx_info.send((media_information::get_track_info(tags_input), duration));
+            });
+            let (info, duration) = rx_info.await.unwrap_or_default();
+            let query = TrackQuery {
+                file_input,
+                duration,
+                info,
+            };
+            let _ = sender.send(provider.fetch(query).await);
+        });
+    }
+
+    /// Track info found by the [`MetadataProvider`], shown above the control bar
+    pub fn track_info(&self) -> Option<&TrackInfo> {
+        self.track_info.as_ref()
+    }
+
+    /// Applies what the [`MetadataProvider`] found once it is done
+    fn receive_metadata(&mut self) {
+        let Some(receiver) = &mut self.metadata_receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok(metadata) => {
+                self.metadata_receiver = None;
+                if let Some(info) = metadata.info {
+                    self.track_info = Some(info);
+                }
+                if let Some(lyrics) = metadata.lyrics {
+                    self.set_lyrics(lyrics);
+                }
+                if let Some(artwork) = metadata.artwork
+                    && !self.custom_poster
+                {
+                    self.poster = Some(cover_image(artwork));
+                    self.poster_loaded = true;
+                }
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Closed) => self.metadata_receiver = None,
+        }
+    }
+
+    /// Transcriber set with [`Player::set_transcriber`], or Whisper with the [`ModelPath`] of the player
+    fn transcriber(&self) -> Arc<dyn Transcriber> {
+        self.transcriber.clone().unwrap_or_else(|| {
+            Arc::new(WhisperTranscriber {
+                model_path: self.model_path.clone(),
+                model: self.whisper_model,
+                resources: self.transcription_resources,
+                decoding: self.decoding_options,
+            })
+        })
+    }
+
+    /// Sets the playback speed, ``1.0`` is normal speed. Values are clamped between ``0.25`` and ``4.0``
+    pub fn set_speed(&mut self, speed: f32) {
+        // Restart the stopwatch so time already played is counted at the previous speed
+        self.elapsed_time = self.get_elapsed_time();
+        if self.stopwatch_instant.is_some() {
+            self.stopwatch_instant = Some(self.clock.now());
+            self.start_time = self.elapsed_time;
+        }
+        self.dsp_settings.set_speed(speed.clamp(0.25, 4.0));
+    }
+
+    /// Current playback speed
+    pub fn speed(&self) -> f32 {
+        self.dsp_settings.speed()
+    }
+
+    /// Configure how speed changes are applied by changing the [`SpeedMode`] enum
+    pub fn set_speed_mode(&mut self, mode: SpeedMode) {
+        self.dsp_settings.set_speed_mode(mode);
+    }
+
+    /// Current [`SpeedMode`]
+    pub fn speed_mode(&self) -> SpeedMode {
+        self.dsp_settings.speed_mode()
+    }
+
+    /// Shifts the pitch by an amount of semitones without changing the speed. Values are clamped between ``-12.0`` and ``12.0``
+    pub fn set_pitch_semitones(&mut self, semitones: f32) {
+        self.dsp_settings
+            .set_pitch_semitones(semitones.clamp(-12.0, 12.0));
+    }
+
+    /// Current pitch shift in semitones
+    pub fn pitch_semitones(&self) -> f32 {
+        self.dsp_settings.pitch_semitones()
+    }
+
+    /// Sums all channels into mono before output, useful for single-ear listening or one-sided recordings
+    pub fn set_mono(&mut self, mono: bool) {
+        self.dsp_settings.set_mono(mono);
+    }
+
+    /// Whether audio is downmixed to mono
+    pub fn is_mono(&self) -> bool {
+        self.dsp_settings.mono()
+    }
+
+    /// Gives access to the [`EffectsChain`] applied to the audio before it is output
+    ///
+    /// Changes are picked up by the playing audio right away
+    pub fn effects_mut(&mut self) -> MutexGuard<'_, EffectsChain> {
+        self.effects.lock().unwrap()
+    }
+
+    /// Sets the volume from a gain in decibels, ``0.0`` is full volume and ``-6.0`` about half the amplitude
+    ///
+    /// The volume is stored as a whole percentage, so the gain is rounded to the nearest one. Gains above 0 dB
+    /// are capped at full volume, boost with [`Player::set_level_gain`] instead
+    pub fn set_volume_db(&mut self, gain_db: f32) {
+        let volume = (100.0 * 10.0_f32.powf(gain_db / 20.0)).round() as i32;
+        self.volume.store(volume.clamp(0, 100), Ordering::Release);
+    }
+
+    /// Gain of the current volume in decibels, ``f32::NEG_INFINITY`` when the volume is zero
+    pub fn volume_db(&self) -> f32 {
+        20.0 * (self.volume.load(Ordering::Acquire) as f32 / 100.0).log10()
+    }
+
+    /// Amount the volume changes for each step scrolled over the volume icon or press of a volume key, ``5`` by default
+    pub fn set_volume_scroll_step(&mut self, step: i32) {
+        self.volume_scroll_step = step;
+    }
+
+    /// Decodes up to ``read_ahead`` of audio ahead of playback on a thread of its own, for files on slow storage or
+    /// heavy formats that would otherwise stutter
+    ///
+    /// Memory stays bounded by the read ahead however long the file is. Zero, the default, decodes on the audio thread
+    /// as the audio is played. Takes effect the next time playback starts or seeks
+    pub fn set_read_ahead(&mut self, read_ahead: Duration) {
+        self.read_ahead = read_ahead;
+    }
+
+    /// Extra gain in decibels applied on top of the volume, used by [`crate::playlist::Playlist::set_level_matching`]
+    /// to bring every entry to the same loudness
+    pub fn set_level_gain(&mut self, gain_db: f32) {
+        self.dsp_settings.set_gain(10.0_f32.powf(gain_db / 20.0));
+    }
+
+    /// Gain set with [`Player::set_level_gain`] in decibels
+    pub fn level_gain(&self) -> f32 {
+        20.0 * self.dsp_settings.gain().log10()
+    }
+
+    /// Turns the output down where the gain stages before it would clip, on by default
+    ///
+    /// The level gain of [`Player::set_level_gain`], EQ boosts and pitch shifting can all push loud files past full scale.
+    /// Audio that stays below full scale is left as it is
+    pub fn set_limiter(&mut self, limiter: bool) {
+        self.dsp_settings.set_limiter(limiter);
+    }
+
+    pub fn is_limiter_enabled(&self) -> bool {
+        self.dsp_settings.limiter()
+    }
+
+    /// Shows peak and RMS level meters for each channel next to the volume control
+    ///
+    /// A light above the meters turns red for a moment whenever the output reaches full scale, with the
+    /// limiter on it shows where the limiter had to step in, see [`Player::set_limiter`]
+    pub fn set_level_meters(&mut self, show: bool) {
+        self.show_level_meters = show;
+    }
+
+    /// Paints a soft gradient behind the control bar that slowly shifts color and glows with the loudness of the audio
+    pub fn set_ambient(&mut self, ambient: bool) {
+        self.ambient = ambient;
+    }
+
+    /// Skips the silence at the start and end of the file, so voice memos begin right away
+    ///
+    /// The audio is scanned on a background thread the first time this is turned on, until then the whole file plays.
+    /// The skipped parts are shaded on the seek bar
+    pub fn set_trim_silence(&mut self, trim: bool) {
+        self.trim_silence = trim;
+        if trim
+            && self.content_bounds.is_none()
+            && self.bounds_receiver.is_none()
+            && matches!(self.media_type, MediaType::Audio)
+        {
+            let file_input = self.file_input.clone();
+            let (tx_bounds, rx_bounds) = mpsc::channel();
+            self.bounds_receiver = Some(rx_bounds);
+            worker_pool::spawn(
+                move || match media_information::content_bounds(file_input) {
+                    Ok(bounds) => {
+                        let _ = tx_bounds.send(bounds);
+                    }
+                    Err(error) => log::error!("Could not find the silence to trim: {error}"),
+                },
+            );
+        }
+    }
+
+    /// Start and end of the audible part of the file, once it has been found with [`Player::set_trim_silence`]
+    pub fn content_bounds(&self) -> Option<(Duration, Duration)> {
+        self.content_bounds
+    }
+
+    /// The [`Player::content_bounds`] while silence is trimmed
+    fn trimmed_bounds(&self) -> Option<(Duration, Duration)> {
+        self.content_bounds.filter(|_| self.trim_silence)
+    }
+
+    /// Shows the time left until the end in large text above the control bar, for presenters playing a clip
+    ///
+    /// The countdown turns red for the last 10 seconds
+    pub fn set_countdown(&mut self, show: bool) {
+        self.show_countdown = show;
+    }
+
+    /// Shows a panel under the player with its internal state, for diagnosing sync or stutter problems
+    ///
+    /// Lists the player state, the time of the stopwatch next to the position of the audio output,
+    /// how much audio is buffered, whether the audio thread is running and how many transcription updates are waiting
+    pub fn set_debug_panel(&mut self, show: bool) {
+        self.show_debug_panel = show;
+    }
+
+    /// Changes how often the player is redrawn while playing, see [`RepaintPolicy`]
+    pub fn set_repaint_policy(&mut self, repaint_policy: RepaintPolicy) {
+        self.repaint_policy = repaint_policy;
+    }
+
+    /// Time left until the end of the media
+    ///
+    /// While silence is trimmed the end is where the audio ends, see [`Player::set_trim_silence`]
+    pub fn remaining_time(&self) -> Duration {
+        let end = self
+            .trimmed_bounds()
+            .map_or(self.total_time, |(_, end)| end);
+        end.saturating_sub(self.elapsed_time)
+    }
+
+    /// Smoothed levels of each channel as shown by the level meters
+    ///
+    /// Only updated while the level meters are shown
+    pub fn levels(&self) -> &[ChannelLevel] {
+        &self.levels
+    }
+
+    /// Configure the visualization shown under the control bar by changing the [`Visualization`] enum
+    ///
+    /// Clicking the visualization plays / pauses the player and double clicking it toggles fullscreen
+    pub fn set_visualization(&mut self, visualization: Visualization) {
+        match visualization {
+            Visualization::None => self.visualizer = None,
+            Visualization::Oscilloscope => self.set_visualizer(Oscilloscope::default()),
+        }
+    }
+
+    /// Shows a custom [`Visualizer`] under the control bar, replacing the current visualization
+    pub fn set_visualizer(&mut self, visualizer: impl Visualizer + 'static) {
+        self.visualizer = Some(Box::new(visualizer));
+    }
+
+    /// Configure what is drawn behind the seek bar by changing the [`SeekBarBackground`] enum
+    ///
+    /// The waveform or spectrogram is computed in the background the first time it is needed
+    pub fn set_seek_bar_background(&mut self, background: SeekBarBackground) {
+        self.seek_bar_background = background;
+    }
+
+    /// Plays through ``context`` instead of [`AudioContext::global`], takes effect the next time playback starts
+    pub fn set_audio_context(&mut self, context: AudioContext) {
+        if self.solo_guard.is_some() {
+            self.solo_guard = Some(SoloGuard::new(context.clone()));
+        }
+        self.audio_context = Some(context);
+    }
+
+    /// Keeps time with ``clock`` instead of the system clock, see [`ManualClock`](crate::clock::ManualClock)
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        self.elapsed_time = self.get_elapsed_time();
+        if self.stopwatch_instant.is_some() {
+            self.start_time = self.elapsed_time;
+            self.stopwatch_instant = Some(clock.now());
+        }
+        if let Some(started) = self.play_started {
+            self.play_time += self.clock.now().saturating_duration_since(started);
+            self.play_started = Some(clock.now());
+        }
+        self.clock = Arc::new(clock);
+    }
+
+    /// The context given with [`Player::set_audio_context`], or [`AudioContext::global`]
+    fn audio_context(&self) -> AudioContext {
+        self.audio_context
+            .clone()
+            .unwrap_or_else(|| AudioContext::global().clone())
+    }
+
+    /// Remembers the settings of this file in ``store``, reapplying what was saved the last time the same file was opened
+    ///
+    /// Files are recognised by their content, so this also works for players made from bytes. The file is hashed on the
+    /// [`worker_pool`] and what was saved is applied by a later [`Player::update`].
+    /// The volume is remembered relative to the volume the player has now, see [`FilePreferences`]
+    pub fn set_preference_store(&mut self, store: impl PreferenceStore + 'static) {
+        self.preference_base_volume = self.volume.load(Ordering::Acquire);
+        self.preference_store = None;
+        let file_input = self.file_input.clone();
+        let cached_hash = self.content_hash.get().cloned();
+        let (sender, receiver) = mpsc::channel();
+        self.preference_receiver = Some(receiver);
+        worker_pool::spawn(move || {
+            let hash = cached_hash.or_else(|| media_information::content_hash(&file_input).ok());
+            let preferences = hash.as_deref().and_then(|hash| store.load(hash));
+            let store: Box<dyn PreferenceStore> = Box::new(store);
+            let _ = sender.send((store, hash, preferences));
+        });
+    }
+
+    /// Applies what the preference store remembered once the file was hashed, see [`Player::set_preference_store`]
+    fn receive_preferences(&mut self) {
+        let Some(receiver) = &self.preference_receiver else {
+            return;
+        };
+        match receiver.try_recv() {
+            Ok((store, hash, preferences)) => {
+                if let Some(preferences) = preferences {
+                    self.apply_preferences(preferences);
+                }
+                self.saved_preferences = Some(self.preferences());
+                self.preference_store = Some(store);
+                if let Some(hash) = hash {
+                    let _ = self.content_hash.set(hash);
+                }
+                self.preference_receiver = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.preference_receiver = None,
+        }
+    }
+
+    /// Hash of the file, see [`media_information::content_hash`]
+    ///
+    /// The file is only hashed the first time, later calls return the same hash
+    pub fn content_hash(&self) -> io::Result<&str> {
+        if let Some(hash) = self.content_hash.get() {
+            return Ok(hash);
+        }
+        let hash = media_information::content_hash(&self.file_input)?;
+        Ok(self.content_hash.get_or_init(|| hash))
+    }
+
+    /// Current settings that are remembered by a [`PreferenceStore`]
+    pub fn preferences(&self) -> FilePreferences {
+        FilePreferences {
+            speed: self.speed(),
+            speed_mode: self.speed_mode(),
+            pitch_semitones: self.pitch_semitones(),
+            volume_offset: self.volume.load(Ordering::Acquire) - self.preference_base_volume,
+            mono: self.is_mono(),
+            caption_source: self.caption_source,
+            caption_offset: self.caption_offset,
+        }
+    }
+
+    pub fn apply_preferences(&mut self, preferences: FilePreferences) {
+        self.set_speed(preferences.speed);
+        self.set_speed_mode(preferences.speed_mode);
+        self.set_pitch_semitones(preferences.pitch_semitones);
+        let volume = self.preference_base_volume + preferences.volume_offset;
+        self.volume.store(volume.clamp(0, 100), Ordering::Release);
+        self.set_mono(preferences.mono);
+        self.set_caption_source(preferences.caption_source);
+        self.set_caption_offset(preferences.caption_offset);
+    }
+
+    /// Writes the settings to the [`PreferenceStore`] once they changed and then stayed the same for [`PREFERENCE_SAVE_DELAY`]
+    ///
+    /// Returns how long until the settings should be checked again, while some are waiting to be written
+    fn save_preferences(&mut self) -> Option<Duration> {
+        let preferences = self.preferences();
+        if self.preference_store.is_none() || self.saved_preferences == Some(preferences) {
+            self.unsaved_preferences = None;
+            return None;
+        }
+        let now = self.clock.now();
+        let changed = match self.unsaved_preferences {
+            Some((unsaved, changed)) if unsaved == preferences => changed,
+            _ => now,
+        };
+        let waited = now.saturating_duration_since(changed);
+        if waited < PREFERENCE_SAVE_DELAY {
+            self.unsaved_preferences = Some((preferences, changed));
+            return Some(PREFERENCE_SAVE_DELAY - waited);
+        }
+        if let (Some(store), Some(hash)) = (&mut self.preference_store, self.content_hash.get()) {
+            store.store(hash, preferences);
+        }
+        self.saved_preferences = Some(preferences);
+        self.unsaved_preferences = None;
+        None
+    }
+
+    /// Counters describing playback so far, handy for logging quality metrics
+    pub fn stats(&self) -> PlaybackStats {
+        let (slow_blocks, decode_errors, average_decode_time) = self.audio_tap.decode_stats();
+        PlaybackStats {
+            play_time: self.play_time
+                + self
+                    .play_started
+                    .map(|started| self.clock.now().saturating_duration_since(started))
+                    .unwrap_or_default(),
+            seeks: self.seeks,
+            slow_blocks,
+            decode_errors,
+            average_decode_time,
+        }
+    }
+
+    /// Silences the player without changing its volume
+    pub fn set_muted(&mut self, muted: bool) {
+        self.muted.store(muted, Ordering::Release);
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted.load(Ordering::Acquire)
+    }
+
+    /// Soloing a player silences every player of the same [`AudioContext`] that is not soloed
+    pub fn set_solo(&mut self, solo: bool) {
+        if solo && self.solo_guard.is_none() {
+            self.solo_guard = Some(SoloGuard::new(self.audio_context()));
+        } else if !solo {
+            self.solo_guard = None;
+        }
+        self.solo.store(solo, Ordering::Release);
+    }
+
+    pub fn is_solo(&self) -> bool {
+        self.solo.load(Ordering::Acquire)
+    }
+
+    /// Shows mute and solo toggles next to the volume control, handy when several players are on screen
+    pub fn set_mix_controls(&mut self, show: bool) {
+        self.show_mix_controls = show;
+    }
+
+    /// Caches computed waveforms and spectrograms inside ``directory`` so reopening a file shows them instantly
+    ///
+    /// Entries are keyed by a hash of the file content, so renamed or moved files are still found
+    pub fn set_waveform_cache_dir(&mut self, directory: impl Into<PathBuf>) {
+        self.analysis_cache = Some(AnalysisCache::new(directory));
+    }
+
+    /// Peaks of the whole file, available once the [`SeekBarBackground::Waveform`] has been computed
+    pub fn waveform_peaks(&self) -> Option<&[f32]> {
+        self.waveform_peaks.as_deref()
+    }
+
+    /// [`Spectrogram`] of the whole file, available once the [`SeekBarBackground::Spectrogram`] has been computed
+    pub fn spectrogram(&self) -> Option<&Spectrogram> {
+        self.spectrogram.as_ref()
+    }
+
+    /// Adds a [`Marker`] that is shown on the [`Player::timeline_ui`]
+    pub fn add_marker(&mut self, time: Duration, label: impl Into<String>) {
+        self.markers.push(Marker {
+            time,
+            label: label.into(),
+        });
+    }
+
+    /// Adds a chapter for every track of ``sheet`` and shows the title of the current track on the control bar
+    pub fn set_cue_sheet(&mut self, sheet: &CueSheet) {
+        for track in &sheet.tracks {
+            self.add_marker(track.start, track.title.clone());
+        }
+        self.show_chapters = true;
+    }
+
+    /// Shows the label of the [`Player::current_chapter`] next to the time on the control bar, and a menu listing
+    /// every chapter to jump to. On for players of CUE sheets and of files with chapters in their tags
+    pub fn set_chapter_controls(&mut self, show: bool) {
+        self.show_chapters = show;
+    }
+
+    /// Jumps over ``range`` whenever playback reaches it, showing "Skipped ``label``" for a moment
+    ///
+    /// Useful for the intro and outro of every episode of a series
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// # use egui_player::player::Player;
+    /// use std::time::Duration;
+    ///
+    /// let mut player = Player::from_path("lecture.mp3");
+    /// player.add_skip_segment(Duration::ZERO..Duration::from_secs(30), "intro");
+    /// ```
+    pub fn add_skip_segment(&mut self, range: Range<Duration>, label: impl Into<String>) {
+        self.skip_segments.push(SkipSegment {
+            range,
+            label: label.into(),
+        });
+    }
+
+    /// Regions added with [`Player::add_skip_segment`]
+    pub fn skip_segments(&self) -> &[SkipSegment] {
+        &self.skip_segments
+    }
+
+    /// Removes every region added with [`Player::add_skip_segment`]
+    pub fn clear_skip_segments(&mut self) {
+        self.skip_segments.clear();
+    }
+
+    /// Adds every [`Chapter`] as a marker and shows the chapter controls
+    /// Applies the length and chapters read on the worker pool by [`Player::new`]
+    fn set_media_info(&mut self, total_time: Duration, chapters: Vec<Chapter>) {
+        // A player that already played to its end knows how long it is, even when probing found nothing
+        if total_time != Duration::ZERO {
+            self.total_time = total_time;
+        }
+        // Seeks made before the length was known were not held to it
+        self.elapsed_time = self.clamp_to_duration(self.elapsed_time);
+        if let Some(last) = self.lyrics.last_mut()
+            && last.end <= last.time
+        {
+            last.end = self.total_time.max(last.time);
+        }
+        if !chapters.is_empty() {
+            self.add_chapters(chapters);
+        }
+    }
+
+    fn add_chapters(&mut self, chapters: Vec<Chapter>) {
+        for chapter in chapters {
+            if let Some(image) = chapter.image {
+                self.chapter_images
+                    .push((chapter.start, cover_image(image)));
+            }
+            self.add_marker(chapter.start, chapter.title);
+        }
+        self.show_chapters = true;
+    }
+
+    /// Menu of every chapter, sorted by time, that seeks to the chapter picked
+    fn chapter_menu(&mut self, ui: &mut Ui) {
+        let mut chapters: Vec<Marker> = self.markers.clone();
+        chapters.sort_by_key(|marker| marker.time);
+        let current = self.current_chapter().map(|marker| marker.time);
+        let mut picked = None;
+        ui.menu_button(self.icons.chapters.as_str(), |ui| {
+            let image_size = Vec2::splat(ui.spacing().interact_size.y * 1.5);
+            for chapter in &chapters {
+                ui.horizontal(|ui| {
+                    if let Some((_, image)) = self
+                        .chapter_images
+                        .iter()
+                        .find(|(time, _)| *time == chapter.time)
+                    {
+                        ui.add(Image::new(image.clone()).fit_to_exact_size(image_size));
+                    }
+                    let text = format!(
+                        "{} {}",
+                        self.time_format.format(chapter.time),
+                        chapter.label
+                    );
+                    if ui
+                        .selectable_label(current == Some(chapter.time), text)
+                        .clicked()
+                    {
+                        picked = Some(chapter.time);
+                        ui.close_menu();
+                    }
+                });
+            }
+        })
+        .response
+        .on_hover_text(&self.locale.chapters);
+        if let Some(time) = picked {
+            self.seek(time);
+        }
+    }
+
+    /// The last [`Marker`] at or before the playback position, chapters are the markers of the player
+    pub fn current_chapter(&self) -> Option<&Marker> {
+        self.markers
+            .iter()
+            .filter(|marker| marker.time <= self.elapsed_time)
+            .max_by_key(|marker| marker.time)
+    }
+
+    /// Keeps track of the playback position, and ends or loops playback once the end is reached
+    ///
+    /// Drawing the player does this every frame. Call it yourself while the player is not drawn, for example in a collapsed panel
+    /// or a hidden tab, so audio keeps playing in the background with the position kept up to date.
+    /// Request a repaint of your app before [`Player::remaining_time`] runs out so the end is noticed
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// # use egui_player::player::Player;
+    /// # let ctx = eframe::egui::Context::default();
+    /// # let mut player = Player::from_path("hello.mp3");
+    /// # let panel_open = false;
+    /// if !panel_open {
+    ///     player.update();
+    ///     ctx.request_repaint_after(player.remaining_time());
+    /// }
+    /// ```
+    pub fn update(&mut self) {
+        // Also catches changes made directly to the public state
+        self.report_state();
+        self.report_events();
+        self.receive_metadata();
+        self.receive_preferences();
+        if let Some(receiver) = &self.poster_receiver {
+            match receiver.try_recv() {
+                Ok((poster, placeholder_color)) => {
+                    self.set_found_poster(poster, placeholder_color);
+                    self.poster_receiver = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.poster_receiver = None,
+            }
+        }
+        if let Some(receiver) = &self.bounds_receiver {
+            match receiver.try_recv() {
+                Ok(bounds) => {
+                    self.content_bounds = Some(bounds);
+                    self.bounds_receiver = None;
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.bounds_receiver = None,
+            }
+        }
+        if let Some(receiver) = &self.lyrics_receiver {
+            match receiver.try_recv() {
+                Ok(lyrics) => {
+                    self.lyrics_receiver = None;
+                    // Lyrics set in the meantime win over the embedded ones
+                    if self.lyrics.is_empty() {
+                        self.set_lyrics(lyrics);
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.lyrics_receiver = None,
+            }
+        }
+        if let Some(receiver) = &self.media_receiver {
+            match receiver.try_recv() {
+                Ok((total_time, chapters)) => {
+                    self.media_receiver = None;
+                    self.set_media_info(total_time, chapters);
+                }
+                Err(mpsc::TryRecvError::Empty) => {}
+                Err(mpsc::TryRecvError::Disconnected) => self.media_receiver = None,
+            }
+        }
+        self.setup_stopwatch();
+
+        // Playback ends once the decoder runs out of audio, the total time is only an estimate and may be missing
+        self.elapsed_time = self.clamp_to_duration(self.elapsed_time);
+        let trimmed = self.trimmed_bounds();
+        if self.player_state == PlayerState::Playing
+            && let Some((start, _)) = trimmed
+            && self.elapsed_time < start
+        {
+            self.seek(start);
+        }
+        if self.player_state == PlayerState::Playing
+            && let Some(segment) = self
+                .skip_segments
+                .iter()
+                .find(|segment| segment.range.contains(&self.elapsed_time))
+        {
+            let message = format!("{} {}", self.locale.skipped, segment.label);
+            self.seek(segment.range.end);
+            self.skip_toast = Some((message, self.clock.now()));
+        }
+        let past_content = trimmed.is_some_and(|(_, end)| self.elapsed_time >= end);
+        if self.player_state == PlayerState::Playing
+            && (self.audio_left.load(Ordering::Acquire) == 0 || past_content)
+        {
+            if self.is_looping() && !self.sync_member {
+                self.elapsed_time = Duration::ZERO;
+                self.restart_playback();
+            } else {
+                self.stop_audio();
+                self.stopwatch_instant = None;
+                if self.total_time == Duration::ZERO {
+                    self.total_time = self.elapsed_time;
+                }
+                self.elapsed_time = self.total_time;
+                self.set_player_state(PlayerState::Ended);
+                if matches!(self.on_end, OnEnd::Callback(_))
+                    && let OnEnd::Callback(mut callback) = mem::take(&mut self.on_end)
+                {
+                    callback(self);
+                    // The callback may have set another end behavior
+                    if matches!(self.on_end, OnEnd::Stop) {
+                        self.on_end = OnEnd::Callback(callback);
+                    }
+                }
+            }
+        }
+
+        if self.player_state == PlayerState::Playing && self.audio_moved.load(Ordering::Acquire) {
+            let elapsed = self.get_elapsed_time();
+            self.elapsed_time = self.clamp_to_duration(elapsed);
+            self.restart_playback();
+        }
+        if self.player_state == PlayerState::Playing && self.audio_failed.load(Ordering::Acquire) {
+            // Keep the position so playback carries on from where the audio stopped
+            let elapsed = self.get_elapsed_time();
+            self.elapsed_time = self.clamp_to_duration(elapsed);
+            self.stop_audio();
+            self.stopwatch_instant = None;
+            self.set_player_state(PlayerState::Error);
+            self.audio_failed_at = Some(self.clock.now());
+        }
+        if self.player_state == PlayerState::Error
+            && self.audio_retry
+            && self.audio_failed_at.is_none_or(|failed_at| {
+                self.clock.now().saturating_duration_since(failed_at) >= AUDIO_RETRY_INTERVAL
+            })
+        {
+            log::debug!("Retrying audio output");
+            self.play_player();
+        }
+
+        if let Some((start, end)) = self.ab_loop()
+            && self.player_state == PlayerState::Playing
+            && self.elapsed_time >= end
+        {
+            self.seek(start);
+        }
+
+        self.transcription_position.set(self.elapsed_time);
+        if let TranscriptionMode::FollowPlayback(_) = self.transcription_mode
+            && self.player_state == PlayerState::Playing
+            && self.transcription_progress == TranscriptionProgress::NoProgress
+            && self.transcription_settings != TranscriptionSettings::None
+        {
+            self.start_transcription(
+                self.transcription_settings == TranscriptionSettings::ShowTimeStamps,
+            );
+        }
+    }
+
+    /// Starts transcribing in the background with the [`Transcriber`] and [`TranscriptionMode`] of the player
+    fn start_transcription(&mut self, is_timestamped: bool) {
+        self.transcription_progress = TranscriptionProgress::Reading;
+        let file_input = self.file_input.clone();
+        let transcriber = self.transcriber();
+        let (tx_transcript, rx_transcript) = tokio::sync::mpsc::unbounded_channel();
+        self.transcript_receiver = Some(rx_transcript);
+
+        let mode = self.transcription_mode;
+        let position = self.transcription_position.clone();
+        let resume_from = self.transcription_resume_from;
+        tokio::spawn(async move {
+            match mode {
+                TranscriptionMode::WholeFile if !resume_from.is_zero() => {
+                    transcriber
+                        .transcribe_from(file_input, resume_from, is_timestamped, tx_transcript)
+                        .await;
+                }
+                TranscriptionMode::WholeFile => {
+                    transcriber
+                        .transcribe(file_input, is_timestamped, tx_transcript)
+                        .await;
+                }
+                TranscriptionMode::FollowPlayback(window) => {
+                    transcriber
+                        .transcribe_following(
+                            file_input,
+                            is_timestamped,
+                            position,
+                            window,
+                            tx_transcript,
+                        )
+                        .await;
+                }
+            }
+        });
+    }
+
+    /// The transcript so far along with where transcription got to, save it to pick up from there with [`Player::resume_transcription`]
+    ///
+    /// Transcription that follows playback does not go from start to end, its checkpoints resume from the first gap
+    pub fn transcription_checkpoint(&self) -> TranscriptionCheckpoint {
+        let mut resume_from = self.transcription_resume_from;
+        for data in &self.transcript {
+            // Following playback leaves gaps, only the part without one from the start counts as done
+            if matches!(
+                self.transcription_mode,
+                TranscriptionMode::FollowPlayback(_)
+            ) && data.time > resume_from + Duration::from_secs(30)
+            {
+                break;
+            }
+            resume_from = resume_from.max(data.end.max(data.time));
+        }
+        TranscriptionCheckpoint {
+            transcript: self.transcript.clone(),
+            resume_from,
+            finished: self.transcription_progress == TranscriptionProgress::Finished,
+        }
+    }
+
+    /// Restores the transcript of ``checkpoint`` and carries on transcribing from where it stopped
+    pub fn resume_transcription(&mut self, checkpoint: TranscriptionCheckpoint) {
+        self.transcript = checkpoint.transcript;
+        self.transcript_layout = None;
+        if checkpoint.finished {
+            self.transcription_progress = TranscriptionProgress::Finished;
+            return;
+        }
+        self.transcription_resume_from = checkpoint.resume_from;
+        self.transcript_receiver = None;
+        self.start_transcription(
+            self.transcription_settings == TranscriptionSettings::ShowTimeStamps,
+        );
+    }
+
+    /// Jumps to ``time``, playback continues from there if the player was playing
+    ///
+    /// Members of a [`crate::sync_group::SyncGroup`] pass this on to the group so every member jumps together
+    pub fn seek(&mut self, time: Duration) {
+        log::debug!("Seeking to {time:?}");
+        if self.sync_member {
+            self.sync_requests.push(SyncRequest::Seek(time));
+            return;
+        }
+        // The audio restarts from the new position without the player going through a pause
+        let resume = self.player_state == PlayerState::Playing;
+        if resume {
+            self.stop_audio();
+        }
+        self.set_position(time);
+        if resume {
+            self.start_audio();
+        }
+    }
+
+    /// ``time`` kept within the length of the file, or as it is while the length is not known yet
+    fn clamp_to_duration(&self, time: Duration) -> Duration {
+        if self.total_time == Duration::ZERO {
+            time
+        } else {
+            time.min(self.total_time)
+        }
+    }
+
+    /// Moves the playback position of a paused player
+    pub(crate) fn set_position(&mut self, time: Duration) {
+        self.seeks += 1;
+        self.stopwatch_instant = None;
+        self.elapsed_time = self.clamp_to_duration(time);
+        if self.player_state == PlayerState::Ended && self.elapsed_time < self.total_time {
+            self.set_player_state(PlayerState::Paused);
+        }
+    }
+
+    /// Region selected by dragging across the [`Player::timeline_ui`], as start and end times
+    pub fn timeline_selection(&self) -> Option<(Duration, Duration)> {
+        self.timeline.selection()
+    }
+
+    /// Removes the region selected on the [`Player::timeline_ui`]
+    pub fn clear_timeline_selection(&mut self) {
+        self.timeline.clear_selection();
+    }
+
+    /// Time spanned by the words selected by dragging across the transcript, as start and end times
+    ///
+    /// Right clicking the selection offers to loop it, pass it to [`Player::export_clip`] to export it
+    pub fn transcript_selection(&self) -> Option<(Duration, Duration)> {
+        self.transcript_selection
+    }
+
+    pub fn clear_transcript_selection(&mut self) {
+        self.transcript_selection = None;
+    }
+
+    /// Writes the audio between ``start`` and ``end`` to ``path``, see [`media_information::export_clip`]
+    ///
+    /// Pass in [`Player::timeline_selection`] to export what the user selected
+    pub fn export_clip(
+        &self,
+        start: Duration,
+        end: Duration,
+        path: impl AsRef<Path>,
+        format: ExportFormat,
+    ) -> io::Result<()> {
+        media_information::export_clip(self.file_input.clone(), start, end, path, format)
+    }
+
+    /// Renders the whole file through the current speed, pitch, mono and [`EffectsChain`] settings and writes it to ``path``
+    ///
+    /// This is what the player sounds like, minus the volume. Playback is paused while rendering since the
+    /// effects are shared with the audio stream, and resumes afterwards. This blocks until the file is written
+    pub fn export_with_effects(
+        &mut self,
+        path: impl AsRef<Path>,
+        format: ExportFormat,
+    ) -> io::Result<()> {
+        let decoded = media_information::open_source(self.file_input.clone())?;
+        let resume = self.player_state == PlayerState::Playing;
+        let position = self.get_elapsed_time();
+        if resume {
+            self.stop_audio();
+        }
+        self.stopwatch_instant = None;
+        self.elapsed_time = position;
+
+        let settings = Arc::new(self.dsp_settings.snapshot());
+        self.effects.lock().unwrap().reset();
+        let source = EffectsStage::new(
+            TimeStretch::new(decoded, Arc::clone(&settings)),
+            Arc::clone(&self.effects),
+            settings,
+            Arc::new(AudioTap::default()),
+        );
+        let result = media_information::write_audio(source, path.as_ref(), format);
+        self.effects.lock().unwrap().reset();
+
+        if resume {
+            self.start_audio();
+        }
+        result
+    }
+
+    /// Allows you to rescale the player ``(Note: Currently non-functional)``
+    pub fn set_player_scale(&mut self, scale: f32) {
+        self.player_scale = scale;
+        if self.player_size.eq(&Vec2::default()) {
+            match self.media_type {
+                MediaType::Audio => {
+                    self.player_size = Vec2 { x: 50.0, y: 10.0 } * self.player_scale
+                }
+                MediaType::Video => self.player_size = Vec2 { x: 0.0, y: 0.0 } * self.player_scale,
+                MediaType::Error => panic!("No size since it is an unsupported type"),
+            }
+        } else {
+            self.player_size *= self.player_scale;
+        }
+    }
+
+    /// Displays bar containing pause/play, video time, draggable bar and volume control
+    fn control_bar(&mut self, ui: &mut Ui) {
+        if self.show_level_meters || self.ambient {
+            self.update_levels(ui);
+        }
+        let ambient_background = ui.painter().add(Shape::Noop);
+        let bar_response = ui.horizontal(|ui| {
+            if let Some(custom_left) = &mut self.controls.custom_left {
+                custom_left(ui);
+            }
+
+            let pause_icon = match self.player_state {
+                PlayerState::Playing => &self.icons.pause,
+                PlayerState::Paused => &self.icons.play,
+                PlayerState::Ended => &self.icons.restart,
+                PlayerState::Error => &self.icons.retry,
+            };
+            let pause_label = match self.player_state {
+                PlayerState::Playing => &self.locale.pause,
+                PlayerState::Paused => &self.locale.play,
+                PlayerState::Ended => &self.locale.restart,
+                PlayerState::Error => &self.locale.audio_output_lost,
+            };
+            let mut pause_response = ui.button(pause_icon.as_str());
+            pause_response
+                .widget_info(|| WidgetInfo::labeled(WidgetType::Button, true, pause_label));
+            if self.player_state == PlayerState::Error {
+                pause_response = pause_response.on_hover_text(pause_label);
+            }
+            if pause_response.clicked() {
+                self.toggle_playback();
+            }
+
+            ui.label(
+                self.time_format.format(self.elapsed_time)
+                    + " / "
+                    + &self.time_format.format(self.total_time),
+            );
+            if self.show_chapters && !self.markers.is_empty() {
+                if let Some(chapter) = self.current_chapter() {
+                    ui.label(RichText::new(&chapter.label).strong());
+                }
+                self.chapter_menu(ui);
+            }
+
+            // Right to left layouts progress from the right edge
+            let right_to_left = ui.layout().prefer_right_to_left();
+            let mut slider_value = self.elapsed_time.as_secs_f32();
+            let range = if right_to_left {
+                self.total_time.as_secs_f32()..=0.0
+            } else {
+                0.0..=self.total_time.as_secs_f32()
+            };
+            let slider = Slider::new(&mut slider_value, range).show_value(false);
+            let background = ui.painter().add(Shape::Noop);
+            // Positions on the seek bar mean nothing until the length of the file is known
+            let slider_response = ui.add_enabled(self.total_time != Duration::ZERO, slider);
+            slider_response.widget_info(|| {
+                let mut info = WidgetInfo::slider(true, slider_value as f64, &self.locale.seek);
+                info.current_text_value = Some(format!(
+                    "{} {} {}",
+                    media_information::spoken_duration(self.elapsed_time),
+                    self.locale.of,
+                    media_information::spoken_duration(self.total_time)
+                ));
+                info
+            });
+            self.paint_seek_bar_background(ui, background, slider_response.rect);
+            self.paint_seek_bar_marks(ui, slider_response.rect);
+            // Clicks land where the pointer is, not only drags of the handle
+            let pointer_time = slider_response.interact_pointer_pos().map(|pointer| {
+                let fraction = ((pointer.x - slider_response.rect.left())
+                    / slider_response.rect.width())
+                .clamp(0.0, 1.0);
+                if right_to_left {
+                    self.total_time.mul_f32(1.0 - fraction)
+                } else {
+                    self.total_time.mul_f32(fraction)
+                }
+            });
+            if let Some(time) = pointer_time {
+                if slider_response.clicked() {
+                    self.seek(time);
+                } else if slider_response.secondary_clicked() {
+                    self.set_ab_point(time);
+                }
+            }
+            if slider_response.drag_started() {
+                self.request_pause();
+                self.set_player_state(PlayerState::Paused);
+            }
+            if slider_response.dragged() {
+                self.elapsed_time = Duration::from_secs_f32(slider_value);
+            }
+            if slider_response.drag_stopped() {
+                if self.sync_member {
+                    self.seek(self.elapsed_time);
+                } else {
+                    self.seeks += 1;
+                }
+            }
+
+            let mut volume = self.volume.load(Ordering::Acquire);
+
+            let volume_icon = if volume > 70 {
+                self.icons.volume_high.clone()
+            } else if volume > 40 {
+                self.icons.volume_medium.clone()
+            } else if volume > 0 {
+                self.icons.volume_low.clone()
+            } else {
+                self.icons.volume_muted.clone()
+            };
+
+            let locale = &self.locale;
+            let mut mono = self.is_mono();
+            let volume_response = ui
+                .menu_button(volume_icon, |ui| {
+                    ui.add(Slider::new(&mut volume, 0..=100).vertical())
+                        .widget_info(|| WidgetInfo::slider(true, volume as f64, &locale.volume));
+                    ui.weak(volume_db_text(volume));
+                    ui.checkbox(&mut mono, &locale.mono);
+                })
+                .response;
+            volume_response.widget_info(|| {
+                WidgetInfo::labeled(
+                    WidgetType::Button,
+                    true,
+                    format!("{} {volume}%", locale.volume),
+                )
+            });
+            if volume_response.hovered() {
+                let scroll = ui.input_mut(|input| {
+                    // Keep a surrounding scroll area from scrolling as well
+                    input.smooth_scroll_delta = Vec2::ZERO;
+                    input.raw_scroll_delta.y
+                });
+                // One step for each notch of a mouse wheel
+                let notch = ui.ctx().options(|options| options.line_scroll_speed);
+                self.volume_scroll += scroll;
+                let steps = (self.volume_scroll / notch).trunc();
+                self.volume_scroll -= steps * notch;
+                volume = (volume + steps as i32 * self.volume_scroll_step).clamp(0, 100);
+                volume_response.on_hover_text(format!(
+                    "{} {volume}% ({})",
+                    locale.volume,
+                    volume_db_text(volume)
+                ));
+            } else {
+                self.volume_scroll = 0.0;
+            }
+
+            self.volume.store(volume, Ordering::Relaxed);
+            self.set_mono(mono);
+
+            if self.show_mix_controls {
+                let mut muted = self.is_muted();
+                let muted_response = ui
+                    .toggle_value(&mut muted, "M")
+                    .on_hover_text(&self.locale.mute);
+                muted_response.widget_info(|| {
+                    WidgetInfo::selected(WidgetType::Checkbox, true, muted, &self.locale.mute)
+                });
+                if muted_response.changed() {
+                    self.set_muted(muted);
+                }
+                let mut solo = self.is_solo();
+                let solo_response = ui
+                    .toggle_value(&mut solo, "S")
+                    .on_hover_text(&self.locale.solo);
+                solo_response.widget_info(|| {
+                    WidgetInfo::selected(WidgetType::Checkbox, true, solo, &self.locale.solo)
+                });
+                if solo_response.changed() {
+                    self.set_solo(solo);
+                }
+            }
+
+            if self.show_level_meters {
+                self.level_meters(ui);
+            }
+
+            let mut speed = self.speed();
+            let mut preserve_pitch = self.speed_mode() == SpeedMode::PreservePitch;
+            let mut pitch_semitones = self.pitch_semitones();
+            let speed_label = format!("{} {speed}x", self.locale.speed);
+            ui.menu_button(format!("{speed}x"), |ui| {
+                for preset in SPEED_PRESETS {
+                    ui.selectable_value(&mut speed, preset, format!("{preset}x"));
+                }
+                ui.separator();
+                ui.checkbox(&mut preserve_pitch, &self.locale.preserve_pitch);
+                ui.add(
+                    Slider::new(&mut pitch_semitones, -12.0..=12.0)
+                        .step_by(1.0)
+                        .text(&self.locale.pitch),
+                );
+            })
+            .response
+            .widget_info(|| WidgetInfo::labeled(WidgetType::Button, true, &speed_label));
+            if speed != self.speed() {
+                self.set_speed(speed);
+            }
+            self.set_speed_mode(if preserve_pitch {
+                SpeedMode::PreservePitch
+            } else {
+                SpeedMode::Resample
+            });
+            self.set_pitch_semitones(pitch_semitones);
+
+            let is_timestamped = matches!(
+                self.transcription_settings,
+                TranscriptionSettings::ShowTimeStamps
+            );
+
+            match self.transcription_settings {
+                TranscriptionSettings::None => {}
+                TranscriptionSettings::Allow
+                | TranscriptionSettings::TranscriptLabel
+                | TranscriptionSettings::ShowTimeStamps => {
+                    let more_response = ui.menu_button("…", |ui| {
+                        if ui.button(&self.locale.transcribe_audio).clicked()
+                            && self.transcript_receiver.is_none()
+                        {
+                            self.start_transcription(is_timestamped);
+                        }
+                    });
+                    more_response.response.widget_info(|| {
+                        WidgetInfo::labeled(WidgetType::Button, true, &self.locale.more)
+                    });
+                }
+            }
+
+            if let Some(receiver) = &mut self.transcript_receiver {
+                profiling::scope!("poll_transcription");
+                match receiver.try_recv() {
+                    Ok(progress) => self.transcription_progress = progress,
+                    // A transcriber that failed stops without finishing, there is nothing more to wait for
+                    Err(tokio::sync::mpsc::error::TryRecvError::Disconnected) => {
+                        log::warn!("Transcription stopped without finishing");
+                        self.transcription_progress = TranscriptionProgress::Failed;
+                    }
+                    Err(tokio::sync::mpsc::error::TryRecvError::Empty) => {}
+                }
+
+                match &self.transcription_progress {
+                    TranscriptionProgress::NoProgress => {}
+                    TranscriptionProgress::InProgress(transcription_data) => {
+                        // Pieces can arrive out of order when transcription follows playback, the transcript is
+                        // kept sorted by time so a piece seen again is found among the ones starting with it
+                        let first = self
+                            .transcript
+                            .partition_point(|data| data.time < transcription_data.time);
+                        let index = self
+                            .transcript
+                            .partition_point(|data| data.time <= transcription_data.time);
+                        // Resumed transcribers may start over, the pieces before the checkpoint are known already
+                        if transcription_data.time >= self.transcription_resume_from
+                            && !self.transcript[first..index].contains(transcription_data)
+                        {
+                            self.transcript.insert(index, transcription_data.clone());
+                        }
+                        ui.label(&self.locale.transcription_in_progress);
+                        ui.spinner();
+                    }
+                    TranscriptionProgress::Reading => {
+                        ui.label(&self.locale.transcription_in_progress);
+                        ui.spinner();
+                    }
+                    TranscriptionProgress::Finished | TranscriptionProgress::Failed => {
+                        self.transcript_receiver = None;
+                    }
+                };
+            }
+            // Transcribing again from the menu replaces the message
+            if self.transcription_progress == TranscriptionProgress::Failed {
+                ui.label(&self.locale.transcription_failed);
+            }
+
+            if let Some(custom_right) = &mut self.controls.custom_right {
+                custom_right(ui);
+            }
+        });
+        if self.ambient {
+            self.paint_ambient(ui, ambient_background, bar_response.response.rect);
+        }
+
+        self.visualization_ui(ui);
+
+        if !self.transcript_detached {
+            self.transcript_view(ui);
+        }
+    }
+
+    /// Starts reading the lyrics embedded in the file, which the next [`Player::update`] shows
+    fn load_embedded_lyrics(&mut self) {
+        if self.lyrics_loaded {
+            return;
+        }
+        self.lyrics_loaded = true;
+        let file_input = self.file_input.clone();
+        let (tx_lyrics, rx_lyrics) = mpsc::channel();
+        self.lyrics_receiver = Some(rx_lyrics);
+        worker_pool::spawn(move || {
+            let _ = tx_lyrics.send(lyrics::read_embedded(&file_input));
+        });
+    }
+
+    /// Shows the words of the transcript when the [`TranscriptionSettings`] include a label
+    fn transcript_view(&mut self, ui: &mut Ui) {
+        if self.caption_source == CaptionSource::Lyrics {
+            self.load_embedded_lyrics();
+        }
+        let showing_lyrics = self.caption_source == CaptionSource::Lyrics;
+        let shown = showing_lyrics
+            || matches!(
+                self.transcription_settings,
+                TranscriptionSettings::TranscriptLabel | TranscriptionSettings::ShowTimeStamps
+            );
+        let source = if showing_lyrics {
+            &self.lyrics
+        } else {
+            &self.transcript
+        };
+        if !shown || source.is_empty() {
+            return;
+        }
+        // Words of right to left scripts flow and wrap from the right edge even in left to right apps
+        let layout = if ui.layout().prefer_right_to_left() || is_right_to_left_text(source) {
+            Layout::right_to_left(Align::Center)
+        } else {
+            Layout::left_to_right(Align::Center)
+        };
+        let timestamped = self.transcription_settings == TranscriptionSettings::ShowTimeStamps;
+        let font = match self.transcript_style.font_size {
+            Some(size) => FontId::proportional(size),
+            None => TextStyle::Body.resolve(ui.style()),
+        };
+
+        // Words are only measured again when the transcript grows or the view changes
+        let width = ui.available_width();
+        if !self.transcript_layout.as_ref().is_some_and(|layout| {
+            layout.is_for(
+                source.len(),
+                width,
+                self.transcript_style.font_size,
+                timestamped,
+            )
+        }) {
+            profiling::scope!("transcript_layout");
+            let (entries, source_length) = self.caption_entries();
+            self.transcript_layout = Some(TranscriptLayout::new(
+                entries,
+                source_length,
+                width,
+                self.transcript_style.font_size,
+                timestamped,
+                showing_lyrics,
+                self.transcript_style.timestamp_width,
+                |text| {
+                    ui.fonts(|fonts| {
+                        fonts
+                            .layout_no_wrap(text.to_string(), font.clone(), Color32::PLACEHOLDER)
+                            .size()
+                            .x
+                    })
+                },
+            ));
+        }
+        let transcript_layout = self.transcript_layout.take().unwrap();
+        let row_height = ui.fonts(|fonts| fonts.row_height(&font));
+
+        ui.scope(|ui| {
+            ui.style_mut().spacing.item_spacing.y = self.transcript_style.line_spacing;
+            ScrollArea::vertical()
+                .max_height(self.transcript_style.max_height)
+                .auto_shrink([false, true])
+                .show_rows(ui, row_height, transcript_layout.rows.len(), |ui, rows| {
+                    let pointer = ui.input(|input| input.pointer.interact_pos());
+                    for row in &transcript_layout.rows[rows] {
+                        ui.allocate_ui_with_layout(
+                            Vec2::new(ui.available_width(), row_height),
+                            layout,
+                            |ui| {
+                                ui.style_mut().spacing.item_spacing.x = 0.0;
+                                if timestamped {
+                                    // Times of the entry in a column in front of its first row
+                                    let times = row.timestamp.map_or(String::new(), |index| {
+                                        let entry = &transcript_layout.entries[index];
+                                        format!(
+                                            "{}-{}",
+                                            self.transcript_style
+                                                .timestamp_format
+                                                .format(entry.time),
+                                            self.transcript_style
+                                                .timestamp_format
+                                                .format(entry.end)
+                                        )
+                                    });
+                                    ui.add_sized(
+                                        [self.transcript_style.timestamp_width, row_height],
+                                        Label::new(self.transcript_text(&times).weak()),
+                                    );
+                                }
+                                for (index, text) in &transcript_layout.pieces[row.pieces.clone()] {
+                                    let word = &transcript_layout.entries[*index];
+                                    self.transcript_word(ui, word, text, pointer);
+                                }
+                            },
+                        );
+                    }
+                });
+        });
+        self.transcript_layout = Some(transcript_layout);
+
+        if ui.input(|input| !input.pointer.any_down()) {
+            self.transcript_drag_anchor = None;
+        }
+        if !showing_lyrics && self.transcription_progress == TranscriptionProgress::Finished {
+            ui.label(&self.locale.end_of_transcript);
+        }
+    }
+
+    /// ``text`` in the font size of the [`TranscriptStyle`]
+    fn transcript_text(&self, text: &str) -> RichText {
+        match self.transcript_style.font_size {
+            Some(size) => RichText::new(text).size(size),
+            None => RichText::new(text),
+        }
+    }
+
+    /// Shows a single entry of the transcript, which seeks when clicked and selects when dragged across
+    fn transcript_word(
+        &mut self,
+        ui: &mut Ui,
+        word: &TranscriptionData,
+        text: &str,
+        pointer: Option<Pos2>,
+    ) {
+        let span = (word.time, word.end.max(word.time));
+        let elapsed = self.elapsed_time;
+        // Highlight the word being spoken and the selected words
+        let mut text = self.transcript_text(text);
+        if word.time <= elapsed && elapsed < word.end {
+            text = text.background_color(ui.visuals().selection.bg_fill);
+        } else if self
+            .transcript_selection
+            .is_some_and(|(start, end)| start <= word.time && span.1 <= end)
+        {
+            text = text.background_color(ui.visuals().selection.bg_fill.gamma_multiply(0.4));
+        }
+        let response = ui.add(Label::new(text).sense(Sense::click_and_drag()));
+
+        // Dragging across words selects the time they span
+        if response.drag_started() {
+            self.transcript_drag_anchor = Some(span);
+        }
+        if let Some(anchor) = self.transcript_drag_anchor
+            && pointer.is_some_and(|pointer| response.rect.contains(pointer))
+        {
+            self.transcript_selection = Some((anchor.0.min(span.0), anchor.1.max(span.1)));
+        }
+        if let Some(selection) = self.transcript_selection {
+            response.context_menu(|ui| {
+                if ui.button(&self.locale.loop_selection).clicked() {
+                    self.set_ab_loop(Some(selection));
+                    self.seek(selection.0);
+                    ui.close_menu();
+                }
+                if ui.button(&self.locale.clear_selection).clicked() {
+                    self.transcript_selection = None;
+                    ui.close_menu();
+                }
+            });
+        }
+        if response.clicked() {
+            match self.transcript_seek_behavior {
+                SeekBehavior::Pause => {
+                    self.request_pause();
+                    self.elapsed_time = word.time;
+                    if self.sync_member {
+                        self.seek(word.time);
+                    } else {
+                        self.seeks += 1;
+                    }
+                }
+                SeekBehavior::KeepPlaying => self.seek(word.time),
+            }
+        }
+    }
+
+    /// Takes the levels measured since the last frame, letting the shown levels fall back slowly
+    fn update_levels(&mut self, ui: &Ui) {
+        if self.audio_tap.take_clipped() {
+            self.clipped_at = Some(self.clock.now());
+        }
+        let measured = self.audio_tap.take_levels();
+        if !measured.is_empty() && measured.len() != self.levels.len() {
+            self.levels = vec![ChannelLevel::default(); measured.len()];
+        }
+        // Falls back by roughly 30 dB per second so short peaks stay readable
+        let decay = 10.0_f32.powf(-1.5 * ui.input(|input| input.stable_dt));
+        for (shown, level) in self.levels.iter_mut().zip(measured) {
+            shown.peak = level.peak.max(shown.peak * decay);
+            shown.rms = level.rms.max(shown.rms * decay);
+        }
+    }
+
+    /// Gradient behind the control bar that slowly drifts in hue and brightens with the loudness, see [`Player::set_ambient`]
+    fn paint_ambient(&self, ui: &Ui, index: ShapeIdx, rect: Rect) {
+        let loudness = self
+            .levels
+            .iter()
+            .map(|level| level.rms)
+            .fold(0.0, f32::max)
+            .sqrt()
+            .min(1.0);
+        let drift = (ui.input(|input| input.time) * 0.02).fract() as f32;
+        let base = Hsva::from(ui.visuals().selection.bg_fill);
+        let stop = |offset: f32, alpha: f32| -> Color32 {
+            Hsva::new((base.h + drift + offset).fract(), 0.6, 0.8, alpha).into()
+        };
+        let rect = rect.expand(ui.spacing().item_spacing.y / 2.0);
+        let edge = stop(0.0, 0.06 + 0.12 * loudness);
+        let middle = stop(0.12, 0.1 + 0.3 * loudness);
+        let mut mesh = Mesh::default();
+        for (index, x) in [rect.left(), rect.center().x, rect.right()]
+            .into_iter()
+            .enumerate()
+        {
+            let color = if index == 1 { middle } else { edge };
+            mesh.colored_vertex(pos2(x, rect.top()), color);
+            mesh.colored_vertex(pos2(x, rect.bottom()), color);
+        }
+        for column in 0..2 {
+            let first = column * 2;
+            mesh.add_triangle(first, first + 1, first + 2);
+            mesh.add_triangle(first + 1, first + 2, first + 3);
+        }
+        ui.painter().set(index, mesh);
+    }
+
+    /// Displays a small vertical meter for each channel, RMS as a bar and the peak as a line
+    fn level_meters(&mut self, ui: &mut Ui) {
+        let channels = self.levels.len().max(1) as f32;
+        let bar_width = 5.0;
+        let size = Vec2::new(
+            channels * bar_width + (channels - 1.0) * 2.0,
+            ui.spacing().interact_size.y,
+        );
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+        let painter = ui.painter_at(rect);
+        let clipping = self
+            .clipped_at
+            .is_some_and(|at| self.clock.now().saturating_duration_since(at) < CLIP_HOLD);
+        let (light, rect) = rect.split_top_bottom_at_y(rect.top() + 3.0);
+        let light_color = if clipping {
+            Color32::RED
+        } else {
+            ui.visuals().extreme_bg_color
+        };
+        painter.rect_filled(light.shrink2(Vec2::new(0.0, 0.5)), 1.0, light_color);
+        let to_height = |amplitude: f32| {
+            let db = 20.0 * amplitude.max(1e-6).log10();
+            ((db + 60.0) / 60.0).clamp(0.0, 1.0) * rect.height()
+        };
+        for (channel, level) in self.levels.iter().enumerate() {
+            let left = rect.left() + channel as f32 * (bar_width + 2.0);
+            let bar = Rect::from_min_max(
+                [left, rect.top()].into(),
+                [left + bar_width, rect.bottom()].into(),
+            );
+            painter.rect_filled(bar, 1.0, ui.visuals().extreme_bg_color);
+            let color = if level.peak >= 1.0 {
+                Color32::RED
+            } else if level.peak >= 0.5 {
+                Color32::YELLOW
+            } else {
+                Color32::GREEN
+            };
+            let rms_top = bar.bottom() - to_height(level.rms);
+            painter.rect_filled(
+                Rect::from_min_max([bar.left(), rms_top].into(), bar.max),
+                1.0,
+                color.gamma_multiply(0.7),
+            );
+            let peak_y = bar.bottom() - to_height(level.peak);
+            painter.hline(bar.x_range(), peak_y, (1.0, color));
+        }
+
+        response.on_hover_ui(|ui| {
+            if clipping {
+                ui.colored_label(Color32::RED, &self.locale.clipping);
+            }
+            for (channel, level) in self.levels.iter().enumerate() {
+                ui.label(format!(
+                    "{} {}: {} {:.1} dB, {} {:.1} dB",
+                    self.locale.channel,
+                    channel + 1,
+                    self.locale.peak,
+                    20.0 * level.peak.max(1e-6).log10(),
+                    self.locale.rms,
+                    20.0 * level.rms.max(1e-6).log10()
+                ));
+            }
+        });
+    }
+
+    /// Receives finished analysis and starts computing whatever the [`SeekBarBackground`] is missing
+    fn update_seek_bar_analysis(&mut self) {
+        if let Some(receiver) = &self.analysis_receiver {
+            match receiver.try_recv() {
+                Ok(SeekBarAnalysis::Waveform(peaks)) => self.waveform_peaks = Some(peaks),
+                Ok(SeekBarAnalysis::Spectrogram(spectrogram)) => {
+                    self.spectrogram = Some(spectrogram)
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => self.analysis_failed = true,
+            }
+            self.analysis_receiver = None;
+        }
+        if self.analysis_failed {
+            return;
+        }
+
+        let missing = match self.seek_bar_background {
+            SeekBarBackground::Spectrogram if self.spectrogram.is_none() => {
+                Some(SeekBarBackground::Spectrogram)
+            }
+            _ if self.waveform_peaks.is_none()
+                && (self.timeline_shown
+                    || self.seek_bar_background == SeekBarBackground::Waveform) =>
+            {
+                Some(SeekBarBackground::Waveform)
+            }
+            _ => None,
+        };
+        if let Some(background) = missing {
+            let file_input = self.file_input.clone();
+            let cache = self.analysis_cache.clone();
+            let (tx_analysis, rx_analysis) = mpsc::channel();
+            self.analysis_receiver = Some(rx_analysis);
+            worker_pool::spawn(
+                move || match analyse_seek_bar(background, file_input, cache) {
+                    Ok(analysis) => {
+                        let _ = tx_analysis.send(analysis);
+                    }
+                    Err(error) => {
+                        log::error!("Could not analyse the file for the seek bar: {error}")
+                    }
+                },
+            );
+        }
+    }
+
+    /// Draws a tick for every marker and the A/B points over the seek bar, shading the region between the A/B points
+    /// and the silence skipped by [`Player::set_trim_silence`]
+    fn paint_seek_bar_marks(&self, ui: &Ui, rect: Rect) {
+        let total = self.total_time.as_secs_f32().max(0.001);
+        let right_to_left = ui.layout().prefer_right_to_left();
+        let time_to_x = |time: Duration| {
+            let offset = time.as_secs_f32() / total * rect.width();
+            if right_to_left {
+                rect.right() - offset
+            } else {
+                rect.left() + offset
+            }
+        };
+        if let Some((start, end)) = self.trimmed_bounds() {
+            let shade = ui.visuals().extreme_bg_color.gamma_multiply(0.6);
+            for (from, to) in [(Duration::ZERO, start), (end, self.total_time)] {
+                if to > from {
+                    ui.painter().rect_filled(
+                        Rect::from_x_y_ranges(time_to_x(from)..=time_to_x(to), rect.y_range()),
+                        0.0,
+                        shade,
+                    );
+                }
+            }
+        }
+        let tick = Stroke::new(1.0, ui.visuals().weak_text_color());
+        for marker in &self.markers {
+            ui.painter()
+                .vline(time_to_x(marker.time), rect.y_range(), tick);
+        }
+        let color = ui.visuals().selection.stroke.color;
+        if let Some((start, end)) = self.ab_loop() {
+            ui.painter().rect_filled(
+                Rect::from_x_y_ranges(time_to_x(start)..=time_to_x(end), rect.y_range()),
+                0.0,
+                color.gamma_multiply(0.2),
+            );
+        }
+        for time in [self.ab_points.0, self.ab_points.1].into_iter().flatten() {
+            ui.painter()
+                .vline(time_to_x(time), rect.y_range(), Stroke::new(2.0, color));
+        }
+    }
+
+    /// Fills the shape reserved at ``index`` with the [`SeekBarBackground`] stretched over ``rect``
+    fn paint_seek_bar_background(&mut self, ui: &Ui, index: ShapeIdx, rect: Rect) {
+        self.update_seek_bar_analysis();
+        let right_to_left = ui.layout().prefer_right_to_left();
+        match self.seek_bar_background {
+            SeekBarBackground::None => {}
+            SeekBarBackground::Waveform => {
+                let Some(peaks) = &self.waveform_peaks else {
+                    return;
+                };
+                let columns = (rect.width() as usize).max(1);
+                let stroke = Stroke::new(1.0, ui.visuals().weak_text_color().gamma_multiply(0.6));
+                let shapes = (0..columns)
+                    .map(|column| {
+                        let start = (column * peaks.len() / columns).min(peaks.len());
+                        let end = ((column + 1) * peaks.len() / columns).clamp(start, peaks.len());
+                        let peak = peaks[start..end]
+                            .iter()
+                            .fold(0.0_f32, |peak, value| peak.max(*value));
+                        let x = if right_to_left {
+                            rect.right() - column as f32 - 0.5
+                        } else {
+                            rect.left() + column as f32 + 0.5
+                        };
+                        let half_height = (peak * rect.height() / 2.0).max(0.5);
+                        Shape::line_segment(
+                            [
+                                pos2(x, rect.center().y - half_height),
+                                pos2(x, rect.center().y + half_height),
+                            ],
+                            stroke,
+                        )
+                    })
+                    .collect();
+                ui.painter().set(index, Shape::Vec(shapes));
+            }
+            SeekBarBackground::Spectrogram => {
+                let Some(spectrogram) = &self.spectrogram else {
+                    return;
+                };
+                let texture = self.spectrogram_texture.get_or_insert_with(|| {
+                    profiling::scope!("upload_spectrogram_texture");
+                    CachedTexture(ui.ctx().load_texture(
+                        "spectrogram",
+                        spectrogram_image(spectrogram),
+                        TextureOptions::LINEAR,
+                    ))
+                });
+                ui.painter().set(
+                    index,
+                    Shape::image(
+                        texture.0.id(),
+                        rect,
+                        if right_to_left {
+                            Rect::from_min_max(pos2(1.0, 0.0), pos2(0.0, 1.0))
+                        } else {
+                            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0))
+                        },
+                        Color32::WHITE,
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Feeds newly played audio to the [`Visualizer`] and displays it across the width of the player
+    ///
+    /// Clicking the visualization plays / pauses, double clicking toggles fullscreen
+    fn visualization_ui(&mut self, ui: &mut Ui) {
+        let (samples, channels, sample_rate) = self.audio_tap.new_samples(&mut self.tap_cursor);
+        if let Some(visualizer) = &mut self.visualizer {
+            if !samples.is_empty() {
+                visualizer.process(&samples, channels, sample_rate);
+            }
+            let (rect, response) = ui.allocate_exact_size(
+                Vec2::new(ui.available_width(), visualizer.height()),
+                Sense::click(),
+            );
+            visualizer.paint(ui, rect);
+            // The first click of a double click already toggled playback, so the pair leaves it as it was
+            if response.clicked() {
+                self.toggle_playback();
+            }
+            if response.double_clicked() {
+                let fullscreen = ui
+                    .ctx()
+                    .input(|input| input.viewport().fullscreen.unwrap_or(false));
+                ui.ctx()
+                    .send_viewport_cmd(ViewportCommand::Fullscreen(!fullscreen));
+            }
+        }
+    }
+
+    /// How soon the player has to be drawn again, ``None`` when nothing changes until the next input
+    fn repaint_interval(&self) -> Option<Duration> {
+        if self.player_state == PlayerState::Playing {
+            let every_frame = match self.repaint_policy {
+                RepaintPolicy::Adaptive => {
+                    self.visualizer.is_some()
+                        || self.show_level_meters
+                        || self.ambient
+                        || self.timeline_shown
+                        || !matches!(
+                            self.time_format,
+                            TimeFormat::Auto | TimeFormat::HoursMinutesSeconds
+                        )
+                }
+                RepaintPolicy::EveryFrame => true,
+                RepaintPolicy::NextSecond => false,
+            };
+            if every_frame {
+                Some(Duration::ZERO)
+            } else {
+                // Only the seconds of the time label change, wake up just after the next one starts
+                let mut until_next = Duration::from_secs(1)
+                    - Duration::from_nanos(self.elapsed_time.subsec_nanos() as u64);
+                if self.show_countdown {
+                    // The countdown turns over when the remaining time passes a whole second
+                    let remaining = self.remaining_time().subsec_nanos() as u64;
+                    if remaining > 0 {
+                        until_next = until_next.min(Duration::from_nanos(remaining));
+                    }
+                }
+                if matches!(
+                    self.transcription_settings,
+                    TranscriptionSettings::TranscriptLabel | TranscriptionSettings::ShowTimeStamps
+                ) {
+                    // The highlighted word of the transcript moves on
+                    let elapsed = self.elapsed_time;
+                    if let Some(boundary) = self
+                        .transcript
+                        .iter()
+                        .flat_map(|word| [word.time, word.end])
+                        .filter(|boundary| *boundary > elapsed)
+                        .min()
+                    {
+                        until_next = until_next.min(boundary - elapsed);
+                    }
+                }
+                if self.remaining_time() == Duration::ZERO {
+                    // Past the estimated end, check often for the audio running out
+                    until_next = until_next.min(Duration::from_millis(50));
+                }
+                Some(until_next.div_f32(self.speed().max(0.01)) + Duration::from_millis(5))
+            }
+        } else if self.transcript_receiver.is_some()
+            || self.analysis_receiver.is_some()
+            || self.bounds_receiver.is_some()
+            || self.poster_receiver.is_some()
+            || self.media_receiver.is_some()
+            || self.lyrics_receiver.is_some()
+            || self.preference_receiver.is_some()
+        {
+            Some(Duration::from_millis(100))
+        } else {
+            None
+        }
+    }
+
+    /// Displays the internal state of the player in a grid
+    fn debug_panel(&self, ui: &mut Ui) {
+        let (streaming, played, buffered) = self.audio_tap.stream_stats();
+        let stats = self.stats();
+        let sink_position = self.start_time + played.mul_f32(self.speed());
+        let drift = sink_position.as_secs_f64() - self.elapsed_time.as_secs_f64();
+        let queued = self
+            .transcript_receiver
+            .as_ref()
+            .map_or(0, |receiver| receiver.len());
+        Grid::new(ui.id().with("debug_panel"))
+            .striped(true)
+            .show(ui, |ui| {
+                let mut row = |name: &str, value: String| {
+                    ui.label(name);
+                    ui.monospace(value);
+                    ui.end_row();
+                };
+                row("State", format!("{:?}", self.player_state));
+                row(
+                    "Stopwatch",
+                    format!("{:.3}s", self.elapsed_time.as_secs_f64()),
+                );
+                if streaming {
+                    row(
+                        "Sink position",
+                        format!("{:.3}s", sink_position.as_secs_f64()),
+                    );
+                    row("Drift", format!("{:+.1}ms", drift * 1000.0));
+                    row(
+                        "Buffered",
+                        format!("{:.1}ms", buffered.as_secs_f64() * 1000.0),
+                    );
+                }
+                row(
+                    "Audio thread",
+                    if self.sync_member {
+                        "Owned by sync group".to_string()
+                    } else if streaming {
+                        "Running".to_string()
+                    } else {
+                        "Stopped".to_string()
+                    },
+                );
+                row(
+                    "Decoding",
+                    format!(
+                        "{:.2}ms per block, {} slow blocks, {} errors",
+                        stats.average_decode_time.as_secs_f64() * 1000.0,
+                        stats.slow_blocks,
+                        stats.decode_errors
+                    ),
+                );
+                let progress = match self.transcription_progress {
+                    TranscriptionProgress::NoProgress => "Not started",
+                    TranscriptionProgress::Reading | TranscriptionProgress::InProgress(_) => {
+                        "Running"
+                    }
+                    TranscriptionProgress::Finished => "Finished",
+                    TranscriptionProgress::Failed => "Failed",
+                };
+                row("Transcription", format!("{progress}, {queued} queued"));
+            });
+    }
+
+    /// Displays the time left in large text
+    fn countdown_ui(&self, ui: &mut Ui) {
+        let remaining = self.remaining_time();
+        let color =
+            if remaining <= Duration::from_secs(10) && self.player_state != PlayerState::Ended {
+                ui.visuals().error_fg_color
+            } else {
+                ui.visuals().strong_text_color()
+            };
+        ui.label(
+            RichText::new(format!("-{}", self.time_format.format(remaining)))
+                .monospace()
+                .size(48.0)
+                .color(color),
+        );
+    }
+
+    // TODO fix this eventually
+    fn display_player(&mut self, ui: &mut Ui) {
+        if self.controls.touch_mode {
+            // Roughly the size of a fingertip
+            let spacing = ui.spacing_mut();
+            spacing.interact_size = Vec2::splat(44.0);
+            spacing.button_padding = Vec2::new(14.0, 10.0);
+            spacing.item_spacing.x = spacing.item_spacing.x.max(12.0);
+            spacing.slider_rail_height = 12.0;
+            if let Some(button) = ui.style_mut().text_styles.get_mut(&TextStyle::Button) {
+                button.size *= 1.5;
+            }
+        }
+        if self.show_countdown {
+            self.countdown_ui(ui);
+        }
+        if !self.has_played || self.player_state == PlayerState::Ended {
+            self.poster_ui(ui);
+        }
+        if let Some(info) = &self.track_info {
+            let text: Vec<&str> = [&info.title, &info.artist]
+                .into_iter()
+                .flatten()
+                .map(String::as_str)
+                .collect();
+            if !text.is_empty() {
+                ui.label(RichText::new(text.join(" — ")).strong());
+            }
+        }
+        match self.media_type {
+            MediaType::Audio => self.control_bar(ui),
+            MediaType::Video => self.control_bar(ui),
+            MediaType::Error => panic!("Can't display due to invalid file type"),
+        }
+        if self.show_debug_panel {
+            self.debug_panel(ui);
+        }
+    }
+
+    /// Starts looking for the cover art of the file, or the color of the artwork placeholder when it has none, which
+    /// the next [`Player::update`] shows
+    fn load_poster(&mut self) {
+        if self.poster_loaded {
+            return;
+        }
+        self.poster_loaded = true;
+        if matches!(self.media_type, MediaType::Audio) {
+            let file_input = self.file_input.clone();
+            let (tx_poster, rx_poster) = mpsc::channel();
+            self.poster_receiver = Some(rx_poster);
+            worker_pool::spawn(move || {
+                let _ = tx_poster.send(find_poster(file_input));
+            });
+        }
+    }
+
+    /// Shows what [`find_poster`] found, unless a poster was set in the meantime
+    fn set_found_poster(
+        &mut self,
+        poster: Option<ImageSource<'static>>,
+        placeholder_color: Option<Color32>,
+    ) {
+        if self.poster.is_none() {
+            self.poster = poster;
+        }
+        self.placeholder_color = placeholder_color;
+    }
+
+    /// Does the slow parts of getting ready to play ahead of time, for a [`crate::playlist::Playlist`] preparing its next entry
+    ///
+    /// Loads the cover art and decodes the first ``audio`` of a local file once, which brings it into the cache of the system
+    pub(crate) fn prefetch(&mut self, audio: Duration) {
+        if !self.poster_loaded && matches!(self.media_type, MediaType::Audio) {
+            self.poster_loaded = true;
+            let (poster, placeholder_color) = find_poster(self.file_input.clone());
+            self.set_found_poster(poster, placeholder_color);
+        }
+        if audio > Duration::ZERO
+            && matches!(self.media_type, MediaType::Audio)
+            && matches!(self.file_input, InputMode::FilePath(_))
+            && let Ok(source) = media_information::open_source(self.file_input.clone())
+        {
+            source.take_duration(audio).for_each(drop);
+        }
+    }
+
+    /// Shows the poster, looking for the cover art of the file the first time, see [`Player::set_poster`]
+    fn poster_ui(&mut self, ui: &mut Ui) {
+        self.load_poster();
+        if let Some(poster) = &self.poster {
+            ui.add(
+                Image::new(poster.clone())
+                    .max_height(POSTER_HEIGHT * self.player_scale)
+                    .maintain_aspect_ratio(true),
+            );
+        } else if self.artwork_placeholder
+            && let Some(color) = self.placeholder_color
+        {
+            let size = Vec2::splat(POSTER_HEIGHT * self.player_scale);
+            let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+            ui.painter().rect_filled(rect, size.x / 16.0, color);
+            ui.painter().text(
+                rect.center(),
+                Align2::CENTER_CENTER,
+                &self.icons.artwork,
+                FontId::proportional(size.y * 0.4),
+                Color32::from_white_alpha(200),
+            );
+        }
+    }
+
+    /// Audio playback
+    ///
+    /// A stream to play audio is started. It is only stopped when the file reaches the end or the [`Player`] is paused
+    fn audio_stream(&mut self) {
+        if self.playback_guard {
+            let playback_source = self.playback_source();
+            let dsp_settings = Arc::clone(&self.dsp_settings);
+            let stop_audio = Arc::clone(&self.stop_playback);
+            let volume = Arc::clone(&self.volume);
+            let muted = Arc::clone(&self.muted);
+            let solo = Arc::clone(&self.solo);
+            let audio_context = self.audio_context();
+            let audio_tap = Arc::clone(&self.audio_tap);
+            let audio_failed = Arc::clone(&self.audio_failed);
+            let audio_moved = Arc::clone(&self.audio_moved);
+            thread::spawn(move || {
+                profiling::register_thread!("egui_player audio");
+                audio_tap.start_stream();
+                let playback = audio_context.play(Box::new(playback_source.build()));
+                log::debug!("Audio stream started");
+                loop {
+                    let audible = audio_context
+                        .is_audible(muted.load(Ordering::Acquire), solo.load(Ordering::Acquire));
+                    if audible {
+                        let factor = volume.load(Ordering::Acquire) as f32 / 100.0;
+                        dsp_settings.set_output_volume(factor);
+                        playback.set_volume(factor);
+                    } else {
+                        playback.set_volume(0.0);
+                    }
+                    audio_tap.record_played(playback.position());
+                    if stop_audio.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if playback.has_failed() {
+                        log::warn!("Audio output failed, stopping playback");
+                        audio_failed.store(true, Ordering::Release);
+                        break;
+                    }
+                    if playback.has_moved() {
+                        log::debug!("Audio output moved to another device");
+                        audio_moved.store(true, Ordering::Release);
+                        break;
+                    }
+                    thread::sleep(AUDIO_POLL_INTERVAL);
+                }
+                audio_tap.end_stream();
+            });
+        }
+    }
+
+    /// Captures what is needed to build the audio of this player starting at the current time
+    fn playback_source(&self) -> PlaybackSource {
+        PlaybackSource {
+            file_input: self.file_input.clone(),
+            start_at: self.elapsed_time,
+            dsp_settings: Arc::clone(&self.dsp_settings),
+            effects: Arc::clone(&self.effects),
+            audio_tap: Arc::clone(&self.audio_tap),
+            audio_left: Arc::clone(&self.audio_left),
+            read_ahead: self.read_ahead,
+        }
+    }
+
+    /// Starts playing as part of a [`crate::sync_group::SyncGroup`], returns the source that the group mixes with the other members
+    pub(crate) fn play_synced(&mut self) -> SyncedSource {
+        self.start_play_time();
+        self.set_player_state(PlayerState::Playing);
+        self.start_playback = true;
+        self.stop_playback = Arc::new(AtomicBool::new(false));
+        self.audio_left = Arc::new(AtomicUsize::new(1));
+        SyncedSource {
+            playback_source: self.playback_source(),
+            volume: Arc::clone(&self.volume),
+            muted: Arc::clone(&self.muted),
+            solo: Arc::clone(&self.solo),
+            audio_context: self.audio_context(),
+            stop_audio: Arc::clone(&self.stop_playback),
+        }
+    }
+
+    /// Pauses a [`crate::sync_group::SyncGroup`] member, keeping the exact time it was paused at
+    pub(crate) fn pause_synced(&mut self) {
+        if self.player_state == PlayerState::Playing {
+            let elapsed = self.get_elapsed_time();
+            self.elapsed_time = self.clamp_to_duration(elapsed);
+            self.stopwatch_instant = None;
+            self.pause_player();
+        }
+    }
+
+    pub(crate) fn is_playing(&self) -> bool {
+        self.player_state == PlayerState::Playing
+    }
+
+    /// Starts the audio again from [`Player::elapsed_time`], which does not count as a change of state for the hook
+    fn restart_playback(&mut self) {
+        self.stop_audio();
+        self.stopwatch_instant = None;
+        self.start_audio();
+    }
+
+    fn set_player_state(&mut self, state: PlayerState) {
+        self.player_state = state;
+        self.has_played |= state == PlayerState::Playing;
+        self.report_state();
+    }
+
+    /// Runs the hook of [`Player::on_event`] for the events the audio stream reported since the last time
+    fn report_events(&mut self) {
+        let events = self.audio_tap.take_events();
+        if let Some(event_hook) = &mut self.event_hook {
+            for event in &events {
+                (event_hook.0)(event);
+            }
+        }
+    }
+
+    /// Runs the hook of [`Player::on_state_change`] if the state changed since it last ran
+    fn report_state(&mut self) {
+        if self.player_state != self.reported_state {
+            let previous = mem::replace(&mut self.reported_state, self.player_state);
+            if let Some(state_hook) = &mut self.state_hook {
+                (state_hook.0)(previous, self.player_state);
+            }
+        }
+    }
+
+    pub(crate) fn has_ended(&self) -> bool {
+        self.player_state == PlayerState::Ended
+    }
+
+    /// Whether a [`crate::playlist::Playlist`] should move on from this player, see [`OnEnd::Advance`]
+    pub(crate) fn should_advance(&self) -> bool {
+        self.has_ended() && matches!(self.on_end, OnEnd::Advance)
+    }
+
+    /// What the play / pause button does
+    fn toggle_playback(&mut self) {
+        match self.player_state {
+            // Pausing the player
+            PlayerState::Playing => {
+                self.request_pause();
+            }
+            // Playing the player
+            PlayerState::Paused => {
+                self.request_play();
+            }
+            // Restarting the player
+            PlayerState::Ended => {
+                self.seek(Duration::ZERO);
+                self.request_play();
+            }
+            // Trying the audio output again
+            PlayerState::Error => {
+                self.play_player();
+            }
+        }
+    }
+
+    /// Contents of the menu shown when right clicking the player
+    fn context_menu_ui(&mut self, ui: &mut Ui) {
+        let mut looping = self.is_looping();
+        if ui
+            .checkbox(&mut looping, &self.locale.loop_playback)
+            .changed()
+        {
+            self.set_looping(looping);
+        }
+        if self.ab_points != (None, None) && ui.button(&self.locale.clear_ab_loop).clicked() {
+            self.set_ab_loop(None);
+            ui.close_menu();
+        }
+        ui.menu_button(self.locale.speed.clone(), |ui| {
+            let mut speed = self.speed();
+            for preset in SPEED_PRESETS {
+                ui.selectable_value(&mut speed, preset, format!("{preset}x"));
+            }
+            if speed != self.speed() {
+                self.set_speed(speed);
+            }
+        });
+        if ui.button(&self.locale.copy_timestamp).clicked() {
+            ui.ctx()
+                .copy_text(self.time_format.format(self.elapsed_time));
+            ui.close_menu();
+        }
+        if !self.transcript.is_empty() || !self.lyrics.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(&self.locale.caption_delay);
+                let mut offset = self.caption_offset;
+                ui.add(
+                    DragValue::new(&mut offset)
+                        .range(-MAX_CAPTION_OFFSET..=MAX_CAPTION_OFFSET)
+                        .speed(10)
+                        .suffix(" ms"),
+                );
+                if offset != self.caption_offset {
+                    self.set_caption_offset(offset);
+                }
+            });
+        }
+        if let Some(mut context_menu) = self.context_menu.take() {
+            ui.separator();
+            (context_menu.0)(ui, self);
+            // The entries may have replaced themselves
+            self.context_menu.get_or_insert(context_menu);
+        }
+    }
+
+    /// Runs the actions of every bound key that was pressed
+    fn handle_key_bindings(&mut self, ui: &Ui, hovered: bool) {
+        if (self.key_bindings.require_hover && !hovered) || ui.ctx().wants_keyboard_input() {
+            return;
+        }
+        // Shortcuts with more modifiers go first, so plain keys do not also catch their shift variants
+        let mut bindings = self.key_bindings.bindings().to_vec();
+        bindings.sort_by_key(|(shortcut, _)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse(
+                [
+                    modifiers.alt,
+                    modifiers.ctrl,
+                    modifiers.shift,
+                    modifiers.command,
+                ]
+                .into_iter()
+                .filter(|pressed| *pressed)
+                .count(),
+            )
+        });
+        let actions: Vec<PlayerAction> = ui.input_mut(|input| {
+            bindings
+                .iter()
+                .filter(|(shortcut, _)| input.consume_shortcut(shortcut))
+                .map(|(_, action)| *action)
+                .collect()
+        });
+        for action in actions {
+            self.run_action(action);
+        }
+    }
+
+    /// Does what a key bound to ``action`` would do, handy to drive the player from your own UI
+    pub fn run_action(&mut self, action: PlayerAction) {
+        let elapsed = self.get_elapsed_time();
+        match action {
+            PlayerAction::PlayPause => self.toggle_playback(),
+            PlayerAction::SeekForward(amount) => self.seek(elapsed + amount),
+            PlayerAction::SeekBackward(amount) => self.seek(elapsed.saturating_sub(amount)),
+            PlayerAction::VolumeUp | PlayerAction::VolumeDown => {
+                let step = if action == PlayerAction::VolumeUp {
+                    self.volume_scroll_step
+                } else {
+                    -self.volume_scroll_step
+                };
+                let volume = self.volume.load(Ordering::Acquire);
+                self.volume
+                    .store((volume + step).clamp(0, 100), Ordering::Release);
+            }
+            PlayerAction::ToggleMute => self.set_muted(!self.is_muted()),
+            PlayerAction::NextChapter => {
+                let next = self
+                    .markers
+                    .iter()
+                    .map(|marker| marker.time)
+                    .filter(|time| *time > elapsed + Duration::from_millis(10))
+                    .min();
+                if let Some(time) = next {
+                    self.seek(time);
+                }
+            }
+            PlayerAction::PreviousChapter => {
+                // Like most players, going back close to the start of a chapter goes to the one before it
+                let previous = self
+                    .markers
+                    .iter()
+                    .map(|marker| marker.time)
+                    .filter(|time| *time + Duration::from_secs(1) < elapsed)
+                    .max();
+                self.seek(previous.unwrap_or(Duration::ZERO));
+            }
+        }
+    }
+
+    /// Plays the player, or asks its [`crate::sync_group::SyncGroup`] to play every member
+    fn request_play(&mut self) {
+        if self.sync_member {
+            self.sync_requests.push(SyncRequest::Play);
+        } else {
+            self.play_player();
+        }
+    }
+
+    /// Pauses the player, or asks its [`crate::sync_group::SyncGroup`] to pause every member
+    fn request_pause(&mut self) {
+        if self.sync_member {
+            self.sync_requests.push(SyncRequest::Pause);
+        } else {
+            self.pause_player();
+        }
+    }
+
+    /// Starts visual/ audio stream by redirecting to the correct function
+    fn start_stream(&mut self) {
+        match self.media_type {
+            MediaType::Audio => self.audio_stream(),
+            MediaType::Video => todo!(),
+            MediaType::Error => todo!(),
+        }
+    }
+
+    pub(crate) fn play_player(&mut self) {
+        log::debug!("Starting playback at {:?}", self.elapsed_time);
+        self.set_player_state(PlayerState::Playing);
+        self.start_audio();
+    }
+
+    /// Starts the audio from [`Player::elapsed_time`] without changing the [`PlayerState`]
+    fn start_audio(&mut self) {
+        self.start_play_time();
+        self.start_playback = true;
+        self.playback_guard = true;
+        self.stop_playback = Arc::new(AtomicBool::new(false));
+        self.audio_left = Arc::new(AtomicUsize::new(1));
+        self.audio_failed = Arc::new(AtomicBool::new(false));
+        self.audio_moved = Arc::new(AtomicBool::new(false));
+        self.start_stream();
+    }
+
+    pub(crate) fn pause_player(&mut self) {
+        if self.player_state == PlayerState::Playing {
+            log::debug!("Stopping playback at {:?}", self.get_elapsed_time());
+        }
+        self.stop_audio();
+        self.set_player_state(PlayerState::Paused);
+    }
+
+    /// Stops the audio without changing the [`PlayerState`]
+    fn stop_audio(&mut self) {
+        if let Some(started) = self.play_started.take() {
+            self.play_time += self.clock.now().saturating_duration_since(started);
+        }
+        self.start_playback = false;
+        self.stop_playback.swap(true, Ordering::Relaxed);
+    }
+
+    fn start_play_time(&mut self) {
+        if self.play_started.is_none() {
+            self.play_started = Some(self.clock.now());
+        }
+    }
+
+    fn get_elapsed_time(&mut self) -> Duration {
         match self.stopwatch_instant {
-            Some(instant) => instant.elapsed() + self.start_time,
+            Some(instant) => {
+                self.clock
+                    .now()
+                    .saturating_duration_since(instant)
+                    .mul_f32(self.speed())
+                    + self.start_time
+            }
             None => self.elapsed_time,
         }
     }
@@ -383,7 +3479,7 @@ impl Player {
     fn setup_stopwatch(&mut self) {
         self.elapsed_time = self.get_elapsed_time();
         if self.start_playback {
-            self.stopwatch_instant = Some(Instant::now());
+            self.stopwatch_instant = Some(self.clock.now());
             self.start_time = self.elapsed_time;
             self.start_playback = false;
         }
@@ -396,16 +3492,477 @@ impl Player {
     fn add_contents(&mut self, ui: &mut Ui) -> Response {
         self.set_player_scale(self.player_scale);
         let (rect, response) = ui.allocate_exact_size(self.player_size, Sense::click());
+        self.update();
         if ui.is_rect_visible(rect) {
-            self.setup_stopwatch();
-            self.display_player(ui);
-            ui.ctx().request_repaint_after(Duration::from_millis(10));
+            let sense = if self.controls.touch_mode || self.controls.gestures {
+                Sense::click_and_drag()
+            } else {
+                Sense::click()
+            };
+            let player_response = ui
+                .scope_builder(UiBuilder::new().sense(sense), |ui| self.display_player(ui))
+                .response;
+            if self.controls.touch_mode || self.controls.gestures {
+                self.surface_gestures(ui, &player_response);
+            }
+            self.skip_toast(ui, player_response.rect);
+            player_response.context_menu(|ui| self.context_menu_ui(ui));
+            self.handle_key_bindings(ui, player_response.contains_pointer());
+            if let Some(delay) = self.save_preferences() {
+                ui.ctx().request_repaint_after(delay);
+            }
+            if let Some(interval) = self.repaint_interval() {
+                ui.ctx().request_repaint_after(interval);
+            }
+        }
+        response
+    }
+
+    /// Taps, swipes and drags on the empty parts of the player, see [`ControlsConfig::touch_mode`] and [`ControlsConfig::gestures`]
+    fn surface_gestures(&mut self, ui: &Ui, response: &Response) {
+        if self.controls.touch_mode && response.clicked() {
+            self.toggle_playback();
+        }
+        if response.dragged() {
+            self.surface_drag += response.drag_delta();
+            if self.controls.gestures {
+                self.drag_gesture(ui, response.rect);
+            }
+        }
+        if response.drag_stopped() {
+            match self.surface_gesture.take() {
+                Some(SurfaceGesture::Scrub { to, .. }) => self.seek(to),
+                Some(SurfaceGesture::Volume { .. }) => {}
+                None if self.controls.touch_mode && !self.controls.gestures => {
+                    let elapsed = self.get_elapsed_time();
+                    if self.surface_drag.x >= SWIPE_DISTANCE {
+                        self.seek(elapsed + SWIPE_SEEK);
+                    } else if self.surface_drag.x <= -SWIPE_DISTANCE {
+                        self.seek(elapsed.saturating_sub(SWIPE_SEEK));
+                    }
+                }
+                None => {}
+            }
+            self.surface_drag = Vec2::ZERO;
+        }
+    }
+
+    /// Scrubbing and volume of [`ControlsConfig::gestures`] while the pointer is held down, with a bubble showing the result
+    fn drag_gesture(&mut self, ui: &Ui, rect: Rect) {
+        if self.surface_gesture.is_none() && self.surface_drag.length() >= GESTURE_THRESHOLD {
+            let on_right = ui
+                .input(|input| input.pointer.press_origin())
+                .is_some_and(|origin| origin.x > rect.center().x);
+            self.surface_gesture =
+                if on_right && self.surface_drag.y.abs() > self.surface_drag.x.abs() {
+                    Some(SurfaceGesture::Volume {
+                        from: self.volume.load(Ordering::Acquire),
+                    })
+                } else {
+                    Some(SurfaceGesture::Scrub {
+                        from: self.get_elapsed_time(),
+                        to: self.get_elapsed_time(),
+                    })
+                };
+        }
+        let bubble = match &mut self.surface_gesture {
+            Some(SurfaceGesture::Scrub { from, to }) => {
+                // Dragging across the whole player covers the whole file
+                let offset =
+                    self.total_time.as_secs_f32() * self.surface_drag.x / rect.width().max(1.0);
+                *to = if offset < 0.0 {
+                    from.saturating_sub(Duration::from_secs_f32(-offset))
+                } else {
+                    // Scrubbing covers nothing while the length is not known, so there is nothing to keep within
+                    (*from + Duration::from_secs_f32(offset)).min(self.total_time.max(*from))
+                };
+                let sign = if *to < *from { "-" } else { "+" };
+                format!(
+                    "{} ({sign}{})",
+                    self.time_format.format(*to),
+                    self.time_format.format(to.abs_diff(*from))
+                )
+            }
+            Some(SurfaceGesture::Volume { from }) => {
+                let volume =
+                    (*from - (self.surface_drag.y / GESTURE_VOLUME_DRAG) as i32).clamp(0, 100);
+                self.volume.store(volume, Ordering::Release);
+                format!("{} {volume}%", self.locale.volume)
+            }
+            None => return,
+        };
+        paint_bubble(ui, rect, Align2::CENTER_CENTER, bubble, 1.0);
+    }
+
+    /// Shows which segment was just skipped at the top of the player, see [`Player::add_skip_segment`]
+    fn skip_toast(&mut self, ui: &Ui, rect: Rect) {
+        let Some((message, skipped_at)) = &self.skip_toast else {
+            return;
+        };
+        let shown = self.clock.now().saturating_duration_since(*skipped_at);
+        if shown >= SKIP_TOAST_DURATION {
+            self.skip_toast = None;
+            return;
+        }
+        let left = (SKIP_TOAST_DURATION - shown).as_secs_f32();
+        paint_bubble(
+            ui,
+            rect,
+            Align2::CENTER_TOP,
+            message.clone(),
+            left.min(0.5) * 2.0,
+        );
+        ui.ctx().request_repaint();
+    }
+
+    /// Shows the transcript on its own so it can be placed anywhere in your layout, such as a side panel or an ``egui::Window``
+    ///
+    /// Once this has been called the transcript is no longer shown under the controls of [`Player::ui`].
+    /// Call it before [`Player::ui`] so the transcript is not shown twice on the first frame.
+    /// Still follows the [`TranscriptionSettings`], nothing is shown unless they include a label
+    ///
+    /// # Examples
+    ///
+    /// ``` no_run
+    /// use eframe::egui;
+    /// use egui_player::player::Player;
+    ///
+    /// fn show(ctx: &egui::Context, player: &mut Player) {
+    ///     egui::SidePanel::right("transcript").show(ctx, |ui| {
+    ///         player.transcript_ui(ui);
+    ///     });
+    ///     egui::CentralPanel::default().show(ctx, |ui| {
+    ///         player.ui(ui);
+    ///     });
+    /// }
+    /// ```
+    pub fn transcript_ui(&mut self, ui: &mut Ui) -> Response {
+        self.transcript_detached = true;
+        ui.vertical(|ui| self.transcript_view(ui)).response
+    }
+
+    /// Shows a large zoomable waveform of the whole file with the playhead and [`Marker`]s
+    ///
+    /// Use ctrl + scroll or pinch to zoom, scroll to move through time, click to seek and drag to select a region
+    /// that can be read back with [`Player::timeline_selection`].
+    /// This is separate from [`Player::ui`] so it can be placed anywhere in your layout
+    pub fn timeline_ui(&mut self, ui: &mut Ui) -> Response {
+        self.timeline_shown = true;
+        self.update_seek_bar_analysis();
+        let (response, seek_to) = self.timeline.show(
+            ui,
+            TimelineContent {
+                peaks: self.waveform_peaks.as_deref(),
+                elapsed: self.elapsed_time,
+                total: self.total_time,
+                markers: &self.markers,
+                playing: self.player_state == PlayerState::Playing,
+                loading_text: &self.locale.loading_waveform,
+                time_format: &self.time_format,
+            },
+        );
+        if let Some(overlay) = &mut self.overlay {
+            let (visible_start, visible_end) = self.timeline.visible_range(self.total_time);
+            let state = OverlayState {
+                elapsed: self.elapsed_time,
+                total: self.total_time,
+                playing: self.player_state == PlayerState::Playing,
+                visible_start,
+                visible_end,
+            };
+            (overlay.0)(&ui.painter_at(response.rect), response.rect, &state);
+        }
+        if let Some(time) = seek_to {
+            self.seek(time);
         }
         response
     }
 
     /// Call this to show the player on screen
     pub fn ui(&mut self, ui: &mut Ui) -> Response {
+        profiling::function_scope!();
         self.add_contents(ui)
     }
 }
+
+/// Computes what is needed to draw ``background``, going through the [`AnalysisCache`] when there is one
+fn analyse_seek_bar(
+    background: SeekBarBackground,
+    file_input: InputMode,
+    cache: Option<AnalysisCache>,
+) -> io::Result<SeekBarAnalysis> {
+    let cache = cache.and_then(|cache| {
+        let hash = media_information::content_hash(&file_input).ok()?;
+        Some((cache, hash))
+    });
+    match background {
+        SeekBarBackground::Spectrogram => {
+            if let Some(spectrogram) = cache.as_ref().and_then(|(cache, hash)| {
+                cache.load_spectrogram(hash, SPECTROGRAM_COLUMNS, SPECTROGRAM_BINS)
+            }) {
+                return Ok(SeekBarAnalysis::Spectrogram(spectrogram));
+            }
+            let spectrogram = media_information::get_spectrogram(
+                file_input,
+                SPECTROGRAM_COLUMNS,
+                SPECTROGRAM_BINS,
+            )?;
+            if let Some((cache, hash)) = &cache {
+                let _ = cache.store_spectrogram(hash, SPECTROGRAM_COLUMNS, &spectrogram);
+            }
+            Ok(SeekBarAnalysis::Spectrogram(spectrogram))
+        }
+        _ => {
+            if let Some(peaks) = cache
+                .as_ref()
+                .and_then(|(cache, hash)| cache.load_peaks(hash, WAVEFORM_COLUMNS))
+            {
+                return Ok(SeekBarAnalysis::Waveform(peaks));
+            }
+            let peaks = media_information::get_waveform_peaks(file_input, WAVEFORM_COLUMNS)?;
+            if let Some((cache, hash)) = &cache {
+                let _ = cache.store_peaks(hash, WAVEFORM_COLUMNS, &peaks);
+            }
+            Ok(SeekBarAnalysis::Waveform(peaks))
+        }
+    }
+}
+
+/// Turns a [`Spectrogram`] into an image with the highest frequencies at the top
+fn spectrogram_image(spectrogram: &Spectrogram) -> ColorImage {
+    let mut pixels = Vec::with_capacity(spectrogram.width * spectrogram.height);
+    for row in 0..spectrogram.height {
+        let band = spectrogram.height - 1 - row;
+        for column in 0..spectrogram.width {
+            pixels.push(heat_color(
+                spectrogram.values[column * spectrogram.height + band],
+            ));
+        }
+    }
+    ColorImage {
+        size: [spectrogram.width, spectrogram.height],
+        pixels,
+    }
+}
+
+/// Maps 0.0 - 1.0 onto a black, purple, orange and yellow gradient
+fn heat_color(value: f32) -> Color32 {
+    const STOPS: [(f32, [f32; 3]); 4] = [
+        (0.0, [0.0, 0.0, 0.0]),
+        (0.4, [70.0, 15.0, 110.0]),
+        (0.75, [225.0, 85.0, 35.0]),
+        (1.0, [255.0, 240.0, 130.0]),
+    ];
+    let value = value.clamp(0.0, 1.0);
+    let upper = STOPS
+        .iter()
+        .position(|(stop, _)| *stop >= value)
+        .unwrap_or(STOPS.len() - 1)
+        .max(1);
+    let (start, from) = STOPS[upper - 1];
+    let (end, to) = STOPS[upper];
+    let t = (value - start) / (end - start);
+    let channel = |index: usize| (from[index] + (to[index] - from[index]) * t) as u8;
+    Color32::from_rgb(channel(0), channel(1), channel(2))
+}
+
+/// Cover art of an audio file, or the color of its placeholder when it has none
+fn find_poster(file_input: InputMode) -> (Option<ImageSource<'static>>, Option<Color32>) {
+    if let Some(cover) = media_information::get_cover_art(file_input.clone()) {
+        (Some(cover_image(cover)), None)
+    } else if let Ok(hash) = media_information::content_hash(&file_input) {
+        let hue = u16::from_str_radix(&hash[..4], 16).unwrap_or_default();
+        let color = Hsva::new(hue as f32 / u16::MAX as f32, 0.35, 0.55, 1.0).into();
+        (None, Some(color))
+    } else {
+        (None, None)
+    }
+}
+
+/// [`CoverArt`] as an image for egui, under a URI made from its content so egui caches each picture once
+fn cover_image(cover: CoverArt) -> ImageSource<'static> {
+    let extension = cover.media_type.rsplit('/').next().unwrap_or("jpg");
+    let hash = media_information::content_hash(&InputMode::Bytes(cover.data.as_slice().into()))
+        .unwrap_or_default();
+    ImageSource::Bytes {
+        uri: format!("bytes://egui_player/cover/{hash}.{extension}").into(),
+        bytes: cover.data.into(),
+    }
+}
+
+/// Whether the first letter of ``transcript`` belongs to a right to left script such as Arabic or Hebrew
+fn is_right_to_left_text(transcript: &[TranscriptionData]) -> bool {
+    transcript
+        .iter()
+        .flat_map(|word| word.text.chars())
+        .find(|character| character.is_alphabetic())
+        .is_some_and(|character| {
+            matches!(character, '\u{0590}'..='\u{08FF}' | '\u{FB1D}'..='\u{FDFF}' | '\u{FE70}'..='\u{FEFF}')
+        })
+}
+
+/// Gain of a volume percentage, such as ``-12.0 dB``
+fn volume_db_text(volume: i32) -> String {
+    if volume <= 0 {
+        return "-∞ dB".to_string();
+    }
+    format!("{:.1} dB", 20.0 * (volume as f32 / 100.0).log10())
+}
+
+/// Paints ``text`` in a rounded bubble placed at ``align`` inside of ``rect``, faded to ``opacity``
+fn paint_bubble(ui: &Ui, rect: Rect, align: Align2, text: String, opacity: f32) {
+    let painter = ui.painter();
+    let galley = painter.layout_no_wrap(
+        text,
+        TextStyle::Heading.resolve(ui.style()),
+        ui.visuals().strong_text_color().gamma_multiply(opacity),
+    );
+    let bubble_rect =
+        align.align_size_within_rect(galley.size() + ui.spacing().button_padding * 2.0, rect);
+    painter.rect_filled(
+        bubble_rect,
+        bubble_rect.height() / 2.0,
+        ui.visuals().window_fill.gamma_multiply(0.9 * opacity),
+    );
+    painter.galley(
+        bubble_rect.center() - galley.size() / 2.0,
+        galley,
+        Color32::PLACEHOLDER,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    /// Player of a short file that plays without an audio device and keeps time with ``clock``
+    fn silent_player(clock: &ManualClock) -> Player {
+        let mut player = Player::from_path("assets/beep.wav");
+        player.set_audio_context(AudioContext::silent_with_clock(clock.clone()));
+        player.set_clock(clock.clone());
+        player
+    }
+
+    /// Changes of state the hook of ``player`` is told about from now on
+    fn record_states(player: &mut Player) -> Arc<Mutex<Vec<(PlayerState, PlayerState)>>> {
+        let states = Arc::new(Mutex::new(vec![]));
+        let recorded = Arc::clone(&states);
+        player.on_state_change(move |previous, state| {
+            recorded.lock().unwrap().push((previous, state));
+        });
+        states
+    }
+
+    #[test]
+    fn seeking_while_playing_is_not_reported_as_a_pause() {
+        let clock = ManualClock::new();
+        let mut player = silent_player(&clock);
+        let states = record_states(&mut player);
+        player.play_player();
+        player.seek(Duration::from_secs(1));
+        player.run_action(PlayerAction::SeekForward(Duration::from_secs(1)));
+        player.update();
+        assert_eq!(
+            *states.lock().unwrap(),
+            [(PlayerState::Paused, PlayerState::Playing)]
+        );
+        assert_eq!(player.player_state, PlayerState::Playing);
+    }
+
+    /// Runs ``update`` until the length probed on the worker pool has come back
+    fn wait_for_media_info(player: &mut Player) {
+        let started = Instant::now();
+        while player.media_receiver.is_some() {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "the length of the file was never probed"
+            );
+            thread::sleep(Duration::from_millis(5));
+            player.update();
+        }
+    }
+
+    #[test]
+    fn seeks_before_the_length_is_known_are_kept() {
+        let clock = ManualClock::new();
+        let mut player = silent_player(&clock);
+        player.seek(Duration::from_secs(3));
+        assert_eq!(player.elapsed_time, Duration::from_secs(3));
+        wait_for_media_info(&mut player);
+        assert_eq!(player.elapsed_time, Duration::from_secs(3));
+
+        let mut player = silent_player(&clock);
+        player.seek(Duration::from_secs(600));
+        wait_for_media_info(&mut player);
+        assert!(player.total_time > Duration::from_secs(3));
+        assert_eq!(player.elapsed_time, player.total_time);
+    }
+
+    #[test]
+    fn elapsed_time_follows_the_clock() {
+        let clock = ManualClock::new();
+        let mut player = silent_player(&clock);
+        player.play_player();
+        player.update();
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(player.get_elapsed_time(), Duration::from_secs(2));
+        player.update();
+        assert_eq!(player.elapsed_time, Duration::from_secs(2));
+        assert_eq!(player.player_state, PlayerState::Playing);
+
+        player.pause_player();
+        player.update();
+        clock.advance(Duration::from_secs(2));
+        player.update();
+        assert_eq!(player.get_elapsed_time(), Duration::from_secs(2));
+        assert_eq!(player.player_state, PlayerState::Paused);
+    }
+
+    #[test]
+    fn seeking_while_playing_keeps_following_the_clock() {
+        let clock = ManualClock::new();
+        let mut player = silent_player(&clock);
+        wait_for_media_info(&mut player);
+        player.play_player();
+        player.update();
+        clock.advance(Duration::from_secs(1));
+        player.seek(Duration::from_secs(5));
+        assert_eq!(player.get_elapsed_time(), Duration::from_secs(5));
+        player.update();
+        clock.advance(Duration::from_secs(2));
+        player.update();
+        assert_eq!(player.elapsed_time, Duration::from_secs(7));
+        assert_eq!(player.player_state, PlayerState::Playing);
+    }
+
+    #[test]
+    fn running_out_of_audio_ends_playback() {
+        let clock = ManualClock::new();
+        let mut player = silent_player(&clock);
+        wait_for_media_info(&mut player);
+        let states = record_states(&mut player);
+        player.play_player();
+        player.update();
+        // The audio thread starts its own count of the clock, so time keeps moving until it runs out
+        let started = Instant::now();
+        while player.player_state == PlayerState::Playing {
+            assert!(
+                started.elapsed() < Duration::from_secs(10),
+                "playback never ended"
+            );
+            clock.advance(Duration::from_secs(1));
+            thread::sleep(Duration::from_millis(5));
+            player.update();
+        }
+        assert_eq!(player.player_state, PlayerState::Ended);
+        assert_eq!(player.elapsed_time, player.total_time);
+        assert_eq!(
+            *states.lock().unwrap(),
+            [
+                (PlayerState::Paused, PlayerState::Playing),
+                (PlayerState::Playing, PlayerState::Ended)
+            ]
+        );
+    }
+}
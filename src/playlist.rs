@@ -0,0 +1,547 @@
+use eframe::egui::{Align, Layout, Response, Stroke, Ui, Widget};
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    analysis_cache::AnalysisCache,
+    media_information,
+    player::{OnEnd, Player},
+    worker_pool, InputMode, MediaType, PlayerDefaults, PlayerLocale, TimeFormat,
+};
+
+/// Audio of the next entry decoded ahead of time by [`Playlist::set_prefetch`]
+const PREFETCH_AUDIO: Duration = Duration::from_secs(5);
+
+/// Loudest a quiet entry is boosted by [`Playlist::set_level_matching`] in decibels, so noise is not blown up
+const MAX_LEVEL_BOOST: f32 = 12.0;
+
+/// An item of a [`Playlist`]
+///
+/// ``duration``: Length of the entry, probed in the background once an entry without one is pushed to a [`Playlist`]
+///
+/// ``loudness``: Integrated loudness in LUFS, measured once [`Playlist::set_level_matching`] is on
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub file_input: InputMode,
+    pub title: String,
+    pub duration: Duration,
+    pub loudness: Option<f32>,
+}
+
+impl PlaylistEntry {
+    /// Creates an entry titled after the file name
+    pub fn from_path(file_path: &str) -> Self {
+        let title = Path::new(file_path)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(file_path)
+            .to_string();
+        Self {
+            duration: Duration::ZERO,
+            file_input: InputMode::FilePath(file_path.to_string()),
+            title,
+            loudness: None,
+        }
+    }
+
+    pub fn from_bytes(bytes: impl Into<Arc<[u8]>>, title: impl Into<String>) -> Self {
+        Self {
+            duration: Duration::ZERO,
+            file_input: InputMode::Bytes(bytes.into()),
+            title: title.into(),
+            loudness: None,
+        }
+    }
+
+    /// Creates an entry that streams the file at ``url`` once it is played, see [`InputMode::Url`]
+    #[cfg(feature = "streaming")]
+    pub fn from_url(url: &str, title: impl Into<String>) -> Self {
+        Self {
+            duration: Duration::ZERO,
+            file_input: InputMode::Url(url.to_string()),
+            title: title.into(),
+            loudness: None,
+        }
+    }
+}
+
+/// Queue of files played one after the other by a single [`Player`]
+///
+/// Show the queue with a [`PlaylistWidget`] and the player of the current entry with [`Playlist::ui`]
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::playlist::{Playlist, PlaylistEntry, PlaylistWidget};
+///
+/// let mut playlist = Playlist::new();
+/// playlist.push(PlaylistEntry::from_path("first.mp3"));
+/// playlist.push(PlaylistEntry::from_path("second.mp3"));
+/// // Later, inside of your egui app
+/// // playlist.ui(ui);
+/// // ui.add(PlaylistWidget::new(&mut playlist));
+/// ```
+#[derive(Debug, Default)]
+pub struct Playlist {
+    entries: Vec<PlaylistEntry>,
+    current: Option<usize>,
+    player: Option<Player>,
+    level_target: Option<f32>,
+    cache_dir: Option<PathBuf>,
+    /// Entry whose loudness is being measured and where the measurement arrives
+    measuring: Option<(usize, Receiver<f32>)>,
+    /// Entries whose duration is being probed and where each duration arrives
+    probing: Vec<(usize, Receiver<Duration>)>,
+    prefetch: bool,
+    /// Player of the next entry that is being prepared, and the entry it is for
+    prefetching: Option<(usize, Receiver<Player>)>,
+    prefetched: Option<(usize, Player)>,
+}
+
+impl Playlist {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn entries(&self) -> &[PlaylistEntry] {
+        &self.entries
+    }
+
+    /// Adds an entry at the end of the queue, its duration is probed in the background when it has none
+    pub fn push(&mut self, entry: PlaylistEntry) {
+        if entry.duration == Duration::ZERO {
+            let file_input = entry.file_input.clone();
+            let (tx_duration, rx_duration) = mpsc::channel();
+            self.probing.push((self.entries.len(), rx_duration));
+            worker_pool::spawn(move || {
+                let media_type = match &file_input {
+                    InputMode::FilePath(file_path) => media_information::get_media_type(file_path),
+                    InputMode::Bytes(_) => MediaType::Audio,
+                    #[cfg(feature = "streaming")]
+                    InputMode::Url(_) => MediaType::Audio,
+                };
+                let _ = tx_duration.send(media_information::get_total_time(media_type, file_input));
+            });
+        }
+        self.entries.push(entry);
+    }
+
+    /// Fills in the durations that were probed since the last call
+    fn receive_durations(&mut self) {
+        let entries = &mut self.entries;
+        self.probing
+            .retain(|(index, receiver)| match receiver.try_recv() {
+                Ok(duration) => {
+                    entries[*index].duration = duration;
+                    false
+                }
+                Err(mpsc::TryRecvError::Empty) => true,
+                Err(mpsc::TryRecvError::Disconnected) => false,
+            });
+    }
+
+    /// Removes the entry at ``index``, stopping it first if it is the current one
+    pub fn remove(&mut self, index: usize) -> PlaylistEntry {
+        match self.current {
+            Some(current) if current == index => self.stop(),
+            Some(current) if current > index => self.current = Some(current - 1),
+            _ => {}
+        }
+        self.clear_prefetch();
+        match &mut self.measuring {
+            Some((measured, _)) if *measured == index => self.measuring = None,
+            Some((measured, _)) if *measured > index => *measured -= 1,
+            _ => {}
+        }
+        self.probing.retain_mut(|(probed, _)| {
+            if *probed == index {
+                return false;
+            }
+            if *probed > index {
+                *probed -= 1;
+            }
+            true
+        });
+        self.entries.remove(index)
+    }
+
+    /// Moves the entry at ``from`` so it ends up at ``to``
+    pub fn move_entry(&mut self, from: usize, to: usize) {
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        self.clear_prefetch();
+        let moved = |index: usize| {
+            if index == from {
+                to
+            } else if from < index && index <= to {
+                index - 1
+            } else if to <= index && index < from {
+                index + 1
+            } else {
+                index
+            }
+        };
+        self.current = self.current.map(moved);
+        if let Some((measured, _)) = &mut self.measuring {
+            *measured = moved(*measured);
+        }
+        for (probed, _) in &mut self.probing {
+            *probed = moved(*probed);
+        }
+    }
+
+    /// Plays every entry at about the same loudness, so the volume does not swing from one entry to the next
+    ///
+    /// ``target`` is in LUFS, -16 is common for spoken word and -14 for music. Entries are measured one at a time
+    /// on a background thread while the playlist is updated, ``None`` turns level matching off
+    pub fn set_level_matching(&mut self, target: Option<f32>) {
+        self.level_target = target;
+        let gain = self.current.map_or(0.0, |current| self.level_gain(current));
+        if let Some(player) = &mut self.player {
+            player.set_level_gain(gain);
+        }
+    }
+
+    /// Caches the loudness of entries inside ``directory`` next to the waveforms of their players,
+    /// see [`Player::set_waveform_cache_dir`]
+    pub fn set_waveform_cache_dir(&mut self, directory: impl Into<PathBuf>) {
+        let directory = directory.into();
+        if let Some(player) = &mut self.player {
+            player.set_waveform_cache_dir(directory.clone());
+        }
+        self.cache_dir = Some(directory);
+    }
+
+    /// Prepares the player of the next entry in the background while the current one plays, so moving on is instant
+    ///
+    /// The next player probes the length and chapters of its file, loads the cover art and decodes the first
+    /// seconds of local files once so they are in the cache of the system
+    pub fn set_prefetch(&mut self, prefetch: bool) {
+        self.prefetch = prefetch;
+        if !prefetch {
+            self.clear_prefetch();
+        }
+    }
+
+    fn clear_prefetch(&mut self) {
+        self.prefetching = None;
+        self.prefetched = None;
+    }
+
+    /// Receives the prepared player of the next entry and starts preparing it when nothing is yet
+    fn prefetch_next(&mut self) {
+        if !self.prefetch {
+            return;
+        }
+        if let Some((index, receiver)) = &self.prefetching {
+            match receiver.try_recv() {
+                Ok(player) => self.prefetched = Some((*index, player)),
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {}
+            }
+            self.prefetching = None;
+        }
+        let Some(next) = self
+            .current
+            .map(|current| current + 1)
+            .filter(|next| *next < self.entries.len())
+        else {
+            return;
+        };
+        if self
+            .prefetched
+            .as_ref()
+            .is_some_and(|(index, _)| *index == next)
+        {
+            return;
+        }
+        let file_input = self.entries[next].file_input.clone();
+        let (tx_player, rx_player) = mpsc::channel();
+        self.prefetching = Some((next, rx_player));
+        worker_pool::spawn(move || {
+            let mut player = open_player(file_input);
+            player.prefetch(PREFETCH_AUDIO);
+            let _ = tx_player.send(player);
+        });
+    }
+
+    /// Gain in decibels that brings the entry at ``index`` to the level matching target
+    fn level_gain(&self, index: usize) -> f32 {
+        match (self.level_target, self.entries[index].loudness) {
+            (Some(target), Some(loudness)) if loudness.is_finite() => {
+                (target - loudness).min(MAX_LEVEL_BOOST)
+            }
+            _ => 0.0,
+        }
+    }
+
+    /// Receives the loudness of the entry being measured and starts measuring the next one without a loudness
+    fn measure_loudness(&mut self) {
+        if self.level_target.is_none() {
+            return;
+        }
+        if let Some((index, receiver)) = &self.measuring {
+            let index = *index;
+            match receiver.try_recv() {
+                Ok(loudness) => {
+                    self.entries[index].loudness = Some(loudness);
+                    if self.current == Some(index) {
+                        let gain = self.level_gain(index);
+                        if let Some(player) = &mut self.player {
+                            player.set_level_gain(gain);
+                        }
+                    }
+                }
+                Err(mpsc::TryRecvError::Empty) => return,
+                Err(mpsc::TryRecvError::Disconnected) => {}
+            }
+            self.measuring = None;
+        }
+        // The current entry goes first, so it is matched as soon as possible
+        let Some(index) = self
+            .current
+            .filter(|current| self.entries[*current].loudness.is_none())
+            .or_else(|| {
+                self.entries
+                    .iter()
+                    .position(|entry| entry.loudness.is_none())
+            })
+        else {
+            return;
+        };
+        let file_input = self.entries[index].file_input.clone();
+        let cache = self.cache_dir.clone().map(AnalysisCache::new);
+        let (tx_loudness, rx_loudness) = mpsc::channel();
+        self.measuring = Some((index, rx_loudness));
+        worker_pool::spawn(move || {
+            let hash = cache
+                .as_ref()
+                .and_then(|_| media_information::content_hash(&file_input).ok());
+            if let (Some(cache), Some(hash)) = (&cache, &hash)
+                && let Some(loudness) = cache.load_loudness(hash)
+            {
+                let _ = tx_loudness.send(loudness);
+                return;
+            }
+            let loudness = match media_information::integrated_loudness(file_input) {
+                Ok(loudness) => loudness,
+                Err(error) => {
+                    // Without a loudness the entry plays without level gain
+                    log::error!("Could not measure the loudness of the file: {error}");
+                    let _ = tx_loudness.send(f32::NEG_INFINITY);
+                    return;
+                }
+            };
+            if let (Some(cache), Some(hash)) = (&cache, &hash) {
+                let _ = cache.store_loudness(hash, loudness);
+            }
+            let _ = tx_loudness.send(loudness);
+        });
+    }
+
+    /// Index of the entry that is loaded in the player
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Loads the entry at ``index`` and starts playing it
+    pub fn play(&mut self, index: usize) {
+        let volume = self
+            .player
+            .as_ref()
+            .map(|player| player.volume.load(Ordering::Acquire));
+        self.stop();
+        let mut player = match self.prefetched.take() {
+            Some((prefetched, player)) if prefetched == index => player,
+            _ => open_player(self.entries[index].file_input.clone()),
+        };
+        if let Some(volume) = volume {
+            player.volume.store(volume, Ordering::Release);
+        }
+        if let Some(directory) = &self.cache_dir {
+            player.set_waveform_cache_dir(directory.clone());
+        }
+        player.set_level_gain(self.level_gain(index));
+        player.set_on_end(OnEnd::Advance);
+        player.play_player();
+        self.player = Some(player);
+        self.current = Some(index);
+    }
+
+    /// Plays the entry after the current one, stops at the end of the queue
+    pub fn next(&mut self) {
+        match self.current.map(|current| current + 1) {
+            Some(next) if next < self.entries.len() => self.play(next),
+            None if !self.entries.is_empty() => self.play(0),
+            _ => self.stop(),
+        }
+    }
+
+    /// Plays the entry before the current one
+    pub fn previous(&mut self) {
+        if let Some(current) = self.current {
+            self.play(current.saturating_sub(1));
+        }
+    }
+
+    /// Stops and unloads the current entry
+    pub fn stop(&mut self) {
+        if let Some(mut player) = self.player.take() {
+            player.pause_player();
+        }
+        self.current = None;
+    }
+
+    /// [`Player`] of the current entry
+    pub fn player_mut(&mut self) -> Option<&mut Player> {
+        self.player.as_mut()
+    }
+
+    /// Keeps the playlist going while it is not drawn, see [`Player::update`]
+    pub fn update(&mut self) {
+        self.receive_durations();
+        self.measure_loudness();
+        self.prefetch_next();
+        if let Some(player) = &mut self.player {
+            player.update();
+            if player.should_advance() {
+                self.next();
+            }
+        }
+    }
+
+    /// Shows the player of the current entry, moving on to the next entry once it has ended
+    pub fn ui(&mut self, ui: &mut Ui) -> Option<Response> {
+        self.receive_durations();
+        self.measure_loudness();
+        self.prefetch_next();
+        let response = self.player.as_mut()?.ui(ui);
+        if self.player.as_ref().is_some_and(Player::should_advance) {
+            self.next();
+        }
+        Some(response)
+    }
+}
+
+/// Player for an entry, before the playlist sets it up
+fn open_player(file_input: InputMode) -> Player {
+    match file_input {
+        InputMode::FilePath(file_path) => Player::from_path(&file_path),
+        InputMode::Bytes(bytes) => Player::from_bytes(bytes),
+        #[cfg(feature = "streaming")]
+        InputMode::Url(url) => Player::from_url(&url),
+    }
+}
+
+/// Action picked in a [`PlaylistWidget`], applied once every row has been drawn
+enum PlaylistAction {
+    Play(usize),
+    Remove(usize),
+    Move { from: usize, to: usize },
+}
+
+/// Drag and drop payload holding the index of the entry being dragged
+struct DraggedEntry(usize);
+
+/// Queue of a [`Playlist`] with titles and durations
+///
+/// Click an entry to play it, drag entries to reorder them and use the bin to remove them.
+/// The entry that is currently playing is highlighted
+pub struct PlaylistWidget<'a> {
+    playlist: &'a mut Playlist,
+    locale: PlayerLocale,
+    time_format: TimeFormat,
+}
+
+impl<'a> PlaylistWidget<'a> {
+    /// Creates the widget with the locale and time format of [`PlayerDefaults`]
+    pub fn new(playlist: &'a mut Playlist) -> Self {
+        let defaults = PlayerDefaults::global();
+        Self {
+            playlist,
+            locale: defaults.locale,
+            time_format: defaults.time_format,
+        }
+    }
+
+    pub fn locale(mut self, locale: PlayerLocale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    pub fn time_format(mut self, time_format: TimeFormat) -> Self {
+        self.time_format = time_format;
+        self
+    }
+}
+
+impl Widget for PlaylistWidget<'_> {
+    fn ui(self, ui: &mut Ui) -> Response {
+        let mut action = None;
+        let response = ui
+            .vertical(|ui| {
+                for (index, entry) in self.playlist.entries.iter().enumerate() {
+                    let id = ui.id().with(("playlist_entry", index));
+                    let row = ui
+                        .dnd_drag_source(id, DraggedEntry(index), |ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("☰");
+                                let current = self.playlist.current == Some(index);
+                                if ui.selectable_label(current, &entry.title).clicked() {
+                                    action = Some(PlaylistAction::Play(index));
+                                }
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    if ui
+                                        .small_button("🗑")
+                                        .on_hover_text(&self.locale.remove)
+                                        .clicked()
+                                    {
+                                        action = Some(PlaylistAction::Remove(index));
+                                    }
+                                    ui.weak(self.time_format.format(entry.duration));
+                                });
+                            });
+                        })
+                        .response;
+
+                    if let Some(dragged) = row.dnd_hover_payload::<DraggedEntry>() {
+                        // Show where the dragged entry will land
+                        let line_y = if dragged.0 > index {
+                            row.rect.top()
+                        } else {
+                            row.rect.bottom()
+                        };
+                        ui.painter().hline(
+                            row.rect.x_range(),
+                            line_y,
+                            Stroke::new(2.0, ui.visuals().selection.stroke.color),
+                        );
+                    }
+                    if let Some(dragged) = row.dnd_release_payload::<DraggedEntry>() {
+                        action = Some(PlaylistAction::Move {
+                            from: dragged.0,
+                            to: index,
+                        });
+                    }
+                }
+            })
+            .response;
+
+        match action {
+            Some(PlaylistAction::Play(index)) => self.playlist.play(index),
+            Some(PlaylistAction::Remove(index)) => {
+                self.playlist.remove(index);
+            }
+            Some(PlaylistAction::Move { from, to }) => self.playlist.move_entry(from, to),
+            None => {}
+        }
+        response
+    }
+}
@@ -0,0 +1,167 @@
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::{error::Error, time::Duration};
+
+use crate::{playlist::PlaylistEntry, InputMode};
+
+/// An item of a [`Podcast`] feed
+///
+/// ``url``: Where the audio of the episode is, from its ``enclosure``
+///
+/// ``media_type``: MIME type the feed gives for the audio, such as ``audio/mpeg``
+///
+/// ``duration``: Length from ``itunes:duration``, zero when the feed does not say
+///
+/// ``published``: The ``pubDate`` of the episode as written in the feed
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Episode {
+    pub title: String,
+    pub url: String,
+    pub media_type: Option<String>,
+    pub duration: Duration,
+    pub published: Option<String>,
+}
+
+impl Episode {
+    /// The audio of the episode as an [`InputMode::Url`], which plays while it downloads
+    pub fn input(&self) -> InputMode {
+        InputMode::Url(self.url.clone())
+    }
+
+    /// Downloads the whole audio of the episode, to keep it or to play it with [`crate::player::Player::from_bytes`]
+    pub async fn download(&self) -> reqwest::Result<Vec<u8>> {
+        let response = reqwest::get(&self.url).await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Entry for a [`crate::playlist::Playlist`] titled after the episode, which streams the episode once it is played
+    pub fn playlist_entry(&self) -> PlaylistEntry {
+        let mut entry = PlaylistEntry::from_url(&self.url, &self.title);
+        // The playlist probes the entries the feed gives no length for
+        entry.duration = self.duration;
+        entry
+    }
+}
+
+/// A podcast read from its RSS feed, episodes in the order of the feed which is usually newest first
+///
+/// Episodes are streamed from their URL with [`Episode::input`] or [`Episode::playlist_entry`], or downloaded in full
+/// with [`Episode::download`] to be kept
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::podcast::Podcast;
+///
+/// # async fn run() {
+/// let podcast = Podcast::fetch("https://example.com/feed.xml").await.unwrap();
+/// let newest = &podcast.episodes[0];
+/// let player = Player::from_url(&newest.url);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Podcast {
+    pub title: String,
+    pub episodes: Vec<Episode>,
+}
+
+impl Podcast {
+    /// Downloads and parses the feed at ``url``
+    pub async fn fetch(url: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let feed = reqwest::get(url).await?.error_for_status()?.text().await?;
+        Ok(Self::parse(&feed)?)
+    }
+
+    /// Parses an RSS feed, items without an ``enclosure`` are skipped as they have nothing to play
+    pub fn parse(feed: &str) -> Result<Self, quick_xml::Error> {
+        let mut reader = Reader::from_str(feed);
+        reader.config_mut().trim_text(true);
+        let mut podcast = Self::default();
+        // Names of the elements the reader is in, from the outside in
+        let mut path: Vec<String> = vec![];
+        let mut episode: Option<Episode> = None;
+        loop {
+            match reader.read_event()? {
+                Event::Start(element) => {
+                    let name = element_name(&element);
+                    if name == "item" {
+                        episode = Some(Episode::default());
+                    }
+                    read_enclosure(&element, &mut episode)?;
+                    path.push(name);
+                }
+                Event::Empty(element) => read_enclosure(&element, &mut episode)?,
+                Event::Text(text) => {
+                    let text = text.unescape()?;
+                    text_of(&path, &mut podcast, &mut episode, &text);
+                }
+                Event::CData(text) => {
+                    let text = String::from_utf8_lossy(&text).to_string();
+                    text_of(&path, &mut podcast, &mut episode, &text);
+                }
+                Event::End(_) => {
+                    if path.pop().as_deref() == Some("item")
+                        && let Some(episode) = episode.take()
+                        && !episode.url.is_empty()
+                    {
+                        podcast.episodes.push(episode);
+                    }
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        Ok(podcast)
+    }
+}
+
+/// Name of ``element`` with its namespace prefix, such as ``itunes:duration``
+fn element_name(element: &BytesStart) -> String {
+    String::from_utf8_lossy(element.name().as_ref()).to_string()
+}
+
+/// Takes the URL and type of the audio from ``element`` if it is the ``enclosure`` of an episode
+fn read_enclosure(
+    element: &BytesStart,
+    episode: &mut Option<Episode>,
+) -> Result<(), quick_xml::Error> {
+    if let Some(episode) = episode
+        && element_name(element) == "enclosure"
+    {
+        if let Some(url) = element.try_get_attribute("url")? {
+            episode.url = url.unescape_value()?.to_string();
+        }
+        if let Some(media_type) = element.try_get_attribute("type")? {
+            episode.media_type = Some(media_type.unescape_value()?.to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Stores ``text`` in the field of the element the reader is in
+fn text_of(path: &[String], podcast: &mut Podcast, episode: &mut Option<Episode>, text: &str) {
+    let (Some(name), Some(parent)) = (path.last(), path.iter().rev().nth(1)) else {
+        return;
+    };
+    match (episode, parent.as_str(), name.as_str()) {
+        (Some(episode), "item", "title") => episode.title.push_str(text),
+        (Some(episode), "item", "itunes:duration") => episode.duration = parse_duration(text),
+        (Some(episode), "item", "pubDate") => episode.published = Some(text.to_string()),
+        (None, "channel", "title") => podcast.title.push_str(text),
+        _ => {}
+    }
+}
+
+/// Parses an ``itunes:duration``, which is either seconds or ``HH:MM:SS`` with the hours optional
+fn parse_duration(text: &str) -> Duration {
+    let seconds = text
+        .trim()
+        .split(':')
+        .try_fold(0.0, |total, part| {
+            part.parse::<f64>().ok().map(|part| total * 60.0 + part)
+        })
+        .unwrap_or(0.0);
+    // Feeds are not trusted to hold lengths that fit in a duration
+    Duration::try_from_secs_f64(seconds).unwrap_or_default()
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use crate::{worker_pool, CaptionSource, SpeedMode};
+
+/// Settings remembered for a single file by a [`PreferenceStore`]
+///
+/// ``volume_offset``: Percentage points the volume of the file is above or below the volume the player had when the
+/// store was set, so the volume of the app itself still applies to files that were turned up or down
+///
+/// ``caption_source``, ``caption_offset``: The captions picked for the file and how far they are moved, see
+/// [`crate::player::Player::set_caption_source`] and [`crate::player::Player::set_caption_offset`].
+/// Files play their first track that can be decoded, so there is no audio track to remember
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FilePreferences {
+    pub speed: f32,
+    pub speed_mode: SpeedMode,
+    pub pitch_semitones: f32,
+    #[serde(default)]
+    pub volume_offset: i32,
+    pub mono: bool,
+    #[serde(default)]
+    pub caption_source: CaptionSource,
+    #[serde(default)]
+    pub caption_offset: i64,
+}
+
+/// Storage for [`FilePreferences`], keyed by a hash of the file content
+///
+/// Give one to [`crate::player::Player::set_preference_store`] and the settings of each file are reapplied whenever it is opened again.
+/// Implement this to keep preferences wherever your app keeps its data, ``Arc<Mutex<_>>`` of a store is also a store
+/// so one can be shared between players
+pub trait PreferenceStore: Send {
+    fn load(&self, hash: &str) -> Option<FilePreferences>;
+
+    fn store(&mut self, hash: &str, preferences: FilePreferences);
+}
+
+impl fmt::Debug for dyn PreferenceStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PreferenceStore")
+    }
+}
+
+impl<S: PreferenceStore> PreferenceStore for Arc<Mutex<S>> {
+    fn load(&self, hash: &str) -> Option<FilePreferences> {
+        self.lock().unwrap().load(hash)
+    }
+
+    fn store(&mut self, hash: &str, preferences: FilePreferences) {
+        self.lock().unwrap().store(hash, preferences);
+    }
+}
+
+/// Keeps preferences for as long as the app runs
+#[derive(Debug, Clone, Default)]
+pub struct MemoryPreferenceStore {
+    preferences: HashMap<String, FilePreferences>,
+}
+
+impl PreferenceStore for MemoryPreferenceStore {
+    fn load(&self, hash: &str) -> Option<FilePreferences> {
+        self.preferences.get(hash).copied()
+    }
+
+    fn store(&mut self, hash: &str, preferences: FilePreferences) {
+        self.preferences.insert(hash.to_string(), preferences);
+    }
+}
+
+/// Keeps preferences in a JSON file, which is rewritten in the background whenever a player saves its preferences
+///
+/// Players save once their settings stopped changing for a moment, not on every step of a slider being dragged.
+/// Clones write to the same file, the preferences stored last are the ones that end up in it
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::preferences::JsonPreferenceStore;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_preference_store(JsonPreferenceStore::open("preferences.json").unwrap());
+/// ```
+#[derive(Debug, Clone)]
+pub struct JsonPreferenceStore {
+    path: PathBuf,
+    preferences: HashMap<String, FilePreferences>,
+    /// Counts the writes handed to the worker pool, so one that runs late does not overwrite a newer one
+    writes: Arc<AtomicU64>,
+    /// The latest write that made it to the file
+    written: Arc<Mutex<u64>>,
+}
+
+impl JsonPreferenceStore {
+    /// Reads the preferences at ``path``, a missing file starts out empty
+    pub fn open(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let preferences = match fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents).map_err(io::Error::from)?,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) => return Err(error),
+        };
+        Ok(Self {
+            path,
+            preferences,
+            writes: Arc::new(AtomicU64::new(0)),
+            written: Arc::new(Mutex::new(0)),
+        })
+    }
+}
+
+impl PreferenceStore for JsonPreferenceStore {
+    fn load(&self, hash: &str) -> Option<FilePreferences> {
+        self.preferences.get(hash).copied()
+    }
+
+    fn store(&mut self, hash: &str, preferences: FilePreferences) {
+        self.preferences.insert(hash.to_string(), preferences);
+        let Ok(contents) = serde_json::to_vec_pretty(&self.preferences) else {
+            return;
+        };
+        let write = self.writes.fetch_add(1, Ordering::Relaxed) + 1;
+        let written = Arc::clone(&self.written);
+        let path = self.path.clone();
+        worker_pool::spawn(move || {
+            let mut written = written.lock().unwrap();
+            if *written > write {
+                return;
+            }
+            // Losing a preference is not worth interrupting playback over
+            if let Err(error) = fs::write(&path, contents) {
+                log::warn!("Could not save preferences to {}: {error}", path.display());
+            }
+            *written = write;
+        });
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    io::{self, Read, Seek, SeekFrom},
+    path::Path,
+    sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak},
+    thread,
+};
+use symphonia::core::io::MediaSource;
+
+/// Amount of bytes the download thread reads from the response at a time
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Downloads that a reader still holds on to, so every player and analysis of a URL shares a single download
+static DOWNLOADS: OnceLock<Mutex<HashMap<String, Weak<Download>>>> = OnceLock::new();
+
+/// A file that is downloaded on a thread of its own while it is read
+///
+/// The download stops once no reader holds on to it anymore
+#[derive(Debug, Default)]
+struct Download {
+    state: Mutex<DownloadState>,
+    /// Notified whenever bytes arrive or the download stops
+    progress: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct DownloadState {
+    bytes: Vec<u8>,
+    /// Length the server gave for the file, if it gave one
+    length: Option<u64>,
+    finished: bool,
+    /// Why the download stopped before the end of the file
+    error: Option<String>,
+}
+
+impl Download {
+    fn start(url: String) -> Arc<Self> {
+        let download = Arc::new(Self::default());
+        let weak = Arc::downgrade(&download);
+        thread::spawn(move || {
+            let result = Self::fetch(&url, &weak);
+            if let Some(download) = weak.upgrade() {
+                if let Err(error) = &result {
+                    log::error!("Could not download {url}: {error}");
+                }
+                download.finish(result.err().map(|error| error.to_string()));
+            }
+        });
+        download
+    }
+
+    fn fetch(url: &str, download: &Weak<Self>) -> Result<(), Box<dyn Error>> {
+        let mut response = reqwest::blocking::get(url)?.error_for_status()?;
+        if let Some(download) = download.upgrade() {
+            download.state.lock().unwrap().length = response.content_length();
+        }
+        let mut chunk = vec![0; CHUNK_SIZE];
+        loop {
+            let read = response.read(&mut chunk)?;
+            if read == 0 {
+                return Ok(());
+            }
+            // Nobody reads the file anymore
+            let Some(download) = download.upgrade() else {
+                return Ok(());
+            };
+            download.append(&chunk[..read]);
+        }
+    }
+
+    fn append(&self, chunk: &[u8]) {
+        self.state.lock().unwrap().bytes.extend_from_slice(chunk);
+        self.progress.notify_all();
+    }
+
+    fn finish(&self, error: Option<String>) {
+        let mut state = self.state.lock().unwrap();
+        state.finished = true;
+        state.error = error;
+        drop(state);
+        self.progress.notify_all();
+    }
+
+    /// Waits until the first ``end`` bytes are downloaded or the download stops
+    ///
+    /// Fails when the download stopped with an error before reaching ``end``
+    fn wait_for(&self, end: u64) -> io::Result<MutexGuard<'_, DownloadState>> {
+        let mut state = self.state.lock().unwrap();
+        while (state.bytes.len() as u64) < end && !state.finished {
+            state = self.progress.wait(state).unwrap();
+        }
+        if (state.bytes.len() as u64) < end
+            && let Some(error) = &state.error
+        {
+            return Err(io::Error::other(error.clone()));
+        }
+        Ok(state)
+    }
+}
+
+/// A file at a URL, opened for decoding or hashing while it is still downloading
+///
+/// Reads wait for the bytes they need to arrive. Seeking ahead of the download is possible, the next read then waits
+/// until the download gets there, so formats that keep their index at the end of the file only start once it is complete.
+/// The whole file is kept in memory for as long as a reader of it is open
+pub(crate) struct RemoteReader {
+    download: Arc<Download>,
+    position: u64,
+}
+
+impl RemoteReader {
+    /// Starts downloading ``url``, or joins the download that is already running for it
+    ///
+    /// Waits for the start of the file, so a URL that can not be downloaded fails here
+    pub(crate) fn open(url: &str) -> io::Result<Self> {
+        let download = {
+            let mut downloads = DOWNLOADS.get_or_init(Default::default).lock().unwrap();
+            downloads.retain(|_, download| download.strong_count() > 0);
+            match downloads.get(url).and_then(Weak::upgrade) {
+                Some(download) => download,
+                None => {
+                    let download = Download::start(url.to_string());
+                    downloads.insert(url.to_string(), Arc::downgrade(&download));
+                    download
+                }
+            }
+        };
+        download.wait_for(1)?;
+        Ok(Self {
+            download,
+            position: 0,
+        })
+    }
+
+    /// Length of the file, waits for the whole download when the server did not give one
+    fn length(&self) -> io::Result<u64> {
+        let state = self.download.wait_for(0)?;
+        if let Some(length) = state.length {
+            return Ok(length);
+        }
+        drop(state);
+        Ok(self.download.wait_for(u64::MAX)?.bytes.len() as u64)
+    }
+}
+
+impl Read for RemoteReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let state = self.download.wait_for(self.position + 1)?;
+        let start = (self.position as usize).min(state.bytes.len());
+        let read = buf.len().min(state.bytes.len() - start);
+        buf[..read].copy_from_slice(&state.bytes[start..start + read]);
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for RemoteReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.length()?.checked_add_signed(offset),
+        };
+        self.position = position.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+impl MediaSource for RemoteReader {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        let state = self.download.state.lock().unwrap();
+        state
+            .length
+            .or(state.finished.then_some(state.bytes.len() as u64))
+    }
+}
+
+/// Extension of the file at ``url``, leaving out the query and fragment
+pub(crate) fn extension(url: &str) -> Option<&str> {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    Path::new(path).extension().and_then(|ext| ext.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Reader of a download that the test feeds itself instead of a server
+    fn reader(download: &Arc<Download>) -> RemoteReader {
+        RemoteReader {
+            download: Arc::clone(download),
+            position: 0,
+        }
+    }
+
+    #[test]
+    fn reads_wait_for_the_download() {
+        let download = Arc::new(Download::default());
+        let feeder = Arc::clone(&download);
+        let feeding = thread::spawn(move || {
+            for chunk in [b"abc".as_slice(), b"def", b"ghi"] {
+                thread::sleep(Duration::from_millis(10));
+                feeder.append(chunk);
+            }
+            feeder.finish(None);
+        });
+        let mut reader = reader(&download);
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).unwrap();
+        feeding.join().unwrap();
+        assert_eq!(bytes, b"abcdefghi");
+    }
+
+    #[test]
+    fn seeking_ahead_waits_for_the_download() {
+        let download = Arc::new(Download::default());
+        download.state.lock().unwrap().length = Some(6);
+        download.append(b"abc");
+        let mut reader = reader(&download);
+        assert_eq!(reader.seek(SeekFrom::End(-2)).unwrap(), 4);
+        let feeder = Arc::clone(&download);
+        let feeding = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            feeder.append(b"def");
+            feeder.finish(None);
+        });
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).unwrap();
+        feeding.join().unwrap();
+        assert_eq!(bytes, b"ef");
+        assert_eq!(reader.byte_len(), Some(6));
+    }
+
+    #[test]
+    fn failed_downloads_fail_reads_past_what_arrived() {
+        let download = Arc::new(Download::default());
+        download.append(b"abc");
+        download.finish(Some("connection reset".to_string()));
+        let mut reader = reader(&download);
+        let mut start = [0; 3];
+        reader.read_exact(&mut start).unwrap();
+        assert_eq!(&start, b"abc");
+        assert!(reader.read(&mut start).is_err());
+    }
+
+    #[test]
+    fn extension_leaves_out_the_query() {
+        assert_eq!(
+            extension("https://example.com/episode.mp3?source=feed"),
+            Some("mp3")
+        );
+        assert_eq!(extension("https://example.com/listen#t=30"), None);
+    }
+}
@@ -0,0 +1,188 @@
+use eframe::egui::{Response, Ui};
+use rodio::{dynamic_mixer, Source};
+use std::{sync::Arc, thread, time::Duration};
+
+use crate::{audio_context::AudioContext, clock::Clock, player::Player};
+
+/// Controls used on a member of a [`SyncGroup`], the group applies them to every member
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum SyncRequest {
+    Play,
+    Pause,
+    Seek(Duration),
+}
+
+/// Links several [`Player`]s so play, pause and seek apply to all of them
+///
+/// Members are mixed into a single stream that starts every member on the same sample,
+/// which makes it possible to A/B two recordings or review the tracks of a multitrack recording together.
+/// Each member keeps its own volume, speed and effects, and using the controls of any member controls the whole group
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::sync_group::SyncGroup;
+///
+/// let mut group = SyncGroup::new();
+/// group.add(Player::from_path("take_one.wav"));
+/// group.add(Player::from_path("take_two.wav"));
+/// // Later, inside of your egui app
+/// // group.ui(ui);
+/// ```
+#[derive(Debug, Default)]
+pub struct SyncGroup {
+    players: Vec<Player>,
+    audio_context: Option<AudioContext>,
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl SyncGroup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds ``player`` to the group, moving it to the position of the other members. Returns its index
+    pub fn add(&mut self, mut player: Player) -> usize {
+        let playing = self.is_playing();
+        self.pause();
+        player.sync_member = true;
+        if let Some(clock) = &self.clock {
+            player.set_clock(Arc::clone(clock));
+        }
+        if let Some(position) = self.players.first().map(|first| first.elapsed_time) {
+            player.set_position(position);
+        }
+        self.players.push(player);
+        if playing {
+            self.play();
+        }
+        self.players.len() - 1
+    }
+
+    /// Takes the player at ``index`` out of the group, pausing it
+    pub fn remove(&mut self, index: usize) -> Player {
+        let playing = self.is_playing();
+        self.pause();
+        let mut player = self.players.remove(index);
+        player.sync_member = false;
+        player.sync_requests.clear();
+        if playing {
+            self.play();
+        }
+        player
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    pub fn player_mut(&mut self, index: usize) -> &mut Player {
+        &mut self.players[index]
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.players.is_empty()
+    }
+
+    /// Plays through ``context`` instead of [`AudioContext::global`], takes effect the next time playback starts
+    pub fn set_audio_context(&mut self, context: AudioContext) {
+        self.audio_context = Some(context);
+    }
+
+    /// Makes every member, including members added later, keep time with ``clock``
+    pub fn set_clock(&mut self, clock: impl Clock + 'static) {
+        let clock: Arc<dyn Clock> = Arc::new(clock);
+        for player in &mut self.players {
+            player.set_clock(Arc::clone(&clock));
+        }
+        self.clock = Some(clock);
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.players.iter().any(Player::is_playing)
+    }
+
+    /// Starts every member that has not reached its end, all on the same sample
+    pub fn play(&mut self) {
+        self.pause();
+        let sources: Vec<_> = self
+            .players
+            .iter_mut()
+            .filter(|player| player.elapsed_time < player.total_time)
+            .map(Player::play_synced)
+            .collect();
+        if sources.is_empty() {
+            return;
+        }
+        let audio_context = self
+            .audio_context
+            .clone()
+            .unwrap_or_else(|| AudioContext::global().clone());
+        thread::spawn(move || {
+            let sources: Vec<_> = sources.into_iter().map(|source| source.build()).collect();
+            let channels = sources.iter().map(Source::channels).max().unwrap_or(2);
+            let sample_rate = sources
+                .iter()
+                .map(Source::sample_rate)
+                .max()
+                .unwrap_or(44100);
+            // Every source is added before the mixer produces its first sample, so they all start together
+            let (controller, mixer) = dynamic_mixer::mixer(channels, sample_rate);
+            for source in sources {
+                controller.add(source);
+            }
+            let playback = audio_context.play(Box::new(mixer));
+            log::debug!("Sync group stream started");
+            while !playback.is_finished() {
+                thread::sleep(Duration::from_millis(10));
+            }
+        });
+    }
+
+    pub fn pause(&mut self) {
+        for player in &mut self.players {
+            player.pause_synced();
+        }
+    }
+
+    /// Moves every member to ``time``, playback continues from there if the group was playing
+    pub fn seek(&mut self, time: Duration) {
+        let playing = self.is_playing();
+        self.pause();
+        for player in &mut self.players {
+            player.set_position(time);
+        }
+        if playing {
+            self.play();
+        }
+    }
+
+    /// Shows every member below each other and applies the controls used on any of them to the whole group
+    pub fn ui(&mut self, ui: &mut Ui) -> Response {
+        let response = ui
+            .vertical(|ui| {
+                for player in &mut self.players {
+                    player.ui(ui);
+                }
+            })
+            .response;
+        let requests: Vec<SyncRequest> = self
+            .players
+            .iter_mut()
+            .flat_map(|player| player.sync_requests.drain(..))
+            .collect();
+        for request in requests {
+            match request {
+                SyncRequest::Play => self.play(),
+                SyncRequest::Pause => self.pause(),
+                SyncRequest::Seek(time) => self.seek(time),
+            }
+        }
+        response
+    }
+}
@@ -0,0 +1,247 @@
+use eframe::egui::{pos2, Align2, Color32, FontId, Rect, Response, Sense, Shape, Stroke, Ui, Vec2};
+use std::time::Duration;
+
+use crate::{Marker, TimeFormat};
+
+/// Spacings between labelled ticks on the time ruler, in seconds
+const TICK_INTERVALS: [f32; 15] = [
+    0.1, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0, 60.0, 120.0, 300.0, 600.0, 900.0, 1800.0, 3600.0,
+];
+
+/// Everything the [`Timeline`] draws besides its own view state
+pub(crate) struct TimelineContent<'a> {
+    pub(crate) peaks: Option<&'a [f32]>,
+    pub(crate) elapsed: Duration,
+    pub(crate) total: Duration,
+    pub(crate) markers: &'a [Marker],
+    pub(crate) playing: bool,
+    pub(crate) loading_text: &'a str,
+    pub(crate) time_format: &'a TimeFormat,
+}
+
+/// Large waveform view that can be zoomed with ctrl + scroll or pinching and scrolled horizontally
+#[derive(Debug, Clone)]
+pub(crate) struct Timeline {
+    /// Seconds of audio visible across the width, ``None`` shows the whole file
+    visible: Option<f32>,
+    /// Time at the left edge in seconds
+    start: f32,
+    /// Selected region in seconds, made by dragging across the timeline
+    selection: Option<(f32, f32)>,
+    /// Time where the current selection drag started
+    drag_anchor: Option<f32>,
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self {
+            visible: None,
+            start: 0.0,
+            selection: None,
+            drag_anchor: None,
+        }
+    }
+}
+
+impl Timeline {
+    const HEIGHT: f32 = 120.0;
+
+    /// Start and end of the region selected by dragging
+    pub(crate) fn selection(&self) -> Option<(Duration, Duration)> {
+        self.selection
+            .map(|(start, end)| (Duration::from_secs_f32(start), Duration::from_secs_f32(end)))
+    }
+
+    /// Times at the left and right edge of the view as it was last drawn
+    pub(crate) fn visible_range(&self, total: Duration) -> (Duration, Duration) {
+        let total = total.as_secs_f32();
+        let visible = self.visible.unwrap_or(total).min(total);
+        (
+            Duration::from_secs_f32(self.start),
+            Duration::from_secs_f32(self.start + visible),
+        )
+    }
+
+    pub(crate) fn clear_selection(&mut self) {
+        self.selection = None;
+    }
+
+    /// Draws the timeline, returns its [`Response`] along with the time that was clicked on to seek
+    pub(crate) fn show(
+        &mut self,
+        ui: &mut Ui,
+        content: TimelineContent,
+    ) -> (Response, Option<Duration>) {
+        let total = content.total.as_secs_f32().max(0.001);
+        let (rect, response) = ui.allocate_exact_size(
+            Vec2::new(ui.available_width(), Self::HEIGHT),
+            Sense::click_and_drag(),
+        );
+
+        if response.hovered() {
+            let (zoom, scroll) = ui.input(|input| (input.zoom_delta(), input.smooth_scroll_delta));
+            let visible = self.visible.unwrap_or(total);
+            if zoom != 1.0 {
+                // Keep the time under the pointer in place while zooming
+                let anchor = response
+                    .hover_pos()
+                    .map(|pointer| (pointer.x - rect.left()) / rect.width())
+                    .unwrap_or(0.5);
+                let anchor_time = self.start + anchor * visible;
+                let zoomed = (visible / zoom).clamp(0.5_f32.min(total), total);
+                self.start = anchor_time - anchor * zoomed;
+                self.visible = Some(zoomed);
+            }
+            let pan = if scroll.x != 0.0 { scroll.x } else { scroll.y };
+            if pan != 0.0 {
+                self.start -= pan / rect.width() * visible;
+            }
+        }
+
+        let visible = self.visible.unwrap_or(total).min(total);
+        let elapsed = content.elapsed.as_secs_f32();
+        if content.playing && (elapsed < self.start || elapsed > self.start + visible) {
+            // Page along with the playhead
+            self.start = elapsed - visible * 0.1;
+        }
+        self.start = self.start.clamp(0.0, (total - visible).max(0.0));
+
+        let to_time =
+            |x: f32| (self.start + (x - rect.left()) / rect.width() * visible).clamp(0.0, total);
+
+        // Dragging selects a region, a plain click clears it and seeks
+        if let Some(pointer) = response.interact_pointer_pos() {
+            if response.drag_started() {
+                self.drag_anchor = Some(to_time(pointer.x));
+            }
+            if let Some(anchor) = self.drag_anchor.filter(|_| response.dragged()) {
+                let time = to_time(pointer.x);
+                self.selection = Some((anchor.min(time), anchor.max(time)));
+            }
+        }
+        if response.drag_stopped() {
+            self.drag_anchor = None;
+        }
+        if response.clicked() {
+            self.selection = None;
+        }
+
+        let to_x = |time: f32| rect.left() + (time - self.start) / visible * rect.width();
+        let painter = ui.painter_at(rect);
+        let visuals = ui.visuals();
+        painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+        // Waveform
+        let ruler_height = 16.0;
+        let wave_rect = Rect::from_min_max(pos2(rect.left(), rect.top() + ruler_height), rect.max);
+        if let Some(peaks) = content.peaks.filter(|peaks| !peaks.is_empty()) {
+            let stroke = Stroke::new(1.0, visuals.text_color().gamma_multiply(0.6));
+            let columns = wave_rect.width() as usize;
+            let shapes = (0..columns)
+                .filter_map(|column| {
+                    let from = self.start + column as f32 / columns as f32 * visible;
+                    let to = self.start + (column + 1) as f32 / columns as f32 * visible;
+                    let first = (from / total * peaks.len() as f32) as usize;
+                    let last = ((to / total * peaks.len() as f32).ceil() as usize)
+                        .clamp(first + 1, peaks.len());
+                    let peak = peaks
+                        .get(first..last)?
+                        .iter()
+                        .fold(0.0_f32, |peak, value| peak.max(*value));
+                    let x = wave_rect.left() + column as f32 + 0.5;
+                    let half_height = (peak * wave_rect.height() / 2.0).max(0.5);
+                    Some(Shape::line_segment(
+                        [
+                            pos2(x, wave_rect.center().y - half_height),
+                            pos2(x, wave_rect.center().y + half_height),
+                        ],
+                        stroke,
+                    ))
+                })
+                .collect();
+            painter.add(Shape::Vec(shapes));
+        } else {
+            painter.text(
+                wave_rect.center(),
+                Align2::CENTER_CENTER,
+                content.loading_text,
+                FontId::proportional(12.0),
+                visuals.weak_text_color(),
+            );
+        }
+
+        // Selection
+        if let Some((start, end)) = self.selection {
+            let selection_rect = Rect::from_x_y_ranges(
+                to_x(start)..=to_x(end).max(to_x(start) + 1.0),
+                rect.y_range(),
+            );
+            painter.rect_filled(
+                selection_rect,
+                0.0,
+                visuals.selection.bg_fill.gamma_multiply(0.4),
+            );
+        }
+
+        // Time ruler
+        let interval = TICK_INTERVALS
+            .into_iter()
+            .find(|interval| interval / visible * rect.width() >= 80.0)
+            .unwrap_or(3600.0);
+        let mut tick = (self.start / interval).floor() * interval;
+        while tick <= self.start + visible {
+            let x = to_x(tick);
+            painter.vline(
+                x,
+                rect.top()..=rect.top() + ruler_height * 0.5,
+                Stroke::new(1.0, visuals.weak_text_color()),
+            );
+            let label = if interval < 1.0 {
+                format!("{tick:.1}s")
+            } else {
+                content.time_format.format(Duration::from_secs_f32(tick))
+            };
+            painter.text(
+                pos2(x + 2.0, rect.top()),
+                Align2::LEFT_TOP,
+                label,
+                FontId::monospace(10.0),
+                visuals.weak_text_color(),
+            );
+            tick += interval;
+        }
+
+        // Markers
+        for marker in content.markers {
+            let x = to_x(marker.time.as_secs_f32());
+            if !rect.x_range().contains(x) {
+                continue;
+            }
+            painter.vline(
+                x,
+                wave_rect.y_range(),
+                Stroke::new(1.0, Color32::LIGHT_BLUE),
+            );
+            painter.text(
+                pos2(x + 2.0, wave_rect.top()),
+                Align2::LEFT_TOP,
+                &marker.label,
+                FontId::proportional(11.0),
+                Color32::LIGHT_BLUE,
+            );
+        }
+
+        // Playhead
+        painter.vline(
+            to_x(elapsed),
+            rect.y_range(),
+            Stroke::new(2.0, Color32::RED),
+        );
+
+        let seek_to = response
+            .interact_pointer_pos()
+            .filter(|_| response.clicked())
+            .map(|pointer| Duration::from_secs_f32(to_time(pointer.x)));
+        (response, seek_to)
+    }
+}
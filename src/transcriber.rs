@@ -0,0 +1,728 @@
+use futures_util::future::BoxFuture;
+use kalosm_sound::Whisper;
+#[cfg(feature = "remote-transcription")]
+use reqwest::multipart::{Form, Part};
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "remote-transcription", feature = "whisper-cpp"))]
+use std::error::Error;
+use std::{
+    fmt, fs,
+    future::Future,
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedSender;
+
+#[cfg(all(feature = "remote-transcription", feature = "streaming"))]
+use crate::remote_reader;
+use crate::{
+    media_information, DecodingOptions, InputMode, MediaType, ModelPath, TranscriptionData,
+    TranscriptionProgress, TranscriptionResources, WhisperModel,
+};
+
+/// Length of audio transcribed at a time by [`follow_playback`], short so the first words show up quickly
+const FOLLOW_STEP: Duration = Duration::from_secs(10);
+
+/// Playback position of a [`crate::player::Player`], shared with a transcriber following it
+///
+/// See [`crate::TranscriptionMode::FollowPlayback`]
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackPosition(Arc<AtomicU64>);
+
+impl PlaybackPosition {
+    pub fn get(&self) -> Duration {
+        Duration::from_nanos(self.0.load(Ordering::Acquire))
+    }
+
+    pub(crate) fn set(&self, position: Duration) {
+        self.0.store(position.as_nanos() as u64, Ordering::Release);
+    }
+}
+
+/// Turns the audio of a [`crate::player::Player`] into a transcript, set with [`crate::player::Player::set_transcriber`]
+///
+/// Players use the [`WhisperTranscriber`]. Implement this to transcribe with another model, a cloud API,
+/// or a mock in tests, the transcript UI stays the same.
+///
+/// Send a [`TranscriptionProgress::InProgress`] for every piece of the transcript as soon as it is known,
+/// [`TranscriptionProgress::Reading`] while working without new text and [`TranscriptionProgress::Finished`] at the end.
+/// A transcriber that fails sends [`TranscriptionProgress::Failed`] or drops ``progress_sender`` without sending
+/// [`TranscriptionProgress::Finished`], which a player shows as failed and a
+/// [`crate::transcription_queue::TranscriptionQueue`] reports as [`crate::transcription_queue::QueueItemState::Failed`].
+/// When ``is_timestamped`` is set the text of each piece starts with its start and end time
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{player::Player, transcriber::Transcriber, InputMode, TranscriptionData, TranscriptionProgress};
+/// use futures_util::future::BoxFuture;
+/// use std::time::Duration;
+/// use tokio::sync::mpsc::UnboundedSender;
+///
+/// struct Mock;
+///
+/// impl Transcriber for Mock {
+///     fn transcribe(
+///         &self,
+///         _file_input: InputMode,
+///         _is_timestamped: bool,
+///         progress_sender: UnboundedSender<TranscriptionProgress>,
+///     ) -> BoxFuture<'_, ()> {
+///         Box::pin(async move {
+///             let _ = progress_sender.send(TranscriptionProgress::InProgress(TranscriptionData {
+///                 text: "Hello".to_string(),
+///                 time: Duration::ZERO,
+///                 end: Duration::from_millis(500),
+///             }));
+///             let _ = progress_sender.send(TranscriptionProgress::Finished);
+///         })
+///     }
+/// }
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcriber(Mock);
+/// ```
+pub trait Transcriber: Send + Sync {
+    fn transcribe(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()>;
+
+    /// Transcribes only what lies up to ``window`` ahead of ``position``, keeping up as playback moves on or seeks
+    ///
+    /// Used by [`crate::TranscriptionMode::FollowPlayback`], pieces may be sent out of order. Should stop once the whole file
+    /// is transcribed or ``progress_sender`` is closed, [`follow_playback`] does the bookkeeping.
+    /// The default transcribes the whole file with [`Transcriber::transcribe`], for transcribers that can not start part way in
+    fn transcribe_following(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        position: PlaybackPosition,
+        window: Duration,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        let _ = (position, window);
+        self.transcribe(file_input, is_timestamped, progress_sender)
+    }
+
+    /// Transcribes ``file_input`` from ``start`` to the end, used to resume from a [`TranscriptionCheckpoint`]
+    ///
+    /// The default transcribes the whole file with [`Transcriber::transcribe`], players drop the pieces before ``start``
+    fn transcribe_from(
+        &self,
+        file_input: InputMode,
+        start: Duration,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        let _ = start;
+        self.transcribe(file_input, is_timestamped, progress_sender)
+    }
+}
+
+/// A transcript that was cut short along with where to carry on from, see [`crate::player::Player::transcription_checkpoint`]
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::transcriber::TranscriptionCheckpoint;
+///
+/// // When the app closes
+/// let player = Player::from_path("interview.mp3");
+/// player.transcription_checkpoint().save("interview.transcript.json").unwrap();
+///
+/// // The next time it starts
+/// let mut player = Player::from_path("interview.mp3");
+/// if let Ok(checkpoint) = TranscriptionCheckpoint::load("interview.transcript.json") {
+///     player.resume_transcription(checkpoint);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptionCheckpoint {
+    pub transcript: Vec<TranscriptionData>,
+    /// Everything before this has been transcribed
+    pub resume_from: Duration,
+    /// Whether the transcript was complete, resuming a finished checkpoint only restores the transcript
+    pub finished: bool,
+}
+
+impl TranscriptionCheckpoint {
+    /// Reads a checkpoint written by [`TranscriptionCheckpoint::save`]
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let contents = fs::read(path)?;
+        serde_json::from_slice(&contents).map_err(io::Error::from)
+    }
+
+    /// Writes the checkpoint as JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let contents = serde_json::to_vec_pretty(self).map_err(io::Error::from)?;
+        fs::write(path, contents)
+    }
+}
+
+/// Calls ``transcribe_range`` with the start and end of the parts of ``file_input`` that are within ``window`` of ``position``
+/// and not transcribed yet, until the whole file is transcribed or ``progress_sender`` is closed
+///
+/// Sends [`TranscriptionProgress::Finished`] once the whole file is transcribed. When the length of the file is unknown
+/// there is nothing to follow, ``transcribe_range`` is then called once with ``Duration::MAX`` as the end to transcribe all of it
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::transcriber::{follow_playback, PlaybackPosition};
+/// use egui_player::InputMode;
+/// use std::time::Duration;
+///
+/// # async fn run(position: PlaybackPosition, progress_sender: tokio::sync::mpsc::UnboundedSender<egui_player::TranscriptionProgress>) {
+/// let file_input = InputMode::FilePath("hello.mp3".to_string());
+/// follow_playback(&file_input, &position, Duration::from_secs(30), &progress_sender, |start, end| async move {
+///     // transcribe from start to end and send the words
+/// })
+/// .await;
+/// # }
+/// ```
+pub async fn follow_playback<F: Future>(
+    file_input: &InputMode,
+    position: &PlaybackPosition,
+    window: Duration,
+    progress_sender: &UnboundedSender<TranscriptionProgress>,
+    mut transcribe_range: impl FnMut(Duration, Duration) -> F,
+) {
+    let total = media_information::get_total_time(MediaType::Audio, file_input.clone());
+    if total == Duration::ZERO {
+        log::info!("The length of the file is unknown, transcribing all of it instead of following playback");
+        transcribe_range(Duration::ZERO, Duration::MAX).await;
+        let _ = progress_sender.send(TranscriptionProgress::Finished);
+        return;
+    }
+    // Transcribed ranges, sorted and merged
+    let mut done: Vec<(Duration, Duration)> = vec![];
+    while !progress_sender.is_closed() {
+        if done.first() == Some(&(Duration::ZERO, total)) {
+            log::info!("Transcription caught up with the whole file");
+            let _ = progress_sender.send(TranscriptionProgress::Finished);
+            return;
+        }
+        let now = position.get().min(total);
+        let mut start = now;
+        for (done_start, done_end) in &done {
+            if *done_start <= start && start < *done_end {
+                start = *done_end;
+            }
+        }
+        if start >= total || start >= now + window {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+            continue;
+        }
+        let next_done = done
+            .iter()
+            .map(|(done_start, _)| *done_start)
+            .filter(|done_start| *done_start > start)
+            .min()
+            .unwrap_or(total);
+        let end = (start + FOLLOW_STEP).min(next_done);
+        log::debug!("Transcribing {start:?} to {end:?} ahead of playback");
+        transcribe_range(start, end).await;
+
+        done.push((start, end));
+        done.sort_by_key(|(done_start, _)| *done_start);
+        let mut merged: Vec<(Duration, Duration)> = vec![];
+        for range in done {
+            match merged.last_mut() {
+                Some(last) if range.0 <= last.1 => last.1 = last.1.max(range.1),
+                _ => merged.push(range),
+            }
+        }
+        done = merged;
+    }
+}
+
+impl fmt::Debug for dyn Transcriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Transcriber")
+    }
+}
+
+/// Transcribes locally with Whisper through kalosm, see [`media_information::transcribe_audio`]
+#[derive(Debug, Clone)]
+pub struct WhisperTranscriber {
+    pub model_path: ModelPath,
+    pub model: WhisperModel,
+    pub resources: TranscriptionResources,
+    pub decoding: DecodingOptions,
+}
+
+impl WhisperTranscriber {
+    /// ``None`` when the model can not be loaded, the transcription then stops without [`TranscriptionProgress::Finished`]
+    async fn load_model(&self) -> Option<Whisper> {
+        media_information::load_whisper(self.model_path.clone(), self.model, self.resources).await
+    }
+}
+
+impl Transcriber for WhisperTranscriber {
+    fn transcribe(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let Some(model) = self.load_model().await else {
+                return;
+            };
+            media_information::transcribe_with_model(
+                &model,
+                file_input,
+                is_timestamped,
+                self.decoding,
+                Some(progress_sender),
+            )
+            .await;
+        })
+    }
+
+    fn transcribe_from(
+        &self,
+        file_input: InputMode,
+        start: Duration,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let Some(model) = self.load_model().await else {
+                return;
+            };
+            log::info!("Resuming transcription at {start:?}");
+            media_information::transcribe_range(
+                &model,
+                file_input,
+                start,
+                Duration::MAX,
+                is_timestamped,
+                self.decoding,
+                &Some(progress_sender.clone()),
+            )
+            .await;
+            let _ = progress_sender.send(TranscriptionProgress::Finished);
+        })
+    }
+
+    fn transcribe_following(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        position: PlaybackPosition,
+        window: Duration,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let Some(model) = self.load_model().await else {
+                return;
+            };
+            let sender = Some(progress_sender.clone());
+            follow_playback(
+                &file_input,
+                &position,
+                window,
+                &progress_sender,
+                |start, end| {
+                    media_information::transcribe_range(
+                        &model,
+                        file_input.clone(),
+                        start,
+                        end,
+                        is_timestamped,
+                        self.decoding,
+                        &sender,
+                    )
+                },
+            )
+            .await;
+        })
+    }
+}
+
+/// Transcribes by posting the audio to an OpenAI Whisper compatible HTTP endpoint, for apps that can not ship a local model
+///
+/// Any server that accepts the ``audio/transcriptions`` request of the OpenAI API and answers with ``verbose_json`` works,
+/// including self hosted ones. Enabled by the ``remote-transcription`` feature
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{player::Player, transcriber::RemoteTranscriber};
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcriber(
+///     RemoteTranscriber::new("https://api.openai.com/v1/audio/transcriptions")
+///         .api_key(std::env::var("OPENAI_API_KEY").unwrap())
+///         .model("whisper-1"),
+/// );
+/// ```
+#[cfg(feature = "remote-transcription")]
+#[derive(Debug, Clone)]
+pub struct RemoteTranscriber {
+    endpoint: String,
+    api_key: Option<String>,
+    model: String,
+    language: Option<String>,
+    decoding: DecodingOptions,
+    temperature: Option<f32>,
+    beam_size: Option<usize>,
+    client: reqwest::Client,
+}
+
+/// Part of a ``verbose_json`` transcription response
+#[cfg(feature = "remote-transcription")]
+#[derive(Debug, Deserialize)]
+struct RemoteTranscription {
+    text: String,
+    #[serde(default)]
+    segments: Vec<RemoteSegment>,
+}
+
+#[cfg(feature = "remote-transcription")]
+#[derive(Debug, Deserialize)]
+struct RemoteSegment {
+    start: f32,
+    end: f32,
+    text: String,
+    #[serde(default)]
+    no_speech_prob: f32,
+}
+
+#[cfg(feature = "remote-transcription")]
+impl RemoteTranscriber {
+    /// Posts to ``endpoint`` with the ``whisper-1`` model
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            endpoint: endpoint.into(),
+            api_key: None,
+            model: "whisper-1".to_string(),
+            language: None,
+            decoding: DecodingOptions::default(),
+            temperature: None,
+            beam_size: None,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Sent as a bearer token
+    pub fn api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.model = model.into();
+        self
+    }
+
+    /// ISO-639-1 code of the spoken language, detected by the server when not set
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Segments above ``no_speech_threshold`` are left out of the transcript
+    pub fn decoding(mut self, decoding: DecodingOptions) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    /// Randomness of the words picked by the server, ``0.0`` always picks the most likely word. Higher values can get a
+    /// transcript unstuck from repeating itself at the cost of more mistakes
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Amount of candidate transcripts the server keeps while decoding, the OpenAI API itself ignores it but several
+    /// self hosted servers use it
+    pub fn beam_size(mut self, beam_size: usize) -> Self {
+        self.beam_size = Some(beam_size);
+        self
+    }
+
+    /// Builds the multipart form of the request
+    fn form(&self, file_name: String, audio: Vec<u8>) -> Form {
+        let mut form = Form::new()
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment");
+        if let Some(language) = &self.language {
+            form = form.text("language", language.clone());
+        }
+        if let Some(temperature) = self.temperature {
+            form = form.text("temperature", temperature.to_string());
+        }
+        if let Some(beam_size) = self.beam_size {
+            form = form.text("beam_size", beam_size.to_string());
+        }
+        form.part("file", Part::bytes(audio).file_name(file_name))
+    }
+
+    async fn request(&self, file_input: InputMode) -> Result<RemoteTranscription, Box<dyn Error>> {
+        let (file_name, audio) = match file_input {
+            InputMode::FilePath(file_path) => {
+                let file_name = Path::new(&file_path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or("audio")
+                    .to_string();
+                (file_name, tokio::fs::read(&file_path).await?)
+            }
+            InputMode::Bytes(bytes) => {
+                // The server tells formats apart by the extension
+                let extension = infer::get(&bytes).map_or("mp3", |kind| kind.extension());
+                (format!("audio.{extension}"), bytes.to_vec())
+            }
+            #[cfg(feature = "streaming")]
+            InputMode::Url(url) => {
+                let extension = remote_reader::extension(&url).unwrap_or("mp3").to_string();
+                let response = self.client.get(&url).send().await?.error_for_status()?;
+                (
+                    format!("audio.{extension}"),
+                    response.bytes().await?.to_vec(),
+                )
+            }
+        };
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .multipart(self.form(file_name, audio));
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?.error_for_status()?;
+        Ok(serde_json::from_slice(&response.bytes().await?)?)
+    }
+}
+
+#[cfg(feature = "remote-transcription")]
+impl Transcriber for RemoteTranscriber {
+    fn transcribe(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let _ = progress_sender.send(TranscriptionProgress::Reading);
+            log::info!("Requesting a transcription from {}", self.endpoint);
+            match self.request(file_input).await {
+                Ok(transcription) => {
+                    let segments = if transcription.segments.is_empty() {
+                        vec![RemoteSegment {
+                            start: 0.0,
+                            end: 0.0,
+                            text: transcription.text,
+                            no_speech_prob: 0.0,
+                        }]
+                    } else {
+                        transcription.segments
+                    };
+                    for segment in segments
+                        .into_iter()
+                        .filter(|segment| !self.decoding.is_silent(segment.no_speech_prob))
+                    {
+                        // Servers are not trusted to send times that fit in a duration
+                        let time = Duration::try_from_secs_f32(segment.start).unwrap_or_default();
+                        let end = Duration::try_from_secs_f32(segment.end)
+                            .unwrap_or_default()
+                            .max(time);
+                        let _ = progress_sender.send(TranscriptionProgress::InProgress(
+                            TranscriptionData {
+                                text: media_information::transcript_text(
+                                    &segment.text,
+                                    time.as_secs_f32(),
+                                    end.as_secs_f32(),
+                                    is_timestamped,
+                                ),
+                                time,
+                                end,
+                            },
+                        ));
+                    }
+                }
+                Err(error) => {
+                    // Dropping the sender without finishing reports the failure
+                    log::error!("Transcription by {} failed: {error}", self.endpoint);
+                    return;
+                }
+            }
+            let _ = progress_sender.send(TranscriptionProgress::Finished);
+        })
+    }
+}
+
+/// Transcribes locally with [whisper.cpp](https://github.com/ggerganov/whisper.cpp) through whisper-rs, which builds on
+/// more targets than kalosm
+///
+/// Takes the ``ggml`` model files of whisper.cpp. Enabled by the ``whisper-cpp`` feature
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::{player::Player, transcriber::WhisperCppTranscriber};
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcriber(WhisperCppTranscriber::new("ggml-base.en.bin").unwrap().language("en"));
+/// ```
+#[cfg(feature = "whisper-cpp")]
+#[derive(Clone)]
+pub struct WhisperCppTranscriber {
+    context: Arc<whisper_rs::WhisperContext>,
+    language: Option<String>,
+    decoding: DecodingOptions,
+    temperature: Option<f32>,
+    beam_size: Option<usize>,
+}
+
+#[cfg(feature = "whisper-cpp")]
+impl fmt::Debug for WhisperCppTranscriber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WhisperCppTranscriber")
+            .field("language", &self.language)
+            .field("decoding", &self.decoding)
+            .field("temperature", &self.temperature)
+            .field("beam_size", &self.beam_size)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "whisper-cpp")]
+impl WhisperCppTranscriber {
+    /// Loads the ``ggml`` model file at ``model``
+    pub fn new(model: impl AsRef<Path>) -> Result<Self, whisper_rs::WhisperError> {
+        let context = whisper_rs::WhisperContext::new_with_params(
+            &model.as_ref().to_string_lossy(),
+            whisper_rs::WhisperContextParameters::default(),
+        )?;
+        Ok(Self {
+            context: Arc::new(context),
+            language: None,
+            decoding: DecodingOptions::default(),
+            temperature: None,
+            beam_size: None,
+        })
+    }
+
+    /// ISO-639-1 code of the spoken language, detected by whisper.cpp when not set
+    pub fn language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// Pieces above ``no_speech_threshold`` are left out of the transcript
+    pub fn decoding(mut self, decoding: DecodingOptions) -> Self {
+        self.decoding = decoding;
+        self
+    }
+
+    /// Randomness of the words picked, ``0.0`` always picks the most likely word
+    pub fn temperature(mut self, temperature: f32) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Decodes with a beam search that keeps ``beam_size`` candidate transcripts instead of greedily
+    pub fn beam_size(mut self, beam_size: usize) -> Self {
+        self.beam_size = Some(beam_size);
+        self
+    }
+
+    /// Runs whisper.cpp over mono 16 kHz ``samples``, returning the start and end in hundredths of a second and the text of every segment
+    fn segments(
+        &self,
+        samples: &[f32],
+    ) -> Result<Vec<(i64, i64, String)>, whisper_rs::WhisperError> {
+        let strategy = match self.beam_size {
+            Some(beam_size) => whisper_rs::SamplingStrategy::BeamSearch {
+                beam_size: beam_size as i32,
+                patience: -1.0,
+            },
+            None => whisper_rs::SamplingStrategy::Greedy { best_of: 1 },
+        };
+        let mut params = whisper_rs::FullParams::new(strategy);
+        params.set_language(Some(self.language.as_deref().unwrap_or("auto")));
+        params.set_print_progress(false);
+        params.set_print_realtime(false);
+        if let Some(temperature) = self.temperature {
+            params.set_temperature(temperature);
+        }
+        if let Some(threshold) = self.decoding.no_speech_threshold {
+            params.set_no_speech_thold(threshold);
+        }
+
+        let mut state = self.context.create_state()?;
+        state.full(params, samples)?;
+        (0..state.full_n_segments()?)
+            .map(|segment| {
+                Ok((
+                    state.full_get_segment_t0(segment)?,
+                    state.full_get_segment_t1(segment)?,
+                    state.full_get_segment_text(segment)?,
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "whisper-cpp")]
+impl Transcriber for WhisperCppTranscriber {
+    fn transcribe(
+        &self,
+        file_input: InputMode,
+        is_timestamped: bool,
+        progress_sender: UnboundedSender<TranscriptionProgress>,
+    ) -> BoxFuture<'_, ()> {
+        Box::pin(async move {
+            let _ = progress_sender.send(TranscriptionProgress::Reading);
+            let transcriber = self.clone();
+            // whisper.cpp blocks until the whole file is transcribed
+            let segments = tokio::task::spawn_blocking(move || {
+                let samples = media_information::whisper_samples(file_input)?;
+                Ok::<_, Box<dyn Error + Send + Sync>>(transcriber.segments(&samples)?)
+            })
+            .await;
+            let segments = match segments {
+                Ok(Ok(segments)) => segments,
+                Ok(Err(error)) => {
+                    // Dropping the sender without finishing reports the failure
+                    log::error!("Transcription with whisper.cpp failed: {error}");
+                    return;
+                }
+                Err(error) => {
+                    log::error!("Transcription with whisper.cpp stopped: {error}");
+                    return;
+                }
+            };
+            for (start, end, text) in segments {
+                let time = Duration::from_millis(start.max(0) as u64 * 10);
+                let end = Duration::from_millis(end.max(0) as u64 * 10).max(time);
+                let _ =
+                    progress_sender.send(TranscriptionProgress::InProgress(TranscriptionData {
+                        text: media_information::transcript_text(
+                            &text,
+                            time.as_secs_f32(),
+                            end.as_secs_f32(),
+                            is_timestamped,
+                        ),
+                        time,
+                        end,
+                    }));
+            }
+            log::info!("Transcription with whisper.cpp finished");
+            let _ = progress_sender.send(TranscriptionProgress::Finished);
+        })
+    }
+}
@@ -0,0 +1,796 @@
+use std::{ops::Range, time::Duration};
+
+use crate::{TimeFormat, TranscriptionData};
+
+/// Words masked by [`TranscriptFilter::default`]
+const BUILT_IN_WORDS: &[&str] = &[
+    "arse",
+    "arsehole",
+    "ass",
+    "asshole",
+    "bastard",
+    "bitch",
+    "bollocks",
+    "bullshit",
+    "cock",
+    "crap",
+    "cunt",
+    "damn",
+    "dick",
+    "fuck",
+    "motherfucker",
+    "piss",
+    "prick",
+    "shit",
+    "slut",
+    "twat",
+    "wanker",
+    "whore",
+];
+
+/// Endings that still count as a filtered word, so ``"fucking"`` is masked along with ``"fuck"``
+const SUFFIXES: &[&str] = &["s", "es", "ed", "er", "ers", "ing", "in"];
+
+/// Masks words in a transcript, set with [`crate::player::Player::set_transcript_filter`]
+///
+/// Matches keep their first letter and have the rest replaced, ``"fuck"`` becomes ``"f***"``.
+/// Matching ignores case and the punctuation around a word
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::transcript::TranscriptFilter;
+///
+/// let mut filter = TranscriptFilter::default();
+/// filter.add_word("heck");
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcript_filter(Some(filter));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranscriptFilter {
+    words: Vec<String>,
+    /// Character that replaces every letter of a match after the first
+    pub mask: char,
+}
+
+impl Default for TranscriptFilter {
+    /// Creates a filter with the built in list of profanity
+    fn default() -> Self {
+        let mut filter = Self::new();
+        for word in BUILT_IN_WORDS {
+            filter.add_word(word);
+        }
+        filter
+    }
+}
+
+impl TranscriptFilter {
+    /// Creates a filter without any words
+    pub fn new() -> Self {
+        Self {
+            words: vec![],
+            mask: '*',
+        }
+    }
+
+    pub fn add_word(&mut self, word: &str) {
+        let word = word.to_lowercase();
+        if !self.words.contains(&word) {
+            self.words.push(word);
+        }
+    }
+
+    pub fn remove_word(&mut self, word: &str) {
+        let word = word.to_lowercase();
+        self.words.retain(|filtered| *filtered != word);
+    }
+
+    /// Every word that is masked, in lowercase
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    /// Whether ``word`` is masked by this filter
+    pub fn is_filtered(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.words.iter().any(|filtered| {
+            word.strip_prefix(filtered.as_str())
+                .is_some_and(|rest| rest.is_empty() || SUFFIXES.contains(&rest))
+        })
+    }
+
+    /// Masks every filtered word of ``text``, leaving spacing and punctuation as they were
+    pub fn apply_text(&self, text: &str) -> String {
+        let mut filtered = String::with_capacity(text.len());
+        let mut word = String::new();
+        for character in text.chars().chain(std::iter::once(' ')) {
+            if character.is_alphanumeric() || character == '\'' {
+                word.push(character);
+                continue;
+            }
+            filtered.push_str(&self.mask_word(&word));
+            word.clear();
+            filtered.push(character);
+        }
+        // Drop the space that flushed the last word
+        filtered.pop();
+        filtered
+    }
+
+    /// Copy of ``transcript`` with [`TranscriptFilter::apply_text`] applied to every entry
+    pub fn apply(&self, transcript: &[TranscriptionData]) -> Vec<TranscriptionData> {
+        transcript
+            .iter()
+            .map(|data| TranscriptionData {
+                text: self.apply_text(&data.text),
+                ..data.clone()
+            })
+            .collect()
+    }
+
+    fn mask_word(&self, word: &str) -> String {
+        if !self.is_filtered(word) {
+            return word.to_string();
+        }
+        let mut characters = word.chars();
+        characters
+            .next()
+            .into_iter()
+            .chain(characters.map(|_| self.mask))
+            .collect()
+    }
+}
+
+/// Hesitations handled by [`TranscriptNormalization::collapse_fillers`] and [`TranscriptNormalization::remove_fillers`]
+const FILLERS: &[&str] = &[
+    "uh", "uhm", "um", "umm", "er", "erm", "ah", "hm", "hmm", "mm",
+];
+
+/// Clean up applied to a transcript before it is shown, set with [`crate::player::Player::set_transcript_normalization`]
+///
+/// Every option is off by default, so the transcript is shown as it was heard
+///
+/// ``strip_noise_tags``: Removes bracketed tags such as ``[MUSIC]`` or ``(laughs)``
+///
+/// ``collapse_fillers``: Turns runs of fillers such as ``"um, uh, um"`` into a single filler
+///
+/// ``remove_fillers``: Removes fillers altogether
+///
+/// ``sentence_case``: Capitalizes the first word of every sentence along with a lone ``"i"``
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::transcript::TranscriptNormalization;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcript_normalization(TranscriptNormalization {
+///     strip_noise_tags: true,
+///     collapse_fillers: true,
+///     sentence_case: true,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct TranscriptNormalization {
+    pub strip_noise_tags: bool,
+    pub collapse_fillers: bool,
+    pub remove_fillers: bool,
+    pub sentence_case: bool,
+}
+
+impl TranscriptNormalization {
+    /// Copy of ``transcript`` with the enabled options applied, entries left without any words are dropped
+    ///
+    /// Entries keep the spacing around them, words inside of an entry are joined by single spaces
+    pub fn apply(&self, transcript: &[TranscriptionData]) -> Vec<TranscriptionData> {
+        let mut after_filler = false;
+        let mut sentence_start = true;
+        let mut normalized = vec![];
+        for data in transcript {
+            let text = if self.strip_noise_tags {
+                strip_bracketed(&data.text)
+            } else {
+                data.text.clone()
+            };
+
+            let mut words = vec![];
+            for word in text.split_whitespace() {
+                let is_filler = FILLERS.contains(&word_core(word).to_lowercase().as_str());
+                if is_filler && (self.remove_fillers || (self.collapse_fillers && after_filler)) {
+                    continue;
+                }
+                after_filler = is_filler;
+
+                let mut word = word.to_string();
+                if self.sentence_case && word.chars().any(char::is_alphabetic) {
+                    let core = word_core(&word);
+                    if sentence_start || core == "i" || core.starts_with("i'") {
+                        word = capitalize(&word);
+                    }
+                    sentence_start = word
+                        .trim_end_matches(['"', '\'', ')', ']'])
+                        .ends_with(['.', '!', '?']);
+                }
+                words.push(word);
+            }
+            if words.is_empty() {
+                continue;
+            }
+
+            // Keep the leading space and trailing newline that separate entries
+            let leading = &text[..text.len() - text.trim_start().len()];
+            let trailing = &text[text.trim_end().len()..];
+            normalized.push(TranscriptionData {
+                text: format!("{leading}{}{trailing}", words.join(" ")),
+                ..data.clone()
+            });
+        }
+        normalized
+    }
+}
+
+/// ``word`` without the punctuation around it
+fn word_core(word: &str) -> &str {
+    word.trim_matches(|character: char| !character.is_alphanumeric() && character != '\'')
+}
+
+/// Uppercases the first letter of ``word``
+fn capitalize(word: &str) -> String {
+    let Some(index) = word.find(char::is_alphabetic) else {
+        return word.to_string();
+    };
+    let mut characters = word[index..].chars();
+    let first = characters.next().unwrap();
+    format!(
+        "{}{}{}",
+        &word[..index],
+        first.to_uppercase(),
+        characters.as_str()
+    )
+}
+
+/// Removes everything between square brackets or parentheses, including the brackets
+fn strip_bracketed(text: &str) -> String {
+    let mut depth = 0usize;
+    let mut stripped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '[' | '(' => depth += 1,
+            ']' | ')' if depth > 0 => depth -= 1,
+            _ if depth == 0 => stripped.push(character),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+/// File format written by [`subtitles`]
+///
+/// ``Srt``: SubRip, numbered cues with comma separated milliseconds
+///
+/// ``Vtt``: WebVTT, used by browsers and most video sites
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+/// Limits the cues written by [`subtitles`] stay within
+///
+/// The defaults follow common caption guidelines, two lines of at most 42 characters shown for one to seven seconds
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SubtitleOptions {
+    /// Words are wrapped onto a new line past this many characters, a longer word gets a line of its own
+    pub max_chars_per_line: usize,
+    pub max_lines_per_cue: usize,
+    /// Short cues are held on screen until this long, unless the next cue starts sooner
+    pub min_cue_duration: Duration,
+    /// Cues are ended once they would be shown for longer than this
+    pub max_cue_duration: Duration,
+}
+
+impl Default for SubtitleOptions {
+    fn default() -> Self {
+        Self {
+            max_chars_per_line: 42,
+            max_lines_per_cue: 2,
+            min_cue_duration: Duration::from_secs(1),
+            max_cue_duration: Duration::from_secs(7),
+        }
+    }
+}
+
+/// Text shown on screen between ``start`` and ``end``
+struct Cue {
+    start: Duration,
+    end: Duration,
+    lines: Vec<String>,
+}
+
+/// Writes ``transcript`` as subtitles, ``total_time`` is when the last entry stops being spoken
+///
+/// The words of an entry are spread evenly over its time, or the time until the next entry when it has no end, then grouped into cues that fit ``options``.
+/// A cue also ends after a word that ends a sentence. Use a transcript without timestamps, the timestamps would be shown as words
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::transcript::{SubtitleFormat, SubtitleOptions};
+///
+/// let player = Player::from_path("hello.mp3");
+/// // Once the transcript is finished
+/// player
+///     .export_subtitles("hello.vtt", SubtitleFormat::Vtt, SubtitleOptions::default())
+///     .unwrap();
+/// ```
+pub fn subtitles(
+    transcript: &[TranscriptionData],
+    total_time: Duration,
+    format: SubtitleFormat,
+    options: SubtitleOptions,
+) -> String {
+    let mut cues = cues(transcript, total_time, options);
+
+    // Hold short cues for longer without running into the next one
+    for index in 0..cues.len() {
+        let next_start = cues.get(index + 1).map_or(total_time, |next| next.start);
+        let cue = &mut cues[index];
+        if cue.end.saturating_sub(cue.start) < options.min_cue_duration {
+            cue.end = (cue.start + options.min_cue_duration).min(next_start.max(cue.end));
+        }
+    }
+
+    let mut output = match format {
+        SubtitleFormat::Srt => String::new(),
+        SubtitleFormat::Vtt => "WEBVTT\n\n".to_string(),
+    };
+    for (index, cue) in cues.iter().enumerate() {
+        if format == SubtitleFormat::Srt {
+            output.push_str(&format!("{}\n", index + 1));
+        }
+        output.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            cue_time(cue.start, format),
+            cue_time(cue.end, format),
+            cue.lines.join("\n")
+        ));
+    }
+    output
+}
+
+/// Groups the words of ``transcript`` into cues that fit ``options``
+fn cues(
+    transcript: &[TranscriptionData],
+    total_time: Duration,
+    options: SubtitleOptions,
+) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = vec![];
+    let mut current: Option<Cue> = None;
+    for (index, data) in transcript.iter().enumerate() {
+        let entry_end = if data.end > data.time {
+            data.end
+        } else {
+            transcript
+                .get(index + 1)
+                .map_or(total_time, |next| next.time)
+                .max(data.time)
+        };
+        let words: Vec<&str> = data.text.split_whitespace().collect();
+        let word_length = (entry_end - data.time) / words.len().max(1) as u32;
+
+        for (word_index, word) in words.iter().enumerate() {
+            let start = data.time + word_length * word_index as u32;
+            let end = start + word_length;
+
+            if let Some(cue) = &mut current {
+                let line_length = cue.lines.last().map_or(0, |line| line.chars().count());
+                let fits_line =
+                    line_length + 1 + word.chars().count() <= options.max_chars_per_line;
+                // Entries of a transcriber may overlap, so a word can end before the cue it follows started
+                let too_long = end.saturating_sub(cue.start) > options.max_cue_duration;
+                if too_long || (!fits_line && cue.lines.len() >= options.max_lines_per_cue) {
+                    cues.extend(current.take());
+                } else {
+                    if fits_line {
+                        let line = cue.lines.last_mut().unwrap();
+                        line.push(' ');
+                        line.push_str(word);
+                    } else {
+                        cue.lines.push(word.to_string());
+                    }
+                    cue.end = cue.end.max(end);
+                }
+            }
+            let cue = current.get_or_insert_with(|| Cue {
+                start,
+                end,
+                lines: vec![word.to_string()],
+            });
+
+            if word
+                .trim_end_matches(['"', '\'', ')', ']'])
+                .ends_with(['.', '!', '?'])
+            {
+                cue.end = end;
+                cues.extend(current.take());
+            }
+        }
+    }
+    cues.extend(current);
+    cues
+}
+
+/// ``time`` as ``hh:mm:ss,mmm`` for SRT or ``hh:mm:ss.mmm`` for WebVTT
+fn cue_time(time: Duration, format: SubtitleFormat) -> String {
+    let separator = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    let seconds = time.as_secs();
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+        time.subsec_millis()
+    )
+}
+
+/// Look of the transcript shown by [`crate::player::Player`], set with [`crate::player::Player::set_transcript_style`]
+///
+/// With [`crate::TranscriptionSettings::ShowTimeStamps`] every entry gets a row, with its start and end in a column in front of the text
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::player::Player;
+/// use egui_player::transcript::TranscriptStyle;
+/// use egui_player::TimeFormat;
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_transcript_style(TranscriptStyle {
+///     font_size: Some(18.0),
+///     max_height: 200.0,
+///     timestamp_format: TimeFormat::Frames(25.0),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone)]
+pub struct TranscriptStyle {
+    /// Size of the text in points, ``None`` uses the body text size of the egui style
+    pub font_size: Option<f32>,
+    /// Space between lines in points
+    pub line_spacing: f32,
+    /// Height the transcript grows to before it scrolls
+    pub max_height: f32,
+    /// How the start and end of an entry are written in the timestamp column
+    pub timestamp_format: TimeFormat,
+    /// Width of the timestamp column in points
+    pub timestamp_width: f32,
+}
+
+impl Default for TranscriptStyle {
+    fn default() -> Self {
+        Self {
+            font_size: None,
+            line_spacing: 3.0,
+            max_height: f32::INFINITY,
+            timestamp_format: TimeFormat::Auto,
+            timestamp_width: 110.0,
+        }
+    }
+}
+
+/// ``text`` without the ``"00:01-00:02: "`` prefix that timestamped transcripts are written with
+fn strip_timestamp(text: &str) -> &str {
+    match text.split_once(": ") {
+        Some((prefix, rest))
+            if !prefix.is_empty()
+                && prefix
+                    .chars()
+                    .all(|character| character.is_ascii_digit() || ":.-".contains(character)) =>
+        {
+            rest.trim_end()
+        }
+        _ => text,
+    }
+}
+
+/// A line of the transcript view, ``pieces`` indexes into [`TranscriptLayout::pieces`]
+#[derive(Debug)]
+pub(crate) struct TranscriptRow {
+    /// Set on the first row of an entry when timestamps are shown, holds the index of the entry
+    pub(crate) timestamp: Option<usize>,
+    pub(crate) pieces: Range<usize>,
+}
+
+/// The shown transcript broken into rows that fit the width of the view, so only the visible rows have to be drawn
+///
+/// Built again whenever the transcript grows or the width, font size or timestamp setting change
+#[derive(Debug)]
+pub(crate) struct TranscriptLayout {
+    /// Entries as they are shown, see [`crate::player::Player::display_transcript`]
+    pub(crate) entries: Vec<TranscriptionData>,
+    /// Words of the entries, each with the index of the entry it belongs to
+    pub(crate) pieces: Vec<(usize, String)>,
+    pub(crate) rows: Vec<TranscriptRow>,
+    key: (usize, f32, Option<f32>, bool),
+}
+
+impl TranscriptLayout {
+    /// Whether this layout was made for ``source_length`` entries of the raw transcript shown with these settings
+    pub(crate) fn is_for(
+        &self,
+        source_length: usize,
+        width: f32,
+        font_size: Option<f32>,
+        timestamped: bool,
+    ) -> bool {
+        self.key == (source_length, width, font_size, timestamped)
+    }
+
+    /// Splits ``entries`` into words and packs them into rows of at most ``width``, measuring words with ``measure``
+    ///
+    /// With ``timestamped`` every entry starts a row and rows leave room for a timestamp column of ``timestamp_width``.
+    /// With ``row_per_entry``, as for lines of lyrics, every entry starts a row without the timestamp column
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        entries: Vec<TranscriptionData>,
+        source_length: usize,
+        width: f32,
+        font_size: Option<f32>,
+        timestamped: bool,
+        row_per_entry: bool,
+        timestamp_width: f32,
+        mut measure: impl FnMut(&str) -> f32,
+    ) -> Self {
+        let text_width = if timestamped {
+            (width - timestamp_width).max(1.0)
+        } else {
+            width
+        };
+        let mut pieces: Vec<(usize, String)> = vec![];
+        let mut rows: Vec<TranscriptRow> = vec![];
+        let mut row_width = 0.0;
+        for (index, entry) in entries.iter().enumerate() {
+            let text = if timestamped {
+                strip_timestamp(&entry.text)
+            } else {
+                &entry.text
+            };
+            if timestamped || row_per_entry || rows.is_empty() {
+                rows.push(TranscriptRow {
+                    timestamp: timestamped.then_some(index),
+                    pieces: pieces.len()..pieces.len(),
+                });
+                row_width = 0.0;
+            }
+            // Keep the space in front of every word so words still read apart when drawn next to each other
+            let mut piece_starts: Vec<usize> = text
+                .char_indices()
+                .filter(|(at, character)| {
+                    *at > 0
+                        && character.is_whitespace()
+                        && !text[..*at].ends_with(char::is_whitespace)
+                })
+                .map(|(at, _)| at)
+                .collect();
+            piece_starts.insert(0, 0);
+            piece_starts.push(text.len());
+            for bounds in piece_starts.windows(2) {
+                let piece = &text[bounds[0]..bounds[1]];
+                if piece.is_empty() {
+                    continue;
+                }
+                let piece_width = measure(piece);
+                let row = rows.last_mut().unwrap();
+                if row_width + piece_width > text_width && !row.pieces.is_empty() {
+                    rows.push(TranscriptRow {
+                        timestamp: None,
+                        pieces: pieces.len()..pieces.len(),
+                    });
+                    row_width = 0.0;
+                }
+                row_width += piece_width;
+                pieces.push((index, piece.to_string()));
+                rows.last_mut().unwrap().pieces.end = pieces.len();
+            }
+        }
+        Self {
+            entries,
+            pieces,
+            rows,
+            key: (source_length, width, font_size, timestamped),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str, time: u64, end: u64) -> TranscriptionData {
+        TranscriptionData {
+            text: text.to_string(),
+            time: Duration::from_millis(time),
+            end: Duration::from_millis(end),
+        }
+    }
+
+    #[test]
+    fn words_wrap_onto_lines_and_then_onto_cues() {
+        let options = SubtitleOptions {
+            max_chars_per_line: 10,
+            max_cue_duration: Duration::from_secs(60),
+            ..Default::default()
+        };
+        let transcript = [entry("one two three four five six", 0, 6000)];
+        assert_eq!(
+            subtitles(
+                &transcript,
+                Duration::from_secs(6),
+                SubtitleFormat::Srt,
+                options
+            ),
+            "1\n00:00:00,000 --> 00:00:04,000\none two\nthree four\n\n\
+             2\n00:00:04,000 --> 00:00:06,000\nfive six\n\n"
+        );
+    }
+
+    #[test]
+    fn cues_end_once_they_would_last_too_long() {
+        let options = SubtitleOptions {
+            max_cue_duration: Duration::from_secs(2),
+            ..Default::default()
+        };
+        let transcript = [entry("a b c d e", 0, 5000)];
+        assert_eq!(
+            subtitles(
+                &transcript,
+                Duration::from_secs(5),
+                SubtitleFormat::Vtt,
+                options
+            ),
+            "WEBVTT\n\n\
+             00:00:00.000 --> 00:00:02.000\na b\n\n\
+             00:00:02.000 --> 00:00:04.000\nc d\n\n\
+             00:00:04.000 --> 00:00:05.000\ne\n\n"
+        );
+    }
+
+    #[test]
+    fn short_cues_are_held_until_the_next_one_starts() {
+        let transcript = [entry("Hi.", 0, 200), entry("There.", 500, 700)];
+        assert_eq!(
+            subtitles(
+                &transcript,
+                Duration::from_secs(10),
+                SubtitleFormat::Srt,
+                SubtitleOptions::default()
+            ),
+            "1\n00:00:00,000 --> 00:00:00,500\nHi.\n\n\
+             2\n00:00:00,500 --> 00:00:01,500\nThere.\n\n"
+        );
+    }
+
+    #[test]
+    fn overlapping_entries_share_a_cue() {
+        let cue = |transcript: &[TranscriptionData]| {
+            let cues = cues(
+                transcript,
+                Duration::from_secs(10),
+                SubtitleOptions::default(),
+            );
+            assert_eq!(cues.len(), 1);
+            (cues[0].start, cues[0].end, cues[0].lines.clone())
+        };
+        assert_eq!(
+            cue(&[entry("one two", 0, 4000), entry("three", 1000, 2000)]),
+            (
+                Duration::ZERO,
+                Duration::from_secs(4),
+                vec!["one two three".to_string()]
+            )
+        );
+        // The second word ends before the cue it is added to started
+        assert_eq!(
+            cue(&[entry("one", 2000, 3000), entry("two", 0, 1000)]),
+            (
+                Duration::from_secs(2),
+                Duration::from_secs(3),
+                vec!["one two".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn entries_without_an_end_last_until_the_next_one() {
+        let transcript = [entry("one", 0, 0), entry("two", 2000, 0)];
+        assert_eq!(
+            subtitles(
+                &transcript,
+                Duration::from_secs(3),
+                SubtitleFormat::Srt,
+                SubtitleOptions::default()
+            ),
+            "1\n00:00:00,000 --> 00:00:03,000\none two\n\n"
+        );
+    }
+
+    #[test]
+    fn filtered_words_keep_their_first_letter() {
+        let filter = TranscriptFilter::default();
+        assert_eq!(
+            filter.apply_text("What the fuck, Fucking hell! Assess it."),
+            "What the f***, F****** hell! Assess it."
+        );
+        assert_eq!(
+            filter.apply(&[entry(" damn", 1000, 2000)]),
+            vec![entry(" d***", 1000, 2000)]
+        );
+    }
+
+    #[test]
+    fn words_can_be_added_and_removed() {
+        let mut filter = TranscriptFilter::new();
+        filter.mask = '#';
+        filter.add_word("Heck");
+        filter.add_word("heck");
+        assert_eq!(filter.words(), ["heck"]);
+        assert_eq!(filter.apply_text("heck, HECKS"), "h###, H####");
+        filter.remove_word("HECK");
+        assert!(!filter.is_filtered("heck"));
+        assert!(filter.words().is_empty());
+    }
+
+    #[test]
+    fn normalization_strips_tags_collapses_fillers_and_capitalizes() {
+        let normalization = TranscriptNormalization {
+            strip_noise_tags: true,
+            collapse_fillers: true,
+            sentence_case: true,
+            ..Default::default()
+        };
+        let transcript = [
+            entry("[MUSIC] um, uh, so i think", 0, 1000),
+            entry(" it works. um great", 1000, 2000),
+            entry(" (laughs)", 2000, 3000),
+        ];
+        assert_eq!(
+            normalization.apply(&transcript),
+            vec![
+                entry(" Um, so I think", 0, 1000),
+                entry(" it works. Um great", 1000, 2000),
+            ]
+        );
+    }
+
+    #[test]
+    fn fillers_can_be_removed() {
+        let normalization = TranscriptNormalization {
+            remove_fillers: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.apply(&[
+                entry("Um, I mean, uh, yes.", 0, 1000),
+                entry(" hmm", 1000, 2000)
+            ]),
+            vec![entry("I mean, yes.", 0, 1000)]
+        );
+    }
+
+    #[test]
+    fn default_normalization_keeps_the_words() {
+        let transcript = [entry(" [MUSIC] um i", 0, 1000)];
+        assert_eq!(
+            TranscriptNormalization::default().apply(&transcript),
+            transcript
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::{
+    sync::{
+        mpsc::{self, Receiver},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use crate::{
+    media_information,
+    transcriber::{Transcriber, WhisperTranscriber},
+    worker_pool, DecodingOptions, InputMode, MediaType, ModelPath, TranscriptionData,
+    TranscriptionProgress, TranscriptionResources, WhisperModel,
+};
+
+/// Where an item of a [`TranscriptionQueue`] is at
+///
+/// ``Waiting``: Not started yet
+///
+/// ``Transcribing``: Being transcribed, words show up in the ``transcript`` of the item
+///
+/// ``Finished``: Done, the ``transcript`` of the item is complete
+///
+/// ``Failed``: The transcriber stopped without finishing, because it ran into an error or panicked. The ``transcript``
+/// of the item holds what was transcribed until then
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum QueueItemState {
+    Waiting,
+    Transcribing,
+    Finished,
+    Failed,
+}
+
+/// A file in a [`TranscriptionQueue`] along with its transcript so far
+///
+/// ``duration``: Length of the file, probed in the background and zero until the [`TranscriptionQueue::update`] after it is known
+#[derive(Debug)]
+pub struct QueueItem {
+    pub file_input: InputMode,
+    pub state: QueueItemState,
+    pub transcript: Vec<TranscriptionData>,
+    pub duration: Duration,
+    receiver: Option<UnboundedReceiver<TranscriptionProgress>>,
+    /// Where the duration arrives once it is probed
+    duration_receiver: Option<Receiver<Duration>>,
+}
+
+impl QueueItem {
+    /// How far into the file the transcript has got, from ``0.0`` to ``1.0``
+    pub fn progress(&self) -> f32 {
+        match self.state {
+            QueueItemState::Waiting => 0.0,
+            QueueItemState::Finished => 1.0,
+            QueueItemState::Transcribing | QueueItemState::Failed => {
+                let reached = self
+                    .transcript
+                    .last()
+                    .map_or(Duration::ZERO, |data| data.time);
+                (reached.as_secs_f32() / self.duration.as_secs_f32().max(f32::EPSILON)).min(1.0)
+            }
+        }
+    }
+}
+
+/// Transcribes many files one after the other, or a few at a time, with the same [`Transcriber`] as players
+///
+/// Call [`TranscriptionQueue::update`] regularly, for example every frame, or await [`TranscriptionQueue::run`] to
+/// transcribe everything without a UI. Transcription is spawned on the tokio runtime, so both have to be called from inside of one
+///
+/// # Examples
+///
+/// ``` no_run
+/// use egui_player::transcription_queue::{QueueItemState, TranscriptionQueue};
+/// use egui_player::InputMode;
+///
+/// # async fn run() {
+/// let mut queue = TranscriptionQueue::new().concurrency(2);
+/// for entry in std::fs::read_dir("recordings").unwrap() {
+///     let path = entry.unwrap().path().to_string_lossy().to_string();
+///     queue.push(InputMode::FilePath(path));
+/// }
+/// queue.run().await;
+/// for item in queue.items() {
+///     if item.state == QueueItemState::Failed {
+///         println!("Could not transcribe {:?}", item.file_input);
+///     } else {
+///         println!("{} words", item.transcript.len());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TranscriptionQueue {
+    items: Vec<QueueItem>,
+    transcriber: Arc<dyn Transcriber>,
+    concurrency: usize,
+    is_timestamped: bool,
+}
+
+impl Default for TranscriptionQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TranscriptionQueue {
+    /// Creates an empty queue that transcribes one file at a time with the [`WhisperTranscriber`]
+    pub fn new() -> Self {
+        Self {
+            items: vec![],
+            transcriber: Arc::new(WhisperTranscriber {
+                model_path: ModelPath::Default,
+                model: WhisperModel::default(),
+                resources: TranscriptionResources::default(),
+                decoding: DecodingOptions::default(),
+            }),
+            concurrency: 1,
+            is_timestamped: false,
+        }
+    }
+
+    pub fn transcriber(mut self, transcriber: impl Transcriber + 'static) -> Self {
+        self.transcriber = Arc::new(transcriber);
+        self
+    }
+
+    /// Amount of files transcribed at once, at least one. Every file running at once keeps its own model in memory
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Starts the text of every piece with its start and end time
+    pub fn timestamped(mut self, is_timestamped: bool) -> Self {
+        self.is_timestamped = is_timestamped;
+        self
+    }
+
+    /// Adds a file at the end of the queue and returns its index
+    pub fn push(&mut self, file_input: InputMode) -> usize {
+        let (tx_duration, rx_duration) = mpsc::channel();
+        let probed_input = file_input.clone();
+        worker_pool::spawn(move || {
+            let _ = tx_duration.send(media_information::get_total_time(
+                MediaType::Audio,
+                probed_input,
+            ));
+        });
+        self.items.push(QueueItem {
+            duration: Duration::ZERO,
+            file_input,
+            state: QueueItemState::Waiting,
+            transcript: vec![],
+            receiver: None,
+            duration_receiver: Some(rx_duration),
+        });
+        self.items.len() - 1
+    }
+
+    pub fn items(&self) -> &[QueueItem] {
+        &self.items
+    }
+
+    /// Whether every item is done, either finished or failed
+    pub fn is_finished(&self) -> bool {
+        self.items.iter().all(|item| {
+            matches!(
+                item.state,
+                QueueItemState::Finished | QueueItemState::Failed
+            )
+        })
+    }
+
+    /// Collects the words transcribed since the last call and starts waiting items while fewer than the concurrency limit are running
+    pub fn update(&mut self) {
+        for (index, item) in self.items.iter_mut().enumerate() {
+            if let Some(receiver) = &item.duration_receiver {
+                match receiver.try_recv() {
+                    Ok(duration) => {
+                        item.duration = duration;
+                        item.duration_receiver = None;
+                    }
+                    Err(mpsc::TryRecvError::Empty) => {}
+                    Err(mpsc::TryRecvError::Disconnected) => item.duration_receiver = None,
+                }
+            }
+            let Some(receiver) = &mut item.receiver else {
+                continue;
+            };
+            // Checked before draining so words sent right before the sender went away are kept
+            let closed = receiver.is_closed();
+            while let Ok(progress) = receiver.try_recv() {
+                match progress {
+                    TranscriptionProgress::InProgress(data) => item.transcript.push(data),
+                    TranscriptionProgress::Finished => item.state = QueueItemState::Finished,
+                    TranscriptionProgress::Failed => item.state = QueueItemState::Failed,
+                    TranscriptionProgress::NoProgress | TranscriptionProgress::Reading => {}
+                }
+            }
+            if matches!(
+                item.state,
+                QueueItemState::Finished | QueueItemState::Failed
+            ) {
+                item.receiver = None;
+            } else if closed {
+                // The transcriber went away without saying it was done
+                log::warn!("Transcription of item {index} of the queue failed");
+                item.state = QueueItemState::Failed;
+                item.receiver = None;
+            }
+        }
+
+        let running = self
+            .items
+            .iter()
+            .filter(|item| item.state == QueueItemState::Transcribing)
+            .count();
+        for (index, item) in self
+            .items
+            .iter_mut()
+            .enumerate()
+            .filter(|(_, item)| item.state == QueueItemState::Waiting)
+            .take(self.concurrency.saturating_sub(running))
+        {
+            log::info!("Transcribing item {index} of the queue");
+            let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+            item.receiver = Some(receiver);
+            item.state = QueueItemState::Transcribing;
+            let transcriber = Arc::clone(&self.transcriber);
+            let file_input = item.file_input.clone();
+            let is_timestamped = self.is_timestamped;
+            tokio::spawn(async move {
+                transcriber
+                    .transcribe(file_input, is_timestamped, sender)
+                    .await;
+            });
+        }
+    }
+
+    /// Transcribes every item, returns once the whole queue is finished
+    pub async fn run(&mut self) {
+        loop {
+            self.update();
+            if self.is_finished() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
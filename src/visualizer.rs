@@ -0,0 +1,112 @@
+use eframe::egui::{Color32, Pos2, Rect, Stroke, Ui};
+use std::{collections::VecDeque, fmt, time::Duration};
+
+/// A visualization drawn from the audio that is being played
+///
+/// [`Visualizer::process`] receives every block of audio once as it is played, interleaved like
+/// ``[L, R, L, R, ...]``, and [`Visualizer::paint`] is called each frame to draw the result
+///
+/// Install one with [`crate::player::Player::set_visualizer`]
+///
+/// # Examples
+///
+/// ``` no_run
+/// use eframe::egui::{Rect, Ui};
+/// use egui_player::{player::Player, visualizer::Visualizer};
+///
+/// #[derive(Default)]
+/// struct Loudest(f32);
+///
+/// impl Visualizer for Loudest {
+///     fn process(&mut self, samples: &[f32], _channels: u16, _sample_rate: u32) {
+///         self.0 = samples.iter().fold(0.0, |loudest, sample| sample.abs().max(loudest));
+///     }
+///
+///     fn paint(&mut self, ui: &mut Ui, rect: Rect) {
+///         ui.put(rect, eframe::egui::ProgressBar::new(self.0));
+///     }
+/// }
+///
+/// let mut player = Player::from_path("hello.mp3");
+/// player.set_visualizer(Loudest::default());
+/// ```
+pub trait Visualizer: Send {
+    /// Receives newly played samples
+    fn process(&mut self, samples: &[f32], channels: u16, sample_rate: u32);
+
+    /// Draws the visualization inside ``rect``
+    fn paint(&mut self, ui: &mut Ui, rect: Rect);
+
+    /// Height the player reserves for the visualization
+    fn height(&self) -> f32 {
+        60.0
+    }
+}
+
+impl fmt::Debug for dyn Visualizer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Visualizer")
+    }
+}
+
+/// Draws the last 100ms of audio as a line per channel
+#[derive(Debug, Default)]
+pub struct Oscilloscope {
+    samples: VecDeque<f32>,
+    channels: u16,
+}
+
+impl Oscilloscope {
+    /// Amount of audio shown across the width of the oscilloscope
+    pub const WINDOW: Duration = Duration::from_millis(100);
+}
+
+impl Visualizer for Oscilloscope {
+    fn process(&mut self, samples: &[f32], channels: u16, sample_rate: u32) {
+        if channels != self.channels {
+            self.samples.clear();
+            self.channels = channels;
+        }
+        let capacity =
+            (Self::WINDOW.as_secs_f32() * sample_rate as f32) as usize * channels as usize;
+        self.samples.extend(samples);
+        let overflow = self.samples.len().saturating_sub(capacity);
+        self.samples.drain(..overflow);
+    }
+
+    fn paint(&mut self, ui: &mut Ui, rect: Rect) {
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 2.0, ui.visuals().extreme_bg_color);
+        painter.hline(
+            rect.x_range(),
+            rect.center().y,
+            Stroke::new(1.0, ui.visuals().weak_text_color()),
+        );
+
+        let channels = self.channels.max(1) as usize;
+        let frames = self.samples.len() / channels;
+        if frames < 2 {
+            return;
+        }
+        // Drawing more points than there are pixels only costs time
+        let step = (frames as f32 / rect.width()).max(1.0);
+        let colors = [Color32::LIGHT_GREEN, Color32::LIGHT_BLUE];
+        for channel in 0..channels {
+            let points: Vec<Pos2> = (0..)
+                .map(|point| (point as f32 * step) as usize)
+                .take_while(|frame| *frame < frames)
+                .map(|frame| {
+                    let sample = self.samples[frame * channels + channel].clamp(-1.0, 1.0);
+                    Pos2::new(
+                        rect.left() + frame as f32 / (frames - 1) as f32 * rect.width(),
+                        rect.center().y - sample * rect.height() / 2.0,
+                    )
+                })
+                .collect();
+            painter.line(
+                points,
+                Stroke::new(1.0, colors[channel % colors.len()].gamma_multiply(0.8)),
+            );
+        }
+    }
+}
@@ -0,0 +1,54 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex, OnceLock,
+    },
+    thread,
+};
+
+/// Most workers the pool starts, even on machines with many cores, the audio threads and the ui need some of them
+const MAX_WORKERS: usize = 4;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Queue of the jobs the workers take from, started by the first job
+static JOBS: OnceLock<Sender<Job>> = OnceLock::new();
+
+/// Runs ``job`` on one of the shared worker threads, after the jobs queued before it
+///
+/// Used for the heavy reading and decoding players do in the background, such as waveforms, silence, loudness, cover
+/// art and tags, so many players created at once wait their turn instead of each starting threads of their own.
+/// Results are sent back over a channel that the player polls, like with a thread of its own
+pub(crate) fn spawn(job: impl FnOnce() + Send + 'static) {
+    let jobs = JOBS.get_or_init(start_workers);
+    let _ = jobs.send(Box::new(job));
+}
+
+/// Starts a worker for every core except one, up to [`MAX_WORKERS`]
+fn start_workers() -> Sender<Job> {
+    let (sender, receiver) = mpsc::channel::<Job>();
+    let receiver = Arc::new(Mutex::new(receiver));
+    let workers = thread::available_parallelism()
+        .map_or(2, |cores| cores.get().saturating_sub(1))
+        .clamp(1, MAX_WORKERS);
+    log::debug!("Starting {workers} background workers");
+    for _ in 0..workers {
+        let receiver = Arc::clone(&receiver);
+        thread::spawn(move || {
+            profiling::register_thread!("egui_player worker");
+            loop {
+                // The lock is let go before the job runs, so the other workers can take the next one
+                let job = receiver.lock().unwrap().recv();
+                let Ok(job) = job else {
+                    break;
+                };
+                // A job that panics drops its sender, which the player sees as disconnected, the worker stays
+                if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                    log::warn!("A background job panicked");
+                }
+            }
+        });
+    }
+    sender
+}